@@ -0,0 +1,71 @@
+use aporia_zero_core::crypto::signature::SignatureScheme;
+use aporia_zero_core::state::{Account, AccountId, ParallelExecutor, State, StateTransition};
+use aporia_zero_core::state::transaction::{Transaction, TransactionType};
+use aporia_zero_core::state::computation::Difficulty;
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::One;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const BLOCK_SIZE: u32 = 256;
+
+/// State with one funded account per sender and receiver
+fn setup_state() -> State<Bls12_381> {
+    let mut state = State::new();
+    for index in 0..BLOCK_SIZE * 2 {
+        let id = AccountId(index.to_be_bytes().to_vec());
+        let mut account = Account::new(
+            id.clone(),
+            <Bls12_381 as PairingEngine>::G1Projective::prime_subgroup_generator(),
+        );
+        account.balance = 1_000_000;
+        state.accounts.insert(id, account);
+    }
+    state
+}
+
+/// Independent transfers, each between its own pair of accounts
+fn independent_transfers() -> Vec<Transaction<Bls12_381>> {
+    let scheme = SignatureScheme::new(128).unwrap();
+    (0..BLOCK_SIZE)
+        .map(|index| {
+            let mut tx = Transaction::new(
+                TransactionType::Transfer,
+                AccountId(index.to_be_bytes().to_vec()),
+                Some(AccountId((BLOCK_SIZE + index).to_be_bytes().to_vec())),
+                10,
+                0,
+                vec![],
+            );
+            tx.sign(&scheme, &Fr::one()).unwrap();
+            tx.solve_computation(&Difficulty::default()).unwrap();
+            tx
+        })
+        .collect()
+}
+
+fn bench_block_execution(c: &mut Criterion) {
+    let transition = StateTransition::<Bls12_381>::new().unwrap();
+    let state = setup_state();
+    let transactions = independent_transfers();
+    let difficulty = Difficulty::default();
+
+    let mut group = c.benchmark_group("block_execution");
+    group.sample_size(10);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| transition.apply_block(&state, &transactions, 1, &difficulty).unwrap())
+    });
+
+    for threads in [2, 4, 8] {
+        let executor = ParallelExecutor::new(&transition, threads);
+        group.bench_with_input(BenchmarkId::new("parallel", threads), &threads, |b, _| {
+            b.iter(|| executor.apply_block(&state, &transactions, 1, &difficulty).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_block_execution);
+criterion_main!(benches);
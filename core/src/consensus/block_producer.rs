@@ -4,7 +4,11 @@ use ark_ec::PairingEngine;
 use ark_ff::Field;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use sha3::{Sha3_256, Digest};
+use crate::crypto::hash_to_curve::{self, DomainTag};
+use crate::state::{Account, AdmissionPolicy, Difficulty, State, StateTransition, Transaction};
+use crate::state::computation;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Block production management
@@ -44,6 +48,7 @@ impl<E: PairingEngine> BlockProducer<E> {
         &self,
         producer: ValidatorId,
         identity_proof: Vec<u8>,
+        receipt_root: E::Fr,
     ) -> Result<Block<E>, ConsensusError> {
         let state = self.state.read().await;
         let current_time = self.current_time()?;
@@ -56,7 +61,8 @@ impl<E: PairingEngine> BlockProducer<E> {
             height: state.height + 1,
             timestamp: current_time,
             prev_hash: state.last_block_hash,
-            hash: self.calculate_block_hash(&state)?,
+            hash: self.calculate_block_hash(&state, &receipt_root)?,
+            receipt_root,
             producer,
             identity_proof: identity_proof.into(),
             epoch_length: self.config.epoch_length,
@@ -68,6 +74,100 @@ impl<E: PairingEngine> BlockProducer<E> {
         Ok(block)
     }
 
+    /// Select pending transactions for the next block
+    ///
+    /// Transactions are admitted by the same policy block execution applies
+    /// and served highest priority first. Each sender's transactions keep
+    /// their nonce order and are charged against its credit as they are
+    /// selected, until the block reaches its maximum size.
+    ///
+    /// `difficulty` is the one block execution will check, derived with
+    /// `StateTransition::difficulty` from the loads of the preceding blocks.
+    pub async fn select_transactions(
+        &self,
+        state: &State<E>,
+        transition: &StateTransition<E>,
+        difficulty: &Difficulty,
+        pending: Vec<Transaction<E>>,
+    ) -> Vec<Transaction<E>> {
+        let block_number = self.state.read().await.height + 1;
+        let policy = transition.admission_policy();
+
+        // Queue transactions per known sender in nonce order
+        let mut queues: BTreeMap<Vec<u8>, VecDeque<Transaction<E>>> = BTreeMap::new();
+        for transaction in pending {
+            queues.entry(transaction.from.0.clone()).or_default().push_back(transaction);
+        }
+        let mut senders = Vec::new();
+        for mut queue in queues.into_values() {
+            queue.make_contiguous().sort_by_key(|transaction| transaction.nonce);
+            if let Some(account) = state.get_account(&queue[0].from) {
+                senders.push((account, queue));
+            }
+        }
+
+        // Order senders by the priority of their next admitted transaction
+        let mut ready = BinaryHeap::new();
+        for (index, (account, queue)) in senders.iter_mut().enumerate() {
+            if let Some(priority) = Self::next_admitted(policy, difficulty, account, queue, block_number) {
+                ready.push((priority, Reverse(index)));
+            }
+        }
+
+        let mut selected = Vec::new();
+        let mut block_size = 0;
+        while let Some((_, Reverse(index))) = ready.pop() {
+            let (account, queue) = &mut senders[index];
+            let transaction = match queue.pop_front() {
+                Some(transaction) => transaction,
+                None => continue,
+            };
+
+            // Later transactions of the sender depend on this one
+            let size = match transaction.encode() {
+                Ok(encoded) => encoded.len(),
+                Err(_) => continue,
+            };
+            if block_size + size > self.config.max_block_size {
+                continue;
+            }
+            block_size += size;
+
+            policy.charge(account, computation::computation_cost(&transaction), block_number);
+            account.nonce += 1;
+            selected.push(transaction);
+
+            if let Some(priority) = Self::next_admitted(policy, difficulty, account, queue, block_number) {
+                ready.push((priority, Reverse(index)));
+            }
+        }
+
+        selected
+    }
+
+    /// Priority of a sender's next transaction if it is admitted
+    ///
+    /// Stale nonces are dropped. A nonce gap or a rejected transaction ends
+    /// the sender's queue, since none of its later transactions can execute.
+    fn next_admitted(
+        policy: &AdmissionPolicy,
+        difficulty: &Difficulty,
+        account: &Account<E>,
+        queue: &mut VecDeque<Transaction<E>>,
+        block_number: u64,
+    ) -> Option<u64> {
+        while queue.front().map_or(false, |transaction| transaction.nonce < account.nonce) {
+            queue.pop_front();
+        }
+        let admitted = queue.front()
+            .filter(|transaction| transaction.nonce == account.nonce)
+            .and_then(|transaction| policy.admit(account, transaction, difficulty, block_number).ok());
+        if admitted.is_none() {
+            queue.clear();
+        }
+        admitted
+    }
+
     /// Verify block
     pub async fn verify_block(&self, block: &Block<E>) -> Result<(), ConsensusError> {
         // Verify block structure
@@ -83,19 +183,20 @@ impl<E: PairingEngine> BlockProducer<E> {
     }
 
     /// Calculate block hash
-    fn calculate_block_hash(&self, state: &ConsensusState<E>) -> Result<E::Fr, ConsensusError> {
-        let mut hasher = Sha3_256::new();
+    fn calculate_block_hash(
+        &self,
+        state: &ConsensusState<E>,
+        receipt_root: &E::Fr,
+    ) -> Result<E::Fr, ConsensusError> {
+        let mut data = Vec::new();
         
         // Add block components to hash
-        hasher.update(&state.height.to_le_bytes());
-        hasher.update(&state.last_block_hash.to_bytes());
-        
-        // Convert hash to field element
-        let hash = hasher.finalize();
-        let hash_fr = E::Fr::from_random_bytes(&hash)
-            .ok_or_else(|| ConsensusError::InvalidBlock("Invalid hash conversion".to_string()))?;
+        data.extend_from_slice(&state.height.to_le_bytes());
+        data.extend_from_slice(&state.last_block_hash.to_bytes());
+        data.extend_from_slice(&receipt_root.to_bytes());
         
-        Ok(hash_fr)
+        hash_to_curve::hash_to_scalar(&data, DomainTag::BLOCK_HASH)
+            .map_err(|e| ConsensusError::InvalidBlock(e.to_string()))
     }
 
     /// Verify block structure
@@ -142,7 +243,7 @@ impl<E: PairingEngine> BlockProducer<E> {
     /// Verify block hash
     async fn verify_block_hash(&self, block: &Block<E>) -> Result<(), ConsensusError> {
         let state = self.state.read().await;
-        let calculated_hash = self.calculate_block_hash(&state)?;
+        let calculated_hash = self.calculate_block_hash(&state, &block.receipt_root)?;
         
         if block.hash != calculated_hash {
             return Err(ConsensusError::InvalidBlock(
@@ -86,7 +86,7 @@ async fn test_block_production() {
     // Create block
     let block = consensus
         .block_producer
-        .create_block(validator.id.clone(), vec![1, 2, 3])
+        .create_block(validator.id.clone(), vec![1, 2, 3], ark_bls12_381::Fr::zero())
         .await
         .unwrap();
 
@@ -94,6 +94,63 @@ async fn test_block_production() {
     assert!(consensus.process_block(block).await.is_ok());
 }
 
+#[tokio::test]
+async fn test_transaction_selection() {
+    use crate::state::{Account, AccountId, AdmissionPolicy, State, StateTransition, Transaction, TransactionType};
+    use ark_ec::ProjectiveCurve;
+
+    let config = setup::create_test_config();
+    let producer = block_producer::BlockProducer::<Bls12_381>::new(
+        config,
+        Arc::new(RwLock::new(ConsensusState::new())),
+    );
+    // Ignore proof work so priority follows stake alone
+    let transition = StateTransition::<Bls12_381>::new()
+        .unwrap()
+        .with_admission_policy(AdmissionPolicy {
+            work_weight: 0,
+            ..AdmissionPolicy::default()
+        });
+
+    let mut state = State::new();
+    for (id, balance) in [(1u8, 0u64), (2, 1_000_000)] {
+        let mut account = Account::new(
+            AccountId(vec![id]),
+            <Bls12_381 as PairingEngine>::G1Projective::prime_subgroup_generator(),
+        );
+        account.balance = balance;
+        state.set_account(account);
+    }
+    // No blocks recorded yet
+    let difficulty = transition.difficulty(&[]);
+
+    let transfer = |from: u8, nonce: u64| {
+        let mut tx = Transaction::new(
+            TransactionType::Transfer,
+            AccountId(vec![from]),
+            Some(AccountId(vec![9])),
+            10,
+            nonce,
+            vec![],
+        );
+        tx.solve_computation(&difficulty).unwrap();
+        tx
+    };
+
+    // Out of order nonces, a nonce gap and an unknown sender
+    let pending = vec![
+        transfer(1, 1),
+        transfer(1, 0),
+        transfer(1, 3),
+        transfer(2, 0),
+        transfer(3, 0),
+    ];
+    let selected = producer.select_transactions(&state, &transition, &difficulty, pending).await;
+
+    let order: Vec<_> = selected.iter().map(|tx| (tx.from.0[0], tx.nonce)).collect();
+    assert_eq!(order, vec![(2, 0), (1, 0), (1, 1)]);
+}
+
 #[tokio::test]
 async fn test_validator_selection() {
     let config = setup::create_test_config();
@@ -148,7 +205,7 @@ async fn test_voting_process() {
     // Create block
     let block = consensus
         .block_producer
-        .create_block(validator.id.clone(), vec![1, 2, 3])
+        .create_block(validator.id.clone(), vec![1, 2, 3], ark_bls12_381::Fr::zero())
         .await
         .unwrap();
 
@@ -197,7 +254,7 @@ async fn test_consensus_full_cycle() {
         let validator = validators.get(i % validators.len()).unwrap();
         let block = consensus
             .block_producer
-            .create_block(validator.id.clone(), vec![1, 2, 3])
+            .create_block(validator.id.clone(), vec![1, 2, 3], ark_bls12_381::Fr::zero())
             .await
             .unwrap();
 
@@ -101,6 +101,9 @@ pub struct Block<E: PairingEngine> {
     /// Block hash
     pub hash: E::Fr,
     
+    /// Root of the block's transaction receipts
+    pub receipt_root: E::Fr,
+    
     /// Block producer
     pub producer: ValidatorId,
     
@@ -0,0 +1,308 @@
+use super::CryptoError;
+use super::hash_to_curve::{self, DomainTag};
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use rand::Rng;
+use std::collections::HashMap;
+use std::ops::{Add, Sub};
+
+/// Largest table, in bits of the message range
+pub const MAX_TABLE_BITS: u32 = 48;
+
+/// Exponential ElGamal ciphertext `(r·G, r·P + m·G)`
+///
+/// Ciphertexts under the same key add to an encryption of the sum of their
+/// messages, and scale to an encryption of the scaled message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElGamalCiphertext<E: PairingEngine> {
+    /// Randomness commitment `r·G`
+    pub c1: E::G1Projective,
+
+    /// Masked message `r·P + m·G`
+    pub c2: E::G1Projective,
+}
+
+impl<E: PairingEngine> ElGamalCiphertext<E> {
+    /// Encrypt `value` to `public_key`
+    pub fn encrypt<R: Rng>(value: E::Fr, public_key: &E::G1Projective, rng: &mut R) -> Self {
+        Self::encrypt_with_randomness(value, public_key, &E::Fr::rand(rng))
+    }
+
+    /// Encrypt `value` to `public_key` with chosen randomness
+    pub fn encrypt_with_randomness(value: E::Fr, public_key: &E::G1Projective, randomness: &E::Fr) -> Self {
+        let g = E::G1Projective::prime_subgroup_generator();
+        Self {
+            c1: g.mul(randomness.into_repr()),
+            c2: public_key.mul(randomness.into_repr()) + g.mul(value.into_repr()),
+        }
+    }
+
+    /// Encryption of the message times `scalar`
+    pub fn scale(&self, scalar: &E::Fr) -> Self {
+        Self {
+            c1: self.c1.mul(scalar.into_repr()),
+            c2: self.c2.mul(scalar.into_repr()),
+        }
+    }
+
+    /// Fresh encryption of the same message, unlinkable to this one
+    pub fn rerandomize<R: Rng>(&self, public_key: &E::G1Projective, rng: &mut R) -> Self {
+        *self + Self::encrypt(E::Fr::zero(), public_key, rng)
+    }
+
+    /// Message as the point `m·G`
+    pub fn decrypt_point(&self, secret_key: &E::Fr) -> E::G1Projective {
+        self.c2 - self.c1.mul(secret_key.into_repr())
+    }
+
+    /// Decrypt a message within the range of `table`
+    pub fn decrypt(&self, secret_key: &E::Fr, table: &BsgsTable<E>) -> Result<u64, CryptoError> {
+        table.solve(&self.decrypt_point(secret_key))
+            .ok_or_else(|| CryptoError::EncryptionError("Message is outside the table range".to_string()))
+    }
+
+    /// Decrypt and prove the result is correct for the key pair
+    pub fn decrypt_with_proof<R: Rng>(
+        &self,
+        secret_key: &E::Fr,
+        table: &BsgsTable<E>,
+        rng: &mut R,
+    ) -> Result<(u64, DecryptionProof<E>), CryptoError> {
+        let value = self.decrypt(secret_key, table)?;
+        let proof = DecryptionProof::prove(self, secret_key, value, rng)?;
+        Ok((value, proof))
+    }
+}
+
+impl<E: PairingEngine> Add for ElGamalCiphertext<E> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            c1: self.c1 + other.c1,
+            c2: self.c2 + other.c2,
+        }
+    }
+}
+
+impl<E: PairingEngine> Sub for ElGamalCiphertext<E> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            c1: self.c1 - other.c1,
+            c2: self.c2 - other.c2,
+        }
+    }
+}
+
+/// Precomputed baby steps for discrete logarithms of small messages
+///
+/// Solves `m·G` for `m < 2^bits` with at most `2^(bits/2)` point additions,
+/// using a table of `2^(bits/2)` points that can be shared across decryptions.
+pub struct BsgsTable<E: PairingEngine> {
+    /// Baby step `j·G` by compressed encoding
+    baby_steps: HashMap<Vec<u8>, u64>,
+
+    /// Number of baby steps
+    step: u64,
+
+    /// Giant step `-step·G`
+    giant_step: E::G1Projective,
+
+    /// Number of giant steps
+    giant_steps: u64,
+}
+
+impl<E: PairingEngine> BsgsTable<E> {
+    /// Precompute table for messages below `2^bits`
+    pub fn new(bits: u32) -> Result<Self, CryptoError> {
+        if bits == 0 || bits > MAX_TABLE_BITS {
+            return Err(CryptoError::ParameterError(format!(
+                "Table must cover between 1 and {} bits", MAX_TABLE_BITS
+            )));
+        }
+
+        let step = 1u64 << ((bits + 1) / 2);
+        let giant_steps = 1u64 << (bits / 2);
+
+        let g = E::G1Projective::prime_subgroup_generator();
+        let mut points = Vec::with_capacity(step as usize);
+        let mut point = E::G1Projective::zero();
+        for _ in 0..step {
+            points.push(point);
+            point += g;
+        }
+
+        let mut baby_steps = HashMap::with_capacity(step as usize);
+        for (j, point) in E::G1Projective::batch_normalization_into_affine(&points).iter().enumerate() {
+            let mut key = Vec::new();
+            point.serialize(&mut key)
+                .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
+            baby_steps.insert(key, j as u64);
+        }
+
+        Ok(Self {
+            baby_steps,
+            step,
+            giant_step: -point,
+            giant_steps,
+        })
+    }
+
+    /// Find `m` with `point = m·G`, if in range
+    pub fn solve(&self, point: &E::G1Projective) -> Option<u64> {
+        let mut current = *point;
+        for i in 0..self.giant_steps {
+            let mut key = Vec::new();
+            current.into_affine().serialize(&mut key).ok()?;
+            if let Some(j) = self.baby_steps.get(&key) {
+                return Some(i * self.step + j);
+            }
+            current += self.giant_step;
+        }
+        None
+    }
+}
+
+/// Proof that a ciphertext decrypts to a value under a public key
+///
+/// Chaum-Pedersen proof that `P = x·G` and `c2 - m·G = x·c1` share `x`,
+/// made non-interactive with a hash challenge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecryptionProof<E: PairingEngine> {
+    /// Commitment `k·G`
+    pub a: E::G1Projective,
+
+    /// Commitment `k·c1`
+    pub b: E::G1Projective,
+
+    /// Response `k + e·x`
+    pub z: E::Fr,
+}
+
+impl<E: PairingEngine> DecryptionProof<E> {
+    /// Prove `ciphertext` decrypts to `value` under `secret_key`
+    pub fn prove<R: Rng>(
+        ciphertext: &ElGamalCiphertext<E>,
+        secret_key: &E::Fr,
+        value: u64,
+        rng: &mut R,
+    ) -> Result<Self, CryptoError> {
+        let g = E::G1Projective::prime_subgroup_generator();
+        let public_key = g.mul(secret_key.into_repr());
+
+        let k = E::Fr::rand(rng);
+        let a = g.mul(k.into_repr());
+        let b = ciphertext.c1.mul(k.into_repr());
+        let e = Self::challenge(ciphertext, &public_key, value, &a, &b)?;
+
+        Ok(Self {
+            a,
+            b,
+            z: k + e * secret_key,
+        })
+    }
+
+    /// Verify `ciphertext` decrypts to `value` under `public_key`
+    pub fn verify(
+        &self,
+        ciphertext: &ElGamalCiphertext<E>,
+        public_key: &E::G1Projective,
+        value: u64,
+    ) -> Result<bool, CryptoError> {
+        let g = E::G1Projective::prime_subgroup_generator();
+        let e = Self::challenge(ciphertext, public_key, value, &self.a, &self.b)?;
+        let shared = ciphertext.c2 - g.mul(E::Fr::from(value).into_repr());
+
+        Ok(g.mul(self.z.into_repr()) == self.a + public_key.mul(e.into_repr())
+            && ciphertext.c1.mul(self.z.into_repr()) == self.b + shared.mul(e.into_repr()))
+    }
+
+    /// Challenge binding the statement and commitments
+    fn challenge(
+        ciphertext: &ElGamalCiphertext<E>,
+        public_key: &E::G1Projective,
+        value: u64,
+        a: &E::G1Projective,
+        b: &E::G1Projective,
+    ) -> Result<E::Fr, CryptoError> {
+        let mut bytes = Vec::new();
+        for point in [public_key, &ciphertext.c1, &ciphertext.c2, a, b] {
+            point.into_affine().serialize(&mut bytes)
+                .map_err(|e| CryptoError::ProofError(e.to_string()))?;
+        }
+        bytes.extend_from_slice(&value.to_be_bytes());
+        hash_to_curve::hash_to_scalar(&bytes, DomainTag::ELGAMAL_PROOF)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use rand::thread_rng;
+
+    fn keypair() -> (Fr, <Bls12_381 as PairingEngine>::G1Projective) {
+        let secret = Fr::rand(&mut thread_rng());
+        let public = <Bls12_381 as PairingEngine>::G1Projective::prime_subgroup_generator()
+            .mul(secret.into_repr());
+        (secret, public)
+    }
+
+    #[test]
+    fn test_homomorphic_operations() {
+        let mut rng = thread_rng();
+        let (secret, public) = keypair();
+        let table = BsgsTable::<Bls12_381>::new(16).unwrap();
+
+        let a = ElGamalCiphertext::<Bls12_381>::encrypt(Fr::from(1200u64), &public, &mut rng);
+        let b = ElGamalCiphertext::<Bls12_381>::encrypt(Fr::from(34u64), &public, &mut rng);
+
+        assert_eq!(a.decrypt(&secret, &table).unwrap(), 1200);
+        assert_eq!((a + b).decrypt(&secret, &table).unwrap(), 1234);
+        assert_eq!((a - b).decrypt(&secret, &table).unwrap(), 1166);
+        assert_eq!(b.scale(&Fr::from(3u64)).decrypt(&secret, &table).unwrap(), 102);
+
+        let rerandomized = a.rerandomize(&public, &mut rng);
+        assert_ne!(rerandomized, a);
+        assert_eq!(rerandomized.decrypt(&secret, &table).unwrap(), 1200);
+    }
+
+    #[test]
+    fn test_table_range() {
+        let (secret, public) = keypair();
+        let table = BsgsTable::<Bls12_381>::new(9).unwrap();
+        let mut rng = thread_rng();
+
+        for value in [0u64, 1, 31, 32, 511] {
+            let ciphertext = ElGamalCiphertext::<Bls12_381>::encrypt(Fr::from(value), &public, &mut rng);
+            assert_eq!(ciphertext.decrypt(&secret, &table).unwrap(), value);
+        }
+        let ciphertext = ElGamalCiphertext::<Bls12_381>::encrypt(Fr::from(512u64), &public, &mut rng);
+        assert!(ciphertext.decrypt(&secret, &table).is_err());
+
+        assert!(BsgsTable::<Bls12_381>::new(0).is_err());
+        assert!(BsgsTable::<Bls12_381>::new(MAX_TABLE_BITS + 1).is_err());
+    }
+
+    #[test]
+    fn test_decryption_proof() {
+        let mut rng = thread_rng();
+        let (secret, public) = keypair();
+        let table = BsgsTable::<Bls12_381>::new(8).unwrap();
+
+        let ciphertext = ElGamalCiphertext::<Bls12_381>::encrypt(Fr::from(42u64), &public, &mut rng);
+        let (value, proof) = ciphertext.decrypt_with_proof(&secret, &table, &mut rng).unwrap();
+        assert_eq!(value, 42);
+        assert!(proof.verify(&ciphertext, &public, 42).unwrap());
+
+        // Wrong value, key or ciphertext
+        assert!(!proof.verify(&ciphertext, &public, 43).unwrap());
+        let (_, other_public) = keypair();
+        assert!(!proof.verify(&ciphertext, &other_public, 42).unwrap());
+        let other = ciphertext.rerandomize(&public, &mut rng);
+        assert!(!proof.verify(&other, &public, 42).unwrap());
+    }
+}
@@ -1,23 +1,109 @@
 use super::CryptoError;
-use ark_ec::PairingEngine;
-use ark_ff::Field;
+use super::elgamal::{BsgsTable, ElGamalCiphertext};
+use aes_gcm::Aes256Gcm;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use chacha20poly1305::ChaCha20Poly1305;
+use hkdf::Hkdf;
 use rand::Rng;
-use sha3::{Sha3_256, Digest};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// Version of the `EncryptedData` encoding
+pub const ENCRYPTION_VERSION: u8 = 1;
+
+/// Size of the per-message HKDF salt
+pub const SALT_SIZE: usize = 32;
+
+/// Size of the AEAD nonce
+pub const NONCE_SIZE: usize = 12;
+
+/// Size of the AEAD authentication tag
+pub const TAG_SIZE: usize = 16;
+
+/// Shortest key accepted, in bytes
+pub const MIN_KEY_SIZE: usize = 16;
+
+/// Version of the `Envelope` encoding
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Most recipients of one envelope
+pub const MAX_RECIPIENTS: usize = 255;
+
+/// Size of a content key wrapped for one recipient, with its tag
+pub const WRAPPED_KEY_SIZE: usize = AEAD_KEY_SIZE + TAG_SIZE;
+
+/// HKDF info prefix, followed by the cipher identifier
+const KEY_INFO: &[u8] = b"aporia-zero/encryption/v1";
+
+/// HKDF info prefix for wrapping an envelope content key
+const WRAP_INFO: &[u8] = b"aporia-zero/envelope/wrap/v1";
+
+/// HKDF info prefix for the envelope payload
+const PAYLOAD_INFO: &[u8] = b"aporia-zero/envelope/payload/v1";
+
+/// Size of the AEAD key
+const AEAD_KEY_SIZE: usize = 32;
+
+/// Encoded size before the ciphertext: version, cipher, salt and tag
+const HEADER_SIZE: usize = 2 + SALT_SIZE + TAG_SIZE;
+
+/// Authenticated encryption algorithm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cipher {
+    /// AES-256 in Galois/Counter Mode
+    Aes256Gcm,
+    /// ChaCha20 with Poly1305
+    ChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// Identifier in the encoding
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Cipher::Aes256Gcm => 1,
+            Cipher::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    /// Cipher from its identifier
+    pub fn from_byte(byte: u8) -> Result<Self, CryptoError> {
+        match byte {
+            1 => Ok(Cipher::Aes256Gcm),
+            2 => Ok(Cipher::ChaCha20Poly1305),
+            _ => Err(CryptoError::EncryptionError(format!("Unknown cipher {}", byte))),
+        }
+    }
+}
 
 /// Encryption scheme for secure data storage and transmission
+///
+/// Every message is encrypted under its own key and nonce, derived with
+/// HKDF-SHA256 from the caller's key and a random salt. Callers never choose
+/// nonces, and a repeated nonce needs a repeated 256-bit salt.
 pub struct EncryptionScheme<E: PairingEngine> {
     /// Security parameter
     security_level: usize,
     
     /// Symmetric key size in bytes
     key_size: usize,
+    
+    /// Cipher used for encryption
+    cipher: Cipher,
+    
+    _engine: std::marker::PhantomData<E>,
 }
 
 /// Encrypted data structure
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EncryptedData {
-    /// Initialization vector
-    pub iv: Vec<u8>,
+    /// Cipher used
+    pub cipher: Cipher,
+    
+    /// Salt the message key and nonce are derived from
+    pub salt: Vec<u8>,
     
     /// Encrypted content
     pub ciphertext: Vec<u8>,
@@ -26,6 +112,144 @@ pub struct EncryptedData {
     pub tag: Vec<u8>,
 }
 
+impl EncryptedData {
+    /// Encode as version, cipher, salt, tag and ciphertext
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.header();
+        bytes.extend_from_slice(&self.tag);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    /// Decode from `encode` output
+    pub fn decode(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(CryptoError::EncryptionError("Encrypted data is truncated".to_string()));
+        }
+        if bytes[0] != ENCRYPTION_VERSION {
+            return Err(CryptoError::EncryptionError(format!(
+                "Unsupported encryption version {}", bytes[0]
+            )));
+        }
+
+        let cipher = Cipher::from_byte(bytes[1])?;
+        let (salt, rest) = bytes[2..].split_at(SALT_SIZE);
+        let (tag, ciphertext) = rest.split_at(TAG_SIZE);
+        Ok(Self {
+            cipher,
+            salt: salt.to_vec(),
+            ciphertext: ciphertext.to_vec(),
+            tag: tag.to_vec(),
+        })
+    }
+
+    /// Version, cipher and salt, authenticated along with the caller's data
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(2 + self.salt.len());
+        header.push(ENCRYPTION_VERSION);
+        header.push(self.cipher.to_byte());
+        header.extend_from_slice(&self.salt);
+        header
+    }
+}
+
+/// Message encrypted to the public keys of one or more accounts
+///
+/// The payload is encrypted under a random content key, which is wrapped for
+/// each recipient with a key derived from a Diffie-Hellman exchange between
+/// one ephemeral key and the recipient key. Recipients are not named: each
+/// tries the wrapped keys in turn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope<E: PairingEngine> {
+    /// Cipher used
+    pub cipher: Cipher,
+    
+    /// Ephemeral public key
+    pub ephemeral_key: E::G1Affine,
+    
+    /// Content key wrapped for each recipient
+    pub wrapped_keys: Vec<Vec<u8>>,
+    
+    /// Encrypted payload followed by its tag
+    pub ciphertext: Vec<u8>,
+}
+
+impl<E: PairingEngine> Envelope<E> {
+    /// Encode as version, cipher, ephemeral key, wrapped keys and payload
+    pub fn encode(&self) -> Result<Vec<u8>, CryptoError> {
+        let mut bytes = self.header()?;
+        bytes.extend_from_slice(&self.ciphertext);
+        Ok(bytes)
+    }
+
+    /// Decode from `encode` output
+    pub fn decode(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let mut reader = bytes;
+
+        let version = take(&mut reader, 1)?[0];
+        if version != ENVELOPE_VERSION {
+            return Err(CryptoError::EncryptionError(format!(
+                "Unsupported envelope version {}", version
+            )));
+        }
+        let cipher = Cipher::from_byte(take(&mut reader, 1)?[0])?;
+
+        let point_size = E::G1Affine::prime_subgroup_generator().serialized_size();
+        let ephemeral_key = E::G1Affine::deserialize(take(&mut reader, point_size)?)
+            .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
+        if ephemeral_key.is_zero() {
+            return Err(CryptoError::EncryptionError("Ephemeral key is the identity".to_string()));
+        }
+
+        let count = take(&mut reader, 1)?[0] as usize;
+        if count == 0 {
+            return Err(CryptoError::EncryptionError("Envelope has no recipients".to_string()));
+        }
+        let wrapped_keys = (0..count)
+            .map(|_| take(&mut reader, WRAPPED_KEY_SIZE).map(|key| key.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The payload is the rest, at least a tag
+        if reader.len() < TAG_SIZE {
+            return Err(CryptoError::EncryptionError("Envelope is truncated".to_string()));
+        }
+        let ciphertext = reader.to_vec();
+
+        Ok(Self {
+            cipher,
+            ephemeral_key,
+            wrapped_keys,
+            ciphertext,
+        })
+    }
+
+    /// Everything before the payload, authenticated with it
+    fn header(&self) -> Result<Vec<u8>, CryptoError> {
+        if self.wrapped_keys.is_empty() || self.wrapped_keys.len() > MAX_RECIPIENTS {
+            return Err(CryptoError::EncryptionError(format!(
+                "Envelope must have between 1 and {} recipients", MAX_RECIPIENTS
+            )));
+        }
+
+        let mut header = self.prefix()?;
+        header.push(self.wrapped_keys.len() as u8);
+        for wrapped in &self.wrapped_keys {
+            if wrapped.len() != WRAPPED_KEY_SIZE {
+                return Err(CryptoError::EncryptionError("Malformed wrapped key".to_string()));
+            }
+            header.extend_from_slice(wrapped);
+        }
+        Ok(header)
+    }
+
+    /// Version, cipher and ephemeral key, authenticated with each wrapped key
+    fn prefix(&self) -> Result<Vec<u8>, CryptoError> {
+        let mut prefix = vec![ENVELOPE_VERSION, self.cipher.to_byte()];
+        prefix.extend_from_slice(&compress::<E>(&self.ephemeral_key)?);
+        Ok(prefix)
+    }
+}
+
 impl<E: PairingEngine> EncryptionScheme<E> {
     /// Create new encryption scheme
     pub fn new(security_level: usize) -> Result<Self, CryptoError> {
@@ -39,9 +263,17 @@ impl<E: PairingEngine> EncryptionScheme<E> {
         Ok(Self {
             security_level,
             key_size,
+            cipher: Cipher::Aes256Gcm,
+            _engine: std::marker::PhantomData,
         })
     }
 
+    /// Use a different cipher for encryption, decryption follows the data
+    pub fn with_cipher(mut self, cipher: Cipher) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
     /// Generate encryption key
     pub fn generate_key<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
         let mut key = vec![0u8; self.key_size];
@@ -56,27 +288,34 @@ impl<E: PairingEngine> EncryptionScheme<E> {
         key: &[u8],
         rng: &mut R,
     ) -> Result<EncryptedData, CryptoError> {
-        // Generate random IV
-        let mut iv = vec![0u8; 16];
-        rng.fill_bytes(&mut iv);
+        self.encrypt_with_aad(data, &[], key, rng)
+    }
 
-        // Derive encryption key using HKDF
-        let mut hasher = Sha3_256::new();
-        hasher.update(key);
-        hasher.update(&iv);
-        let derived_key = hasher.finalize();
+    /// Encrypt data, authenticating `aad` without encrypting it
+    pub fn encrypt_with_aad<R: Rng>(
+        &self,
+        data: &[u8],
+        aad: &[u8],
+        key: &[u8],
+        rng: &mut R,
+    ) -> Result<EncryptedData, CryptoError> {
+        let mut salt = vec![0u8; SALT_SIZE];
+        rng.fill_bytes(&mut salt);
 
-        // Encrypt data using AES-GCM
-        let ciphertext = self.aes_encrypt(data, &derived_key, &iv)?;
-        
-        // Generate authentication tag
-        let tag = self.generate_tag(data, &derived_key)?;
+        let mut encrypted = EncryptedData {
+            cipher: self.cipher,
+            salt,
+            ciphertext: Vec::new(),
+            tag: Vec::new(),
+        };
 
-        Ok(EncryptedData {
-            iv,
-            ciphertext,
-            tag,
-        })
+        let (message_key, nonce) = derive_message_key(key, &encrypted.salt, encrypted.cipher)?;
+        let aad = [encrypted.header().as_slice(), aad].concat();
+        let mut sealed = aead_seal(encrypted.cipher, &message_key, &nonce, data, &aad)?;
+
+        encrypted.tag = sealed.split_off(sealed.len() - TAG_SIZE);
+        encrypted.ciphertext = sealed;
+        Ok(encrypted)
     }
 
     /// Decrypt data
@@ -85,118 +324,423 @@ impl<E: PairingEngine> EncryptionScheme<E> {
         encrypted: &EncryptedData,
         key: &[u8],
     ) -> Result<Vec<u8>, CryptoError> {
-        // Derive decryption key
-        let mut hasher = Sha3_256::new();
-        hasher.update(key);
-        hasher.update(&encrypted.iv);
-        let derived_key = hasher.finalize();
-
-        // Verify authentication tag
-        let computed_tag = self.generate_tag(&encrypted.ciphertext, &derived_key)?;
-        if computed_tag != encrypted.tag {
-            return Err(CryptoError::EncryptionError(
-                "Invalid authentication tag".to_string()
-            ));
+        self.decrypt_with_aad(encrypted, &[], key)
+    }
+
+    /// Decrypt data encrypted with the same `aad`
+    pub fn decrypt_with_aad(
+        &self,
+        encrypted: &EncryptedData,
+        aad: &[u8],
+        key: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        if encrypted.salt.len() != SALT_SIZE || encrypted.tag.len() != TAG_SIZE {
+            return Err(CryptoError::EncryptionError("Malformed encrypted data".to_string()));
         }
 
-        // Decrypt data
-        self.aes_decrypt(&encrypted.ciphertext, &derived_key, &encrypted.iv)
+        let (message_key, nonce) = derive_message_key(key, &encrypted.salt, encrypted.cipher)?;
+        let aad = [encrypted.header().as_slice(), aad].concat();
+        let sealed = [encrypted.ciphertext.as_slice(), &encrypted.tag].concat();
+        aead_open(encrypted.cipher, &message_key, &nonce, &sealed, &aad)
     }
 
-    /// AES encryption (placeholder - would use actual AES implementation)
-    fn aes_encrypt(&self, data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
-        // This is a placeholder - in real implementation, use a proper AES library
-        let mut ciphertext = Vec::with_capacity(data.len());
-        for (i, &byte) in data.iter().enumerate() {
-            ciphertext.push(byte ^ key[i % key.len()] ^ iv[i % iv.len()]);
+    /// Encrypt data to the public keys of one or more accounts, authenticating `aad`
+    pub fn seal<R: Rng>(
+        &self,
+        data: &[u8],
+        aad: &[u8],
+        recipients: &[E::G1Projective],
+        rng: &mut R,
+    ) -> Result<Envelope<E>, CryptoError> {
+        if recipients.is_empty() || recipients.len() > MAX_RECIPIENTS {
+            return Err(CryptoError::EncryptionError(format!(
+                "Envelope must have between 1 and {} recipients", MAX_RECIPIENTS
+            )));
+        }
+        if recipients.iter().any(|recipient| recipient.is_zero()) {
+            return Err(CryptoError::KeyError("Recipient key is the identity".to_string()));
+        }
+
+        let ephemeral_secret = loop {
+            let secret = E::Fr::rand(rng);
+            if !secret.is_zero() {
+                break secret;
+            }
+        };
+        let mut envelope = Envelope {
+            cipher: self.cipher,
+            ephemeral_key: E::G1Projective::prime_subgroup_generator()
+                .mul(ephemeral_secret.into_repr())
+                .into_affine(),
+            wrapped_keys: Vec::with_capacity(recipients.len()),
+            ciphertext: Vec::new(),
+        };
+
+        // Wrap a fresh content key for every recipient
+        let mut content_key = Zeroizing::new(vec![0u8; AEAD_KEY_SIZE]);
+        rng.fill_bytes(&mut content_key);
+        let wrap_aad = [envelope.prefix()?.as_slice(), aad].concat();
+        for recipient in recipients {
+            let shared = recipient.mul(ephemeral_secret.into_repr());
+            let (wrap_key, nonce) = derive_wrap_key::<E>(
+                &shared.into_affine(),
+                &envelope.ephemeral_key,
+                &recipient.into_affine(),
+                envelope.cipher,
+            )?;
+            envelope.wrapped_keys.push(aead_seal(envelope.cipher, &wrap_key, &nonce, &content_key, &wrap_aad)?);
+        }
+
+        let (payload_key, nonce) = derive_payload_key(&content_key, envelope.cipher)?;
+        let payload_aad = [envelope.header()?.as_slice(), aad].concat();
+        envelope.ciphertext = aead_seal(envelope.cipher, &payload_key, &nonce, data, &payload_aad)?;
+        Ok(envelope)
+    }
+
+    /// Decrypt an envelope sealed to the public key of `secret_key` with the same `aad`
+    pub fn open(
+        &self,
+        envelope: &Envelope<E>,
+        aad: &[u8],
+        secret_key: &E::Fr,
+    ) -> Result<Vec<u8>, CryptoError> {
+        if envelope.ephemeral_key.is_zero() {
+            return Err(CryptoError::EncryptionError("Ephemeral key is the identity".to_string()));
         }
-        Ok(ciphertext)
+
+        let public_key = E::G1Projective::prime_subgroup_generator().mul(secret_key.into_repr());
+        let shared = envelope.ephemeral_key.mul(secret_key.into_repr());
+        let (wrap_key, nonce) = derive_wrap_key::<E>(
+            &shared.into_affine(),
+            &envelope.ephemeral_key,
+            &public_key.into_affine(),
+            envelope.cipher,
+        )?;
+
+        // Find the content key wrapped for this recipient
+        let wrap_aad = [envelope.prefix()?.as_slice(), aad].concat();
+        let content_key = envelope.wrapped_keys.iter()
+            .find_map(|wrapped| aead_open(envelope.cipher, &wrap_key, &nonce, wrapped, &wrap_aad).ok())
+            .map(Zeroizing::new)
+            .ok_or_else(|| CryptoError::EncryptionError(
+                "Envelope is not addressed to this key".to_string()
+            ))?;
+
+        let (payload_key, nonce) = derive_payload_key(&content_key, envelope.cipher)?;
+        let payload_aad = [envelope.header()?.as_slice(), aad].concat();
+        aead_open(envelope.cipher, &payload_key, &nonce, &envelope.ciphertext, &payload_aad)
     }
 
-/// AES decryption (placeholder - would use actual AES implementation)
-fn aes_decrypt(&self, data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
-    // This is a placeholder - in real implementation, use a proper AES library
-    let mut plaintext = Vec::with_capacity(data.len());
-    for (i, &byte) in data.iter().enumerate() {
-        plaintext.push(byte ^ key[i % key.len()] ^ iv[i % iv.len()]);
+    /// Homomorphic encryption for specific operations
+    pub fn homomorphic_encrypt<R: Rng>(
+        &self,
+        value: E::Fr,
+        public_key: &E::G1Projective,
+        rng: &mut R,
+    ) -> Result<ElGamalCiphertext<E>, CryptoError> {
+        // (g^r, h^r · g^m)
+        Ok(ElGamalCiphertext::encrypt(value, public_key, rng))
+    }
+
+    /// Decrypt a homomorphic ciphertext of a message within the range of `table`
+    pub fn homomorphic_decrypt(
+        &self,
+        ciphertext: &ElGamalCiphertext<E>,
+        secret_key: &E::Fr,
+        table: &BsgsTable<E>,
+    ) -> Result<u64, CryptoError> {
+        ciphertext.decrypt(secret_key, table)
     }
-    Ok(plaintext)
 }
 
-/// Generate authentication tag
-fn generate_tag(&self, data: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
-    let mut hasher = Sha3_256::new();
-    hasher.update(key);
-    hasher.update(data);
-    Ok(hasher.finalize().to_vec())
+/// HKDF-SHA256 (RFC 5869) extract and expand
+pub fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, CryptoError> {
+    let mut okm = vec![0u8; length];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(info, &mut okm)
+        .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
+    Ok(okm)
 }
 
-/// Homomorphic encryption for specific operations
-pub fn homomorphic_encrypt<R: Rng>(
-    &self,
-    value: E::Fr,
-    public_key: &E::G1Projective,
-    rng: &mut R,
-) -> Result<(E::G1Projective, E::G1Projective), CryptoError> {
-    let r = E::Fr::rand(rng);
-    let g = E::G1Projective::prime_subgroup_generator();
-    
-    // (g^r, h^r · g^m)
-    let c1 = g.mul(r.into_repr());
-    let c2 = public_key.mul(r.into_repr()) + g.mul(value.into_repr());
-    
-    Ok((c1, c2))
+/// Split `n` bytes off the front of an encoding
+fn take<'a>(reader: &mut &'a [u8], n: usize) -> Result<&'a [u8], CryptoError> {
+    if reader.len() < n {
+        return Err(CryptoError::EncryptionError("Envelope is truncated".to_string()));
+    }
+    let (head, rest) = reader.split_at(n);
+    *reader = rest;
+    Ok(head)
 }
+
+/// Encrypt with an AEAD, returning ciphertext followed by the tag
+fn aead_seal(cipher: Cipher, key: &[u8], nonce: &[u8], data: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let payload = Payload { msg: data, aad };
+    match cipher {
+        Cipher::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|e| CryptoError::EncryptionError(e.to_string()))?
+            .encrypt(nonce.into(), payload),
+        Cipher::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptoError::EncryptionError(e.to_string()))?
+            .encrypt(nonce.into(), payload),
+    }
+    .map_err(|_| CryptoError::EncryptionError("Encryption failed".to_string()))
 }
 
-#[cfg(test)]
-mod tests {
-use super::*;
-use ark_bls12_381::{Bls12_381, Fr};
-use rand::thread_rng;
-
-#[test]
-fn test_encryption_decryption() {
-    let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap();
-    let mut rng = thread_rng();
-    
-    let key = scheme.generate_key(&mut rng);
-    let data = b"test message";
-    
-    let encrypted = scheme.encrypt(data, &key, &mut rng).unwrap();
-    let decrypted = scheme.decrypt(&encrypted, &key).unwrap();
-    
-    assert_eq!(data.to_vec(), decrypted);
+/// Decrypt ciphertext followed by the tag
+fn aead_open(cipher: Cipher, key: &[u8], nonce: &[u8], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let payload = Payload { msg: sealed, aad };
+    match cipher {
+        Cipher::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|e| CryptoError::EncryptionError(e.to_string()))?
+            .decrypt(nonce.into(), payload),
+        Cipher::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|e| CryptoError::EncryptionError(e.to_string()))?
+            .decrypt(nonce.into(), payload),
+    }
+    .map_err(|_| CryptoError::EncryptionError("Invalid authentication tag".to_string()))
 }
 
-#[test]
-fn test_invalid_decryption() {
-    let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap();
-    let mut rng = thread_rng();
-    
-    let key = scheme.generate_key(&mut rng);
-    let wrong_key = scheme.generate_key(&mut rng);
-    let data = b"test message";
-    
-    let encrypted = scheme.encrypt(data, &key, &mut rng).unwrap();
-    let result = scheme.decrypt(&encrypted, &wrong_key);
-    
-    assert!(result.is_err());
+/// Derive the key and nonce of one message
+fn derive_message_key(
+    key: &[u8],
+    salt: &[u8],
+    cipher: Cipher,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), CryptoError> {
+    if key.len() < MIN_KEY_SIZE {
+        return Err(CryptoError::KeyError(format!(
+            "Key must be at least {} bytes", MIN_KEY_SIZE
+        )));
+    }
+
+    derive_key_and_nonce(key, salt, &[KEY_INFO, &[cipher.to_byte()]].concat())
+}
+
+/// Derive the key and nonce wrapping a content key for one recipient
+///
+/// Bound to both public keys, so a wrapped key cannot be moved between
+/// recipients or envelopes.
+fn derive_wrap_key<E: PairingEngine>(
+    shared: &E::G1Affine,
+    ephemeral_key: &E::G1Affine,
+    recipient: &E::G1Affine,
+    cipher: Cipher,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), CryptoError> {
+    let secret = Zeroizing::new(compress::<E>(shared)?);
+    let salt = [compress::<E>(ephemeral_key)?, compress::<E>(recipient)?].concat();
+    derive_key_and_nonce(&secret, &salt, &[WRAP_INFO, &[cipher.to_byte()]].concat())
+}
+
+/// Derive the key and nonce of an envelope payload from its content key
+fn derive_payload_key(
+    content_key: &[u8],
+    cipher: Cipher,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), CryptoError> {
+    derive_key_and_nonce(content_key, &[], &[PAYLOAD_INFO, &[cipher.to_byte()]].concat())
+}
+
+/// Expand input key material into an AEAD key and nonce
+fn derive_key_and_nonce(
+    ikm: &[u8],
+    salt: &[u8],
+    info: &[u8],
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), CryptoError> {
+    let mut okm = Zeroizing::new(hkdf_sha256(ikm, salt, info, AEAD_KEY_SIZE + NONCE_SIZE)?);
+    let nonce = okm.split_off(AEAD_KEY_SIZE);
+    Ok((okm, nonce))
+}
+
+/// Compressed encoding of a curve point
+fn compress<E: PairingEngine>(point: &E::G1Affine) -> Result<Vec<u8>, CryptoError> {
+    let mut bytes = Vec::new();
+    point.serialize(&mut bytes)
+        .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
+    Ok(bytes)
 }
 
-#[test]
-fn test_homomorphic_encryption() {
-    let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap();
-    let mut rng = thread_rng();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use rand::thread_rng;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_encryption_decryption() {
+        let mut rng = thread_rng();
+        for cipher in [Cipher::Aes256Gcm, Cipher::ChaCha20Poly1305] {
+            let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap().with_cipher(cipher);
+            let key = scheme.generate_key(&mut rng);
+            let data = b"test message";
+            
+            let encrypted = scheme.encrypt(data, &key, &mut rng).unwrap();
+            assert_eq!(encrypted.cipher, cipher);
+            assert_ne!(encrypted.ciphertext, data.to_vec());
+            let decrypted = scheme.decrypt(&encrypted, &key).unwrap();
+            assert_eq!(data.to_vec(), decrypted);
+            
+            // Fresh salt, key and nonce for every message
+            let again = scheme.encrypt(data, &key, &mut rng).unwrap();
+            assert_ne!(again.salt, encrypted.salt);
+            assert_ne!(again.ciphertext, encrypted.ciphertext);
+        }
+    }
+
+    #[test]
+    fn test_invalid_decryption() {
+        let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap();
+        let mut rng = thread_rng();
+        
+        let key = scheme.generate_key(&mut rng);
+        let wrong_key = scheme.generate_key(&mut rng);
+        let data = b"test message";
+        
+        let encrypted = scheme.encrypt(data, &key, &mut rng).unwrap();
+        let result = scheme.decrypt(&encrypted, &wrong_key);
+        
+        assert!(result.is_err());
+        assert!(scheme.decrypt(&encrypted, &key[..8]).is_err());
+    }
+
+    #[test]
+    fn test_associated_data_and_tampering() {
+        let scheme = EncryptionScheme::<Bls12_381>::new(256).unwrap();
+        let mut rng = thread_rng();
+        let key = scheme.generate_key(&mut rng);
+        
+        let encrypted = scheme.encrypt_with_aad(b"validator key", b"validator-1", &key, &mut rng).unwrap();
+        assert_eq!(
+            scheme.decrypt_with_aad(&encrypted, b"validator-1", &key).unwrap(),
+            b"validator key".to_vec()
+        );
+        assert!(scheme.decrypt_with_aad(&encrypted, b"validator-2", &key).is_err());
+        assert!(scheme.decrypt(&encrypted, &key).is_err());
+        
+        let mut tampered = encrypted.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert!(scheme.decrypt_with_aad(&tampered, b"validator-1", &key).is_err());
+        
+        // Header is authenticated, the cipher cannot be swapped
+        let mut tampered = encrypted.clone();
+        tampered.cipher = Cipher::ChaCha20Poly1305;
+        assert!(scheme.decrypt_with_aad(&tampered, b"validator-1", &key).is_err());
+        
+        let mut tampered = encrypted;
+        tampered.salt[0] ^= 1;
+        assert!(scheme.decrypt_with_aad(&tampered, b"validator-1", &key).is_err());
+    }
+
+    #[test]
+    fn test_encoding() {
+        let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap().with_cipher(Cipher::ChaCha20Poly1305);
+        let mut rng = thread_rng();
+        let key = scheme.generate_key(&mut rng);
+        
+        let encrypted = scheme.encrypt(b"test message", &key, &mut rng).unwrap();
+        let bytes = encrypted.encode();
+        assert_eq!(bytes.len(), HEADER_SIZE + 12);
+        assert_eq!(bytes[0], ENCRYPTION_VERSION);
+        
+        let decoded = EncryptedData::decode(&bytes).unwrap();
+        assert_eq!(decoded, encrypted);
+        assert_eq!(scheme.decrypt(&decoded, &key).unwrap(), b"test message".to_vec());
+        
+        let mut unsupported = bytes.clone();
+        unsupported[0] = 2;
+        assert!(EncryptedData::decode(&unsupported).is_err());
+        let mut unknown_cipher = bytes.clone();
+        unknown_cipher[1] = 9;
+        assert!(EncryptedData::decode(&unknown_cipher).is_err());
+        assert!(EncryptedData::decode(&bytes[..HEADER_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_hkdf_rfc5869_vector() {
+        // RFC 5869 test case 1
+        let okm = hkdf_sha256(
+            &[0x0b; 22],
+            &from_hex("000102030405060708090a0b0c"),
+            &from_hex("f0f1f2f3f4f5f6f7f8f9"),
+            42,
+        ).unwrap();
+        assert_eq!(
+            okm,
+            from_hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+        );
+    }
+
+    #[test]
+    fn test_envelope_multiple_recipients() {
+        let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap();
+        let mut rng = thread_rng();
+        let g = Bls12_381::G1Projective::prime_subgroup_generator();
+        
+        let secrets: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+        let recipients: Vec<_> = secrets.iter().map(|secret| g.mul(secret.into_repr())).collect();
+        
+        let envelope = scheme.seal(b"memo", b"tx", &recipients, &mut rng).unwrap();
+        for secret in &secrets {
+            assert_eq!(scheme.open(&envelope, b"tx", secret).unwrap(), b"memo".to_vec());
+        }
+        
+        // Not a recipient, or different associated data
+        assert!(scheme.open(&envelope, b"tx", &Fr::rand(&mut rng)).is_err());
+        assert!(scheme.open(&envelope, b"other", &secrets[0]).is_err());
+        
+        // Wrapped keys are bound to the envelope
+        let other = scheme.seal(b"memo", b"tx", &recipients[..1], &mut rng).unwrap();
+        let mut spliced = envelope.clone();
+        spliced.wrapped_keys[0] = other.wrapped_keys[0].clone();
+        assert!(scheme.open(&spliced, b"tx", &secrets[0]).is_err());
+        
+        assert!(scheme.seal(b"memo", b"tx", &[], &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_envelope_encoding() {
+        let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap().with_cipher(Cipher::ChaCha20Poly1305);
+        let mut rng = thread_rng();
+        let secret = Fr::rand(&mut rng);
+        let recipient = Bls12_381::G1Projective::prime_subgroup_generator().mul(secret.into_repr());
+        
+        let envelope = scheme.seal(b"memo", &[], &[recipient, recipient], &mut rng).unwrap();
+        let bytes = envelope.encode().unwrap();
+        
+        // Version, cipher, ephemeral key, count, wrapped keys, payload and tag
+        assert_eq!(bytes.len(), 2 + 48 + 1 + 2 * WRAPPED_KEY_SIZE + 4 + TAG_SIZE);
+        
+        let decoded = Envelope::<Bls12_381>::decode(&bytes).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(scheme.open(&decoded, &[], &secret).unwrap(), b"memo".to_vec());
+        
+        assert!(Envelope::<Bls12_381>::decode(&bytes[..bytes.len() - 5]).is_err());
+        assert!(Envelope::<Bls12_381>::decode(&bytes[..2 + 48 + 1 + WRAPPED_KEY_SIZE]).is_err());
+        let mut unsupported = bytes.clone();
+        unsupported[0] = 2;
+        assert!(Envelope::<Bls12_381>::decode(&unsupported).is_err());
+    }
+
+    #[test]
+    fn test_homomorphic_encryption() {
+        let scheme = EncryptionScheme::<Bls12_381>::new(128).unwrap();
+        let mut rng = thread_rng();
     
-    let secret = Fr::rand(&mut rng);
-    let g = Bls12_381::G1Projective::prime_subgroup_generator();
-    let public_key = g.mul(secret.into_repr());
+        let secret = Fr::rand(&mut rng);
+        let g = Bls12_381::G1Projective::prime_subgroup_generator();
+        let public_key = g.mul(secret.into_repr());
     
-    let value = Fr::from(42u32);
-    let (c1, c2) = scheme.homomorphic_encrypt(value, &public_key, &mut rng).unwrap();
+        let value = Fr::from(42u32);
+        let ciphertext = scheme.homomorphic_encrypt(value, &public_key, &mut rng).unwrap();
     
-    assert!(c1.is_in_correct_subgroup_assuming_on_curve());
-    assert!(c2.is_in_correct_subgroup_assuming_on_curve());
+        assert!(ciphertext.c1.into_affine().is_in_correct_subgroup_assuming_on_curve());
+        assert!(ciphertext.c2.into_affine().is_in_correct_subgroup_assuming_on_curve());
+    
+        let table = BsgsTable::new(8).unwrap();
+        assert_eq!(scheme.homomorphic_decrypt(&ciphertext, &secret, &table).unwrap(), 42);
+    }
 }
-}
\ No newline at end of file
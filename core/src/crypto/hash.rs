@@ -1,4 +1,5 @@
 use super::CryptoError;
+use super::hash_to_curve::{self, DomainTag};
 use ark_ff::Field;
 use sha3::{Sha3_256, Sha3_512, Digest};
 use blake2::{Blake2b512, Blake2s256};
@@ -41,8 +42,8 @@ pub trait HashFunction {
     /// Hash arbitrary data
     fn hash(&self, data: &[u8]) -> Result<Vec<u8>, CryptoError>;
     
-    /// Hash to field element
-    fn hash_to_field<F: Field>(&self, data: &[u8]) -> Result<F, CryptoError>;
+    /// Hash to field element under a domain tag
+    fn hash_to_field<F: Field>(&self, data: &[u8], tag: DomainTag) -> Result<F, CryptoError>;
 }
 
 /// Implementation of different hash functions
@@ -92,12 +93,8 @@ impl HashFunction for CryptoHash {
         Ok(hash)
     }
 
-    fn hash_to_field<F: Field>(&self, data: &[u8]) -> Result<F, CryptoError> {
-        let hash = self.hash(data)?;
-        
-        F::from_random_bytes(&hash).ok_or_else(|| {
-            CryptoError::HashError("Failed to convert hash to field element".to_string())
-        })
+    fn hash_to_field<F: Field>(&self, data: &[u8], tag: DomainTag) -> Result<F, CryptoError> {
+        hash_to_curve::hash_to_scalar(data, tag)
     }
 }
 
@@ -152,8 +149,9 @@ mod tests {
         let hasher = CryptoHash::new(config);
         
         let data = b"test data";
-        let field_element: Fr = hasher.hash_to_field(data).unwrap();
+        let field_element: Fr = hasher.hash_to_field(data, DomainTag::MERKLE_LEAF).unwrap();
         assert!(!field_element.is_zero());
+        assert_ne!(field_element, hasher.hash_to_field::<Fr>(data, DomainTag::MERKLE_NODE).unwrap());
     }
 
     #[test]
@@ -0,0 +1,475 @@
+use super::CryptoError;
+use ark_bls12_381::{Bls12_381, Fq, Fq2, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{field_new, BigInteger, Field, FpParameters, PrimeField, SquareRootField, Zero};
+use sha2::{Digest, Sha256};
+
+/// Output size of SHA-256
+const HASH_SIZE: usize = 32;
+
+/// Input block size of SHA-256
+const BLOCK_SIZE: usize = 64;
+
+/// Longest domain separation tag
+pub const MAX_TAG_SIZE: usize = 255;
+
+/// Target security level in bits, setting the bytes hashed per field element
+const SECURITY_BITS: usize = 128;
+
+/// Domain separation tag of a hash-to-field or hash-to-curve use
+///
+/// Every use of a hash as a field element or curve point names its purpose,
+/// so values computed for one purpose are never valid for another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DomainTag(&'static str);
+
+impl DomainTag {
+    /// Transaction signing hash
+    pub const TRANSACTION_HASH: DomainTag = DomainTag::new("APORIA-ZERO-V01-TRANSACTION-HASH");
+
+    /// Block hash
+    pub const BLOCK_HASH: DomainTag = DomainTag::new("APORIA-ZERO-V01-BLOCK-HASH");
+
+    /// Account state hash recorded in receipts
+    pub const ACCOUNT_HASH: DomainTag = DomainTag::new("APORIA-ZERO-V01-ACCOUNT-HASH");
+
+    /// Contract code hash
+    pub const CODE_HASH: DomainTag = DomainTag::new("APORIA-ZERO-V01-CODE-HASH");
+
+    /// Merkle tree leaf
+    pub const MERKLE_LEAF: DomainTag = DomainTag::new("APORIA-ZERO-V01-MERKLE-LEAF");
+
+    /// Merkle tree internal node
+    pub const MERKLE_NODE: DomainTag = DomainTag::new("APORIA-ZERO-V01-MERKLE-NODE");
+
+    /// Signature challenge
+    pub const SIGNATURE_CHALLENGE: DomainTag = DomainTag::new("APORIA-ZERO-V01-SIGNATURE-CHALLENGE");
+
+    /// Deterministic signature nonce
+    pub const SIGNATURE_NONCE: DomainTag = DomainTag::new("APORIA-ZERO-V01-SIGNATURE-NONCE");
+
+    /// ElGamal decryption proof challenge
+    pub const ELGAMAL_PROOF: DomainTag = DomainTag::new("APORIA-ZERO-V01-ELGAMAL-DECRYPTION-PROOF");
+
+    /// Key generation dealer proof challenge
+    pub const DKG_PROOF: DomainTag = DomainTag::new("APORIA-ZERO-V01-DKG-DEALER-PROOF");
+
+    /// Threshold signing binding factor
+    pub const THRESHOLD_BINDING: DomainTag = DomainTag::new("APORIA-ZERO-V01-THRESHOLD-BINDING");
+
+    /// Commitment generators with unknown discrete logarithm
+    pub const GENERATOR: DomainTag = DomainTag::new("APORIA-ZERO-V01-GENERATOR");
+
+    /// Range proof transcript challenge
+    pub const RANGE_PROOF: DomainTag = DomainTag::new("APORIA-ZERO-V01-RANGE-PROOF");
+
+    /// Generic hash to G1
+    pub const G1_POINT: DomainTag = DomainTag::new("APORIA-ZERO-V01-CS01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_");
+
+    /// Generic hash to G2
+    pub const G2_POINT: DomainTag = DomainTag::new("APORIA-ZERO-V01-CS01-with-BLS12381G2_XMD:SHA-256_SSWU_RO_");
+
+    /// Tag from 1 to 255 bytes
+    pub const fn new(tag: &'static str) -> Self {
+        assert!(!tag.is_empty() && tag.len() <= MAX_TAG_SIZE, "Domain tag must be 1 to 255 bytes");
+        Self(tag)
+    }
+
+    /// Tag bytes
+    pub fn as_bytes(&self) -> &'static [u8] {
+        self.0.as_bytes()
+    }
+}
+
+/// RFC 9380 `expand_message_xmd` with SHA-256
+pub fn expand_message_xmd(message: &[u8], tag: DomainTag, length: usize) -> Result<Vec<u8>, CryptoError> {
+    let blocks = length.div_ceil(HASH_SIZE);
+    if blocks > 255 || length > u16::MAX as usize {
+        return Err(CryptoError::HashError(format!("Cannot expand message to {} bytes", length)));
+    }
+
+    let mut tag_prime = tag.as_bytes().to_vec();
+    tag_prime.push(tag.as_bytes().len() as u8);
+
+    let b0 = Sha256::new()
+        .chain_update([0u8; BLOCK_SIZE])
+        .chain_update(message)
+        .chain_update((length as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(&tag_prime)
+        .finalize();
+
+    let mut output = Vec::with_capacity(blocks * HASH_SIZE);
+    let mut previous = Sha256::new()
+        .chain_update(b0)
+        .chain_update([1u8])
+        .chain_update(&tag_prime)
+        .finalize();
+    output.extend_from_slice(&previous);
+    for i in 2..=blocks {
+        let mixed: Vec<u8> = b0.iter().zip(previous.iter()).map(|(a, b)| a ^ b).collect();
+        previous = Sha256::new()
+            .chain_update(mixed)
+            .chain_update([i as u8])
+            .chain_update(&tag_prime)
+            .finalize();
+        output.extend_from_slice(&previous);
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
+
+/// RFC 9380 `hash_to_field`: `count` field elements from a message
+///
+/// Each base field component is reduced from `ceil((bits + 128) / 8)`
+/// uniform bytes, so the bias is at most 2^-128.
+pub fn hash_to_field<F: Field>(message: &[u8], tag: DomainTag, count: usize) -> Result<Vec<F>, CryptoError> {
+    let degree = F::extension_degree() as usize;
+    let modulus_bits = <F::BasePrimeField as PrimeField>::Params::MODULUS_BITS as usize;
+    let component_size = (modulus_bits + SECURITY_BITS).div_ceil(8);
+    let uniform = expand_message_xmd(message, tag, count * degree * component_size)?;
+
+    uniform.chunks(degree * component_size)
+        .map(|element| {
+            let components: Vec<F::BasePrimeField> = element.chunks(component_size)
+                .map(F::BasePrimeField::from_be_bytes_mod_order)
+                .collect();
+            F::from_base_prime_field_elems(&components)
+                .ok_or_else(|| CryptoError::HashError("Invalid field extension degree".to_string()))
+        })
+        .collect()
+}
+
+/// Single field element from a message
+pub fn hash_to_scalar<F: Field>(message: &[u8], tag: DomainTag) -> Result<F, CryptoError> {
+    hash_to_field(message, tag, 1).map(|mut elements| elements.remove(0))
+}
+
+/// Hash to the groups of a pairing-friendly curve
+///
+/// Implemented per curve with its RFC 9380 random oracle suites, so generic
+/// code hashes to points as `E::hash_to_g1` and `E::hash_to_g2`.
+pub trait HashToCurve: PairingEngine {
+    /// Hash to G1 under a domain tag
+    fn hash_to_g1(message: &[u8], tag: DomainTag) -> Result<Self::G1Projective, CryptoError>;
+
+    /// Hash to G2 under a domain tag
+    fn hash_to_g2(message: &[u8], tag: DomainTag) -> Result<Self::G2Projective, CryptoError>;
+}
+
+impl HashToCurve for Bls12_381 {
+    /// `BLS12381G1_XMD:SHA-256_SSWU_RO_` suite
+    fn hash_to_g1(message: &[u8], tag: DomainTag) -> Result<G1Projective, CryptoError> {
+        let point = |coordinates: Option<(Fq, Fq)>| {
+            coordinates.map_or_else(G1Projective::zero, |(x, y)| G1Affine::new(x, y, false).into_projective())
+        };
+        let u = hash_to_field::<Fq>(message, tag, 2)?;
+        let q0 = point(G1_SWU.map_to_curve(u[0])?);
+        let q1 = point(G1_SWU.map_to_curve(u[1])?);
+        Ok((q0 + q1).mul(G1_H_EFF))
+    }
+
+    /// `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite
+    fn hash_to_g2(message: &[u8], tag: DomainTag) -> Result<G2Projective, CryptoError> {
+        let point = |coordinates: Option<(Fq2, Fq2)>| {
+            coordinates.map_or_else(G2Projective::zero, |(x, y)| G2Affine::new(x, y, false).into_projective())
+        };
+        let u = hash_to_field::<Fq2>(message, tag, 2)?;
+        let q0 = point(G2_SWU.map_to_curve(u[0])?);
+        let q1 = point(G2_SWU.map_to_curve(u[1])?);
+        Ok((q0 + q1).mul(G2_H_EFF))
+    }
+}
+
+/// Sign of a field element, RFC 9380 section 4.1
+trait Sgn0 {
+    fn sgn0(&self) -> bool;
+}
+
+impl Sgn0 for Fq {
+    fn sgn0(&self) -> bool {
+        self.into_repr().is_odd()
+    }
+}
+
+impl Sgn0 for Fq2 {
+    fn sgn0(&self) -> bool {
+        self.c0.sgn0() || (self.c0.is_zero() && self.c1.sgn0())
+    }
+}
+
+/// Simplified SWU map to a curve isogenous to the target, and the isogeny back
+///
+/// The isogenous curve is `y^2 = x^3 + a x + b`. Isogeny polynomials list
+/// their coefficients from the constant term up.
+struct IsogenousSwu<F: 'static> {
+    a: F,
+    b: F,
+    z: F,
+    x_numerator: &'static [F],
+    x_denominator: &'static [F],
+    y_numerator: &'static [F],
+    y_denominator: &'static [F],
+}
+
+impl<F: SquareRootField + Sgn0> IsogenousSwu<F> {
+    /// Affine coordinates of the target curve point, `None` for the identity
+    fn map_to_curve(&self, u: F) -> Result<Option<(F, F)>, CryptoError> {
+        // Simplified SWU, RFC 9380 section 6.6.2
+        let z_u2 = self.z * u.square();
+        let tv1 = z_u2.square() + z_u2;
+        let x1 = match tv1.inverse() {
+            Some(tv1) => -self.b / self.a * (F::one() + tv1),
+            None => self.b / (self.z * self.a),
+        };
+        let (x, y) = match self.curve(x1).sqrt() {
+            Some(y) => (x1, y),
+            None => {
+                let x2 = z_u2 * x1;
+                let y = self.curve(x2).sqrt()
+                    .ok_or_else(|| CryptoError::HashError("No square root in SWU map".to_string()))?;
+                (x2, y)
+            }
+        };
+        let y = if y.sgn0() != u.sgn0() { -y } else { y };
+
+        // Isogeny to the target curve, its kernel maps to the identity
+        let x_denominator = evaluate(self.x_denominator, x);
+        let y_denominator = evaluate(self.y_denominator, x);
+        Ok(x_denominator.inverse().zip(y_denominator.inverse()).map(|(x_inverse, y_inverse)| (
+            evaluate(self.x_numerator, x) * x_inverse,
+            y * evaluate(self.y_numerator, x) * y_inverse,
+        )))
+    }
+
+    /// Right-hand side of the isogenous curve equation
+    fn curve(&self, x: F) -> F {
+        (x.square() + self.a) * x + self.b
+    }
+}
+
+/// Evaluate a polynomial given from the constant term up
+fn evaluate<F: Field>(coefficients: &[F], x: F) -> F {
+    coefficients.iter().rev().fold(F::zero(), |acc, coefficient| acc * x + coefficient)
+}
+
+/// Effective G1 cofactor `1 - z`, RFC 9380 section 8.8.1
+const G1_H_EFF: &[u64] = &[0xd201000000010001];
+
+/// Effective G2 cofactor, RFC 9380 section 8.8.2
+const G2_H_EFF: &[u64] = &[
+    0xe8020005aaa95551, 0x59894c0adebbf6b4, 0xe954cbc06689f6a3, 0x2ec0ec69d7477c1a, 0x6d82bf015d1212b0,
+    0x329c2f178731db95, 0x9986ff031508ffe1, 0x88e2a8e9145ad768, 0x584c6a0ea91b3528, 0x0bc69f08f2ee75b3,
+];
+
+/// Map to G1 through the 11-isogenous curve, RFC 9380 appendix E.2
+#[rustfmt::skip]
+const G1_SWU: IsogenousSwu<Fq> = IsogenousSwu {
+    a: field_new!(Fq, "12190336318893619529228877361869031420615612348429846051986726275283378313155663745811710833465465981901188123677"),
+    b: field_new!(Fq, "2906670324641927570491258158026293881577086121416628140204402091718288198173574630967936031029026176254968826637280"),
+    z: field_new!(Fq, "11"),
+    x_numerator: &[
+        field_new!(Fq, "2712959285290305970661081772124144179193819192423276218370281158706191519995889425075952244140278856085036081760695"),
+        field_new!(Fq, "3564859427549639835253027846704205725951033235539816243131874237388832081954622352624080767121604606753339903542203"),
+        field_new!(Fq, "2051387046688339481714726479723076305756384619135044672831882917686431912682625619320120082313093891743187631791280"),
+        field_new!(Fq, "3612713941521031012780325893181011392520079402153354595775735142359240110423346445050803899623018402874731133626465"),
+        field_new!(Fq, "2247053637822768981792833880270996398470828564809439728372634811976089874056583714987807553397615562273407692740057"),
+        field_new!(Fq, "3415427104483187489859740871640064348492611444552862448295571438270821994900526625562705192993481400731539293415811"),
+        field_new!(Fq, "2067521456483432583860405634125513059912765526223015704616050604591207046392807563217109432457129564962571408764292"),
+        field_new!(Fq, "3650721292069012982822225637849018828271936405382082649291891245623305084633066170122780668657208923883092359301262"),
+        field_new!(Fq, "1239271775787030039269460763652455868148971086016832054354147730155061349388626624328773377658494412538595239256855"),
+        field_new!(Fq, "3479374185711034293956731583912244564891370843071137483962415222733470401948838363051960066766720884717833231600798"),
+        field_new!(Fq, "2492756312273161536685660027440158956721981129429869601638362407515627529461742974364729223659746272460004902959995"),
+        field_new!(Fq, "1058488477413994682556770863004536636444795456512795473806825292198091015005841418695586811009326456605062948114985"),
+    ],
+    x_denominator: &[
+        field_new!(Fq, "1353092447850172218905095041059784486169131709710991428415161466575141675351394082965234118340787683181925558786844"),
+        field_new!(Fq, "2822220997908397120956501031591772354860004534930174057793539372552395729721474912921980407622851861692773516917759"),
+        field_new!(Fq, "1717937747208385987946072944131378949849282930538642983149296304709633281382731764122371874602115081850953846504985"),
+        field_new!(Fq, "501624051089734157816582944025690868317536915684467868346388760435016044027032505306995281054569109955275640941784"),
+        field_new!(Fq, "3025903087998593826923738290305187197829899948335370692927241015584233559365859980023579293766193297662657497834014"),
+        field_new!(Fq, "2224140216975189437834161136818943039444741035168992629437640302964164227138031844090123490881551522278632040105125"),
+        field_new!(Fq, "1146414465848284837484508420047674663876992808692209238763293935905506532411661921697047880549716175045414621825594"),
+        field_new!(Fq, "3179090966864399634396993677377903383656908036827452986467581478509513058347781039562481806409014718357094150199902"),
+        field_new!(Fq, "1549317016540628014674302140786462938410429359529923207442151939696344988707002602944342203885692366490121021806145"),
+        field_new!(Fq, "1442797143427491432630626390066422021593505165588630398337491100088557278058060064930663878153124164818522816175370"),
+        field_new!(Fq, "1"),
+    ],
+    y_numerator: &[
+        field_new!(Fq, "1393399195776646641963150658816615410692049723305861307490980409834842911816308830479576739332720113414154429643571"),
+        field_new!(Fq, "2968610969752762946134106091152102846225411740689724909058016729455736597929366401532929068084731548131227395540630"),
+        field_new!(Fq, "122933100683284845219599644396874530871261396084070222155796123161881094323788483360414289333111221370374027338230"),
+        field_new!(Fq, "303251954782077855462083823228569901064301365507057490567314302006681283228886645653148231378803311079384246777035"),
+        field_new!(Fq, "1353972356724735644398279028378555627591260676383150667237975415318226973994509601413730187583692624416197017403099"),
+        field_new!(Fq, "3443977503653895028417260979421240655844034880950251104724609885224259484262346958661845148165419691583810082940400"),
+        field_new!(Fq, "718493410301850496156792713845282235942975872282052335612908458061560958159410402177452633054233549648465863759602"),
+        field_new!(Fq, "1466864076415884313141727877156167508644960317046160398342634861648153052436926062434809922037623519108138661903145"),
+        field_new!(Fq, "1536886493137106337339531461344158973554574987550750910027365237255347020572858445054025958480906372033954157667719"),
+        field_new!(Fq, "2171468288973248519912068884667133903101171670397991979582205855298465414047741472281361964966463442016062407908400"),
+        field_new!(Fq, "3915937073730221072189646057898966011292434045388986394373682715266664498392389619761133407846638689998746172899634"),
+        field_new!(Fq, "3802409194827407598156407709510350851173404795262202653149767739163117554648574333789388883640862266596657730112910"),
+        field_new!(Fq, "1707589313757812493102695021134258021969283151093981498394095062397393499601961942449581422761005023512037430861560"),
+        field_new!(Fq, "349697005987545415860583335313370109325490073856352967581197273584891698473628451945217286148025358795756956811571"),
+        field_new!(Fq, "885704436476567581377743161796735879083481447641210566405057346859953524538988296201011389016649354976986251207243"),
+        field_new!(Fq, "3370924952219000111210625390420697640496067348723987858345031683392215988129398381698161406651860675722373763741188"),
+    ],
+    y_denominator: &[
+        field_new!(Fq, "3396434800020507717552209507749485772788165484415495716688989613875369612529138640646200921379825018840894888371137"),
+        field_new!(Fq, "3907278185868397906991868466757978732688957419873771881240086730384895060595583602347317992689443299391009456758845"),
+        field_new!(Fq, "854914566454823955479427412036002165304466268547334760894270240966182605542146252771872707010378658178126128834546"),
+        field_new!(Fq, "3496628876382137961119423566187258795236027183112131017519536056628828830323846696121917502443333849318934945158166"),
+        field_new!(Fq, "1828256966233331991927609917644344011503610008134915752990581590799656305331275863706710232159635159092657073225757"),
+        field_new!(Fq, "1362317127649143894542621413133849052553333099883364300946623208643344298804722863920546222860227051989127113848748"),
+        field_new!(Fq, "3443845896188810583748698342858554856823966611538932245284665132724280883115455093457486044009395063504744802318172"),
+        field_new!(Fq, "3484671274283470572728732863557945897902920439975203610275006103818288159899345245633896492713412187296754791689945"),
+        field_new!(Fq, "3755735109429418587065437067067640634211015783636675372165599470771975919172394156249639331555277748466603540045130"),
+        field_new!(Fq, "3459661102222301807083870307127272890283709299202626530836335779816726101522661683404130556379097384249447658110805"),
+        field_new!(Fq, "742483168411032072323733249644347333168432665415341249073150659015707795549260947228694495111018381111866512337576"),
+        field_new!(Fq, "1662231279858095762833829698537304807741442669992646287950513237989158777254081548205552083108208170765474149568658"),
+        field_new!(Fq, "1668238650112823419388205992952852912407572045257706138925379268508860023191233729074751042562151098884528280913356"),
+        field_new!(Fq, "369162719928976119195087327055926326601627748362769544198813069133429557026740823593067700396825489145575282378487"),
+        field_new!(Fq, "2164195715141237148945939585099633032390257748382945597506236650132835917087090097395995817229686247227784224263055"),
+        field_new!(Fq, "1"),
+    ],
+};
+
+/// Map to G2 through the 3-isogenous curve, RFC 9380 appendix E.3
+#[rustfmt::skip]
+const G2_SWU: IsogenousSwu<Fq2> = IsogenousSwu {
+    a: field_new!(Fq2, field_new!(Fq, "0"), field_new!(Fq, "240")),
+    b: field_new!(Fq2, field_new!(Fq, "1012"), field_new!(Fq, "1012")),
+    z: field_new!(Fq2, field_new!(Fq, "-2"), field_new!(Fq, "-1")),
+    x_numerator: &[
+        field_new!(Fq2, field_new!(Fq, "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542"), field_new!(Fq, "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542")),
+        field_new!(Fq2, field_new!(Fq, "0"), field_new!(Fq, "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706522")),
+        field_new!(Fq2, field_new!(Fq, "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706526"), field_new!(Fq, "1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853261")),
+        field_new!(Fq2, field_new!(Fq, "3557697382419259905260257622876359250272784728834673675850718343221361467102966990615722337003569479144794908942033"), field_new!(Fq, "0")),
+    ],
+    x_denominator: &[
+        field_new!(Fq2, field_new!(Fq, "0"), field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559715")),
+        field_new!(Fq2, field_new!(Fq, "12"), field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559775")),
+        field_new!(Fq2, field_new!(Fq, "1"), field_new!(Fq, "0")),
+    ],
+    y_numerator: &[
+        field_new!(Fq2, field_new!(Fq, "3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558"), field_new!(Fq, "3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558")),
+        field_new!(Fq2, field_new!(Fq, "0"), field_new!(Fq, "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235518")),
+        field_new!(Fq2, field_new!(Fq, "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706524"), field_new!(Fq, "1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853263")),
+        field_new!(Fq2, field_new!(Fq, "2816510427748580758331037284777117739799287910327449993381818688383577828123182200904113516794492504322962636245776"), field_new!(Fq, "0")),
+    ],
+    y_denominator: &[
+        field_new!(Fq2, field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355"), field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355")),
+        field_new!(Fq2, field_new!(Fq, "0"), field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559571")),
+        field_new!(Fq2, field_new!(Fq, "18"), field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559769")),
+        field_new!(Fq2, field_new!(Fq, "1"), field_new!(Fq, "0")),
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn fq(hex: &str) -> Fq {
+        Fq::from_be_bytes_mod_order(&from_hex(hex))
+    }
+
+    fn fq2(c0: &str, c1: &str) -> Fq2 {
+        Fq2::new(fq(c0), fq(c1))
+    }
+
+    // Test vectors from RFC 9380, appendices K.1, J.9.1 and J.10.1
+
+    #[test]
+    fn test_expand_message_xmd() {
+        const TAG: DomainTag = DomainTag::new("QUUX-V01-CS02-with-expander-SHA256-128");
+
+        assert_eq!(
+            expand_message_xmd(b"", TAG, 0x20).unwrap(),
+            from_hex("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abc", TAG, 0x20).unwrap(),
+            from_hex("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abcdef0123456789", TAG, 0x20).unwrap(),
+            from_hex("eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abc", TAG, 0x80).unwrap()[..40],
+            from_hex("abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a647e6c3163d40b76")[..]
+        );
+        assert!(expand_message_xmd(b"abc", TAG, 256 * 32).is_err());
+    }
+
+    #[test]
+    fn test_hash_to_g1() {
+        const TAG: DomainTag = DomainTag::new("QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_");
+
+        let u: Vec<Fq> = hash_to_field(b"", TAG, 2).unwrap();
+        assert_eq!(u, vec![
+            fq("0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941eece7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f"),
+            fq("019b9bd7979f12657976de2884c7cce192b82c177c80e0ec604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9"),
+        ]);
+
+        let vectors = [
+            (
+                &b""[..],
+                "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+                "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+            ),
+            (
+                &b"abc"[..],
+                "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+                "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d",
+            ),
+        ];
+        for (message, x, y) in vectors {
+            let point = Bls12_381::hash_to_g1(message, TAG).unwrap().into_affine();
+            assert_eq!((point.x, point.y), (fq(x), fq(y)));
+        }
+    }
+
+    #[test]
+    fn test_hash_to_g2() {
+        const TAG: DomainTag = DomainTag::new("QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_");
+
+        let u: Vec<Fq2> = hash_to_field(b"abc", TAG, 2).unwrap();
+        assert_eq!(u[0], fq2(
+            "15f7c0aa8f6b296ab5ff9c2c7581ade64f4ee6f1bf18f55179ff44a2cf355fa53dd2a2158c5ecb17d7c52f63e7195771",
+            "01c8067bf4c0ba709aa8b9abc3d1cef589a4758e09ef53732d670fd8739a7274e111ba2fcaa71b3d33df2a3a0c8529dd",
+        ));
+
+        let point = Bls12_381::hash_to_g2(b"", TAG).unwrap().into_affine();
+        assert_eq!(point.x, fq2(
+            "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+            "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+        ));
+        assert_eq!(point.y, fq2(
+            "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+            "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+        ));
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let message = b"same input";
+        let a: Fr = hash_to_scalar(message, DomainTag::TRANSACTION_HASH).unwrap();
+        let b: Fr = hash_to_scalar(message, DomainTag::BLOCK_HASH).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, hash_to_scalar(message, DomainTag::TRANSACTION_HASH).unwrap());
+
+        assert_ne!(
+            Bls12_381::hash_to_g1(message, DomainTag::G1_POINT).unwrap(),
+            Bls12_381::hash_to_g1(message, DomainTag::new("APORIA-ZERO-V01-OTHER")).unwrap()
+        );
+    }
+}
@@ -1,7 +1,11 @@
 use super::CryptoError;
-use ark_ec::{PairingEngine, ProjectiveCurve};
-use ark_ff::Field;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use hmac::{Hmac, Mac};
 use rand::Rng;
+use sha2::Sha512;
+use sha3::{Digest, Sha3_256};
 use std::marker::PhantomData;
 
 /// Key pair for digital signatures
@@ -48,30 +52,54 @@ impl<E: PairingEngine> KeyManager<E> {
             .mul(secret_key.into_repr())
     }
 
-    /// Import key pair from bytes
+    /// Import key pair from `export_keypair` bytes
+    ///
+    /// The secret must be a canonical non-zero scalar, and the public key a
+    /// compressed point on the curve, in the prime-order subgroup, and
+    /// matching the secret.
     pub fn import_keypair(
         &self,
         secret_bytes: &[u8],
         public_bytes: &[u8],
     ) -> Result<KeyPair<E>, CryptoError> {
-        let secret_key = E::Fr::from_random_bytes(secret_bytes)
-            .ok_or_else(|| CryptoError::KeyError("Invalid secret key bytes".to_string()))?;
+        let mut reader = secret_bytes;
+        let secret_key = E::Fr::deserialize(&mut reader)
+            .map_err(|_| CryptoError::KeyError("Invalid secret key bytes".to_string()))?;
+        if !reader.is_empty() || secret_key.is_zero() {
+            return Err(CryptoError::KeyError("Invalid secret key bytes".to_string()));
+        }
 
-        let public_key = E::G1Projective::from_random_bytes(public_bytes)
-            .ok_or_else(|| CryptoError::KeyError("Invalid public key bytes".to_string()))?;
+        let mut reader = public_bytes;
+        let public_key = E::G1Affine::deserialize(&mut reader)
+            .map_err(|_| CryptoError::KeyError("Invalid public key bytes".to_string()))?;
+        if !reader.is_empty() {
+            return Err(CryptoError::KeyError("Invalid public key bytes".to_string()));
+        }
+        if !public_key.is_on_curve() || !public_key.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(CryptoError::KeyError("Public key is not in the subgroup".to_string()));
+        }
 
-        Ok(KeyPair {
+        let keypair = KeyPair {
             secret_key,
-            public_key,
-        })
+            public_key: public_key.into_projective(),
+        };
+        if !self.verify_keypair(&keypair) {
+            return Err(CryptoError::KeyError("Public key does not match secret key".to_string()));
+        }
+        Ok(keypair)
     }
 
-    /// Export key pair to bytes
-    pub fn export_keypair(&self, keypair: &KeyPair<E>) -> (Vec<u8>, Vec<u8>) {
-        let secret_bytes = keypair.secret_key.into_repr().to_bytes_le();
-        let public_bytes = keypair.public_key.into_affine().into_repr().to_bytes_le();
-        
-        (secret_bytes, public_bytes)
+    /// Export key pair as the canonical secret scalar and compressed public key
+    pub fn export_keypair(&self, keypair: &KeyPair<E>) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        let mut secret_bytes = Vec::new();
+        keypair.secret_key.serialize(&mut secret_bytes)
+            .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+
+        let mut public_bytes = Vec::new();
+        keypair.public_key.into_affine().serialize(&mut public_bytes)
+            .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+
+        Ok((secret_bytes, public_bytes))
     }
 
     /// Verify key pair
@@ -81,7 +109,202 @@ impl<E: PairingEngine> KeyManager<E> {
     }
 }
 
-/// HD key derivation
+/// Offset of hardened child indices
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// HMAC key for master key generation
+const MASTER_HMAC_KEY: &[u8] = b"aporia-zero HD seed";
+
+/// Shortest accepted seed
+pub const MIN_SEED_SIZE: usize = 16;
+
+/// Longest accepted seed
+pub const MAX_SEED_SIZE: usize = 64;
+
+/// Size of a chain code
+pub const CHAIN_CODE_SIZE: usize = 32;
+
+/// Size of an encoded extended public key
+pub const EXTENDED_PUBLIC_KEY_SIZE: usize = 1 + 4 + 4 + CHAIN_CODE_SIZE + 48;
+
+/// Derivation path such as `m/44'/7'/0'/0/5`
+///
+/// Indices marked with `'` or `h` are hardened.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath {
+    /// Child indices from the master key, hardened ones offset by `HARDENED_OFFSET`
+    pub indices: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// Whether any index is hardened
+    pub fn is_hardened(&self) -> bool {
+        self.indices.iter().any(|index| *index >= HARDENED_OFFSET)
+    }
+}
+
+impl std::str::FromStr for DerivationPath {
+    type Err = CryptoError;
+
+    fn from_str(path: &str) -> Result<Self, CryptoError> {
+        let mut components = path.split('/');
+        if components.next() != Some("m") {
+            return Err(CryptoError::KeyError(format!("Path {} does not start at m", path)));
+        }
+
+        let mut indices = Vec::new();
+        for component in components {
+            let (number, offset) = match component.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+                Some(number) => (number, HARDENED_OFFSET),
+                None => (component, 0),
+            };
+            let index = number.parse::<u32>()
+                .ok()
+                .filter(|index| *index < HARDENED_OFFSET && !number.starts_with('+'))
+                .ok_or_else(|| CryptoError::KeyError(format!("Invalid path component {}", component)))?;
+            indices.push(index + offset);
+        }
+        if indices.len() > u8::MAX as usize {
+            return Err(CryptoError::KeyError("Path is too deep".to_string()));
+        }
+
+        Ok(Self { indices })
+    }
+}
+
+impl std::fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m")?;
+        for index in &self.indices {
+            if *index >= HARDENED_OFFSET {
+                write!(f, "/{}'", index - HARDENED_OFFSET)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Key pair with the chain code needed to derive children
+#[derive(Clone)]
+pub struct ExtendedKeyPair<E: PairingEngine> {
+    /// Key pair
+    pub keypair: KeyPair<E>,
+
+    /// Chain code
+    pub chain_code: [u8; CHAIN_CODE_SIZE],
+
+    /// Depth below the master key
+    pub depth: u8,
+
+    /// Fingerprint of the parent public key
+    pub parent_fingerprint: [u8; 4],
+
+    /// Index this key was derived at
+    pub index: u32,
+}
+
+impl<E: PairingEngine> ExtendedKeyPair<E> {
+    /// Watch-only counterpart of this key
+    pub fn public(&self) -> ExtendedPublicKey<E> {
+        ExtendedPublicKey {
+            public_key: self.keypair.public_key,
+            chain_code: self.chain_code,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            index: self.index,
+        }
+    }
+}
+
+/// Public key with the chain code needed to derive non-hardened children
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedPublicKey<E: PairingEngine> {
+    /// Public key
+    pub public_key: E::G1Projective,
+
+    /// Chain code
+    pub chain_code: [u8; CHAIN_CODE_SIZE],
+
+    /// Depth below the master key
+    pub depth: u8,
+
+    /// Fingerprint of the parent public key
+    pub parent_fingerprint: [u8; 4],
+
+    /// Index this key was derived at
+    pub index: u32,
+}
+
+impl<E: PairingEngine> ExtendedPublicKey<E> {
+    /// First four bytes of the SHA3-256 hash of the compressed public key
+    pub fn fingerprint(&self) -> Result<[u8; 4], CryptoError> {
+        let hash = Sha3_256::digest(&compress::<E>(&self.public_key)?);
+        let mut fingerprint = [0u8; 4];
+        fingerprint.copy_from_slice(&hash[..4]);
+        Ok(fingerprint)
+    }
+
+    /// Encode as depth, parent fingerprint, index, chain code and compressed key
+    pub fn encode(&self) -> Result<Vec<u8>, CryptoError> {
+        let mut bytes = Vec::with_capacity(EXTENDED_PUBLIC_KEY_SIZE);
+        bytes.push(self.depth);
+        bytes.extend_from_slice(&self.parent_fingerprint);
+        bytes.extend_from_slice(&self.index.to_be_bytes());
+        bytes.extend_from_slice(&self.chain_code);
+        bytes.extend_from_slice(&compress::<E>(&self.public_key)?);
+        Ok(bytes)
+    }
+
+    /// Decode an encoded extended public key, checking the point is in the subgroup
+    pub fn decode(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != EXTENDED_PUBLIC_KEY_SIZE {
+            return Err(CryptoError::KeyError("Invalid extended public key length".to_string()));
+        }
+
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&bytes[1..5]);
+        let mut index = [0u8; 4];
+        index.copy_from_slice(&bytes[5..9]);
+        let mut chain_code = [0u8; CHAIN_CODE_SIZE];
+        chain_code.copy_from_slice(&bytes[9..9 + CHAIN_CODE_SIZE]);
+
+        let mut reader = &bytes[9 + CHAIN_CODE_SIZE..];
+        let public_key = E::G1Affine::deserialize(&mut reader)
+            .map_err(|_| CryptoError::KeyError("Invalid public key bytes".to_string()))?;
+        if public_key.is_zero()
+            || !public_key.is_on_curve()
+            || !public_key.is_in_correct_subgroup_assuming_on_curve()
+        {
+            return Err(CryptoError::KeyError("Public key is not in the subgroup".to_string()));
+        }
+
+        Ok(Self {
+            public_key: public_key.into_projective(),
+            chain_code,
+            depth: bytes[0],
+            parent_fingerprint,
+            index: u32::from_be_bytes(index),
+        })
+    }
+}
+
+/// Hierarchical deterministic key derivation
+///
+/// BIP32 adapted to G1 of a pairing-friendly curve. Every step computes
+/// `T = HMAC-SHA512(key, data || 0x00)` reduced modulo the group order as the
+/// tweak, and `HMAC-SHA512(key, data || 0x01)[..32]` as the chain code, so
+/// tweaks are unbiased despite the scalar field being smaller than 256 bits.
+///
+/// - Master: key is `"aporia-zero HD seed"`, data is the seed, secret is `T`.
+/// - Hardened child: key is the parent chain code, data is
+///   `0x00 || secret (32 bytes LE) || index (BE)`, secret is `parent + T`.
+/// - Normal child: data is `compressed parent public key || index (BE)`,
+///   public key is `parent + T·G`, so it can be derived without the secret.
+///
+/// Leaking a normal child secret and the parent extended public key reveals
+/// the parent secret, as in BIP32; hardened children do not have this issue.
 pub struct HDKeyDeriver<E: PairingEngine> {
     /// Key manager
     key_manager: KeyManager<E>,
@@ -94,34 +317,145 @@ impl<E: PairingEngine> HDKeyDeriver<E> {
         }
     }
 
-    /// Derive child key from parent
+    /// Generate the master key from a seed
+    pub fn master_key(&self, seed: &[u8]) -> Result<ExtendedKeyPair<E>, CryptoError> {
+        if seed.len() < MIN_SEED_SIZE || seed.len() > MAX_SEED_SIZE {
+            return Err(CryptoError::KeyError(format!(
+                "Seed must be between {} and {} bytes", MIN_SEED_SIZE, MAX_SEED_SIZE
+            )));
+        }
+
+        let (secret_key, chain_code) = tweak::<E>(MASTER_HMAC_KEY, seed)?;
+        Ok(ExtendedKeyPair {
+            keypair: KeyPair {
+                secret_key,
+                public_key: self.key_manager.derive_public_key(&secret_key),
+            },
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0u8; 4],
+            index: 0,
+        })
+    }
+
+    /// Derive child key from parent, hardened if `index >= HARDENED_OFFSET`
     pub fn derive_child_key(
         &self,
-        parent: &KeyPair<E>,
+        parent: &ExtendedKeyPair<E>,
         index: u32,
-    ) -> Result<KeyPair<E>, CryptoError> {
-        // Combine parent public key and index
+    ) -> Result<ExtendedKeyPair<E>, CryptoError> {
+        let parent_public = parent.public();
+        let depth = child_depth(parent.depth)?;
+
         let mut data = Vec::new();
-        data.extend_from_slice(&parent.public_key.into_affine().into_repr().to_bytes_le());
-        data.extend_from_slice(&index.to_le_bytes());
-        
-        // Hash the combined data
-        let mut hasher = sha3::Sha3_256::new();
-        hasher.update(&data);
-        let hash = hasher.finalize();
-        
-        // Generate child private key
-        let child_scalar = E::Fr::from_random_bytes(&hash)
-            .ok_or_else(|| CryptoError::KeyError("Invalid child key derivation".to_string()))?;
-        
-        let child_secret = parent.secret_key + child_scalar;
-        let child_public = self.key_manager.derive_public_key(&child_secret);
-        
-        Ok(KeyPair {
-            secret_key: child_secret,
-            public_key: child_public,
+        if index >= HARDENED_OFFSET {
+            data.push(0);
+            parent.keypair.secret_key.serialize(&mut data)
+                .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+        } else {
+            data.extend_from_slice(&compress::<E>(&parent.keypair.public_key)?);
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (tweak, chain_code) = tweak::<E>(&parent.chain_code, &data)?;
+        let secret_key = parent.keypair.secret_key + tweak;
+        if secret_key.is_zero() {
+            return Err(CryptoError::KeyError(format!("Index {} derives an invalid key", index)));
+        }
+
+        Ok(ExtendedKeyPair {
+            keypair: KeyPair {
+                secret_key,
+                public_key: self.key_manager.derive_public_key(&secret_key),
+            },
+            chain_code,
+            depth,
+            parent_fingerprint: parent_public.fingerprint()?,
+            index,
         })
     }
+
+    /// Derive non-hardened child public key without the parent secret
+    pub fn derive_public_child(
+        &self,
+        parent: &ExtendedPublicKey<E>,
+        index: u32,
+    ) -> Result<ExtendedPublicKey<E>, CryptoError> {
+        if index >= HARDENED_OFFSET {
+            return Err(CryptoError::KeyError("Hardened keys need the parent secret".to_string()));
+        }
+        let depth = child_depth(parent.depth)?;
+
+        let mut data = compress::<E>(&parent.public_key)?;
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (tweak, chain_code) = tweak::<E>(&parent.chain_code, &data)?;
+        let public_key = parent.public_key + self.key_manager.derive_public_key(&tweak);
+        if public_key.is_zero() {
+            return Err(CryptoError::KeyError(format!("Index {} derives an invalid key", index)));
+        }
+
+        Ok(ExtendedPublicKey {
+            public_key,
+            chain_code,
+            depth,
+            parent_fingerprint: parent.fingerprint()?,
+            index,
+        })
+    }
+
+    /// Derive key at a path below `parent`
+    pub fn derive_path(
+        &self,
+        parent: &ExtendedKeyPair<E>,
+        path: &DerivationPath,
+    ) -> Result<ExtendedKeyPair<E>, CryptoError> {
+        path.indices.iter()
+            .try_fold(parent.clone(), |key, index| self.derive_child_key(&key, *index))
+    }
+
+    /// Derive public key at a non-hardened path below `parent`
+    pub fn derive_public_path(
+        &self,
+        parent: &ExtendedPublicKey<E>,
+        path: &DerivationPath,
+    ) -> Result<ExtendedPublicKey<E>, CryptoError> {
+        path.indices.iter()
+            .try_fold(parent.clone(), |key, index| self.derive_public_child(&key, *index))
+    }
+}
+
+/// Tweak scalar and chain code for one derivation step
+fn tweak<E: PairingEngine>(key: &[u8], data: &[u8]) -> Result<(E::Fr, [u8; CHAIN_CODE_SIZE]), CryptoError> {
+    let mac = |domain: u8| -> Result<Vec<u8>, CryptoError> {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key)
+            .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+        mac.update(data);
+        mac.update(&[domain]);
+        Ok(mac.finalize().into_bytes().to_vec())
+    };
+
+    let tweak = E::Fr::from_le_bytes_mod_order(&mac(0)?);
+    if tweak.is_zero() {
+        return Err(CryptoError::KeyError("Derivation produced a zero tweak".to_string()));
+    }
+    let mut chain_code = [0u8; CHAIN_CODE_SIZE];
+    chain_code.copy_from_slice(&mac(1)?[..CHAIN_CODE_SIZE]);
+    Ok((tweak, chain_code))
+}
+
+/// Depth of a child key
+fn child_depth(parent_depth: u8) -> Result<u8, CryptoError> {
+    parent_depth.checked_add(1)
+        .ok_or_else(|| CryptoError::KeyError("Maximum derivation depth reached".to_string()))
+}
+
+/// Compressed encoding of a point
+fn compress<E: PairingEngine>(point: &E::G1Projective) -> Result<Vec<u8>, CryptoError> {
+    let mut bytes = Vec::new();
+    point.into_affine().serialize(&mut bytes)
+        .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+    Ok(bytes)
 }
 
 #[cfg(test)]
@@ -145,7 +479,7 @@ mod tests {
         let mut rng = thread_rng();
         
         let original_keypair = key_manager.generate_keypair(&mut rng);
-        let (secret_bytes, public_bytes) = key_manager.export_keypair(&original_keypair);
+        let (secret_bytes, public_bytes) = key_manager.export_keypair(&original_keypair).unwrap();
         
         let imported_keypair = key_manager.import_keypair(&secret_bytes, &public_bytes).unwrap();
         assert_eq!(original_keypair.secret_key, imported_keypair.secret_key);
@@ -153,15 +487,94 @@ mod tests {
     }
 
     #[test]
-    fn test_hd_key_derivation() {
-        let hd_deriver = HDKeyDeriver::<Bls12_381>::new();
+    fn test_import_rejects_mismatched_keys() {
         let key_manager = KeyManager::<Bls12_381>::new();
         let mut rng = thread_rng();
         
-        let parent = key_manager.generate_keypair(&mut rng);
-        let child = hd_deriver.derive_child_key(&parent, 0).unwrap();
+        let (secret_bytes, _) = key_manager.export_keypair(&key_manager.generate_keypair(&mut rng)).unwrap();
+        let (_, other_public) = key_manager.export_keypair(&key_manager.generate_keypair(&mut rng)).unwrap();
+        assert!(key_manager.import_keypair(&secret_bytes, &other_public).is_err());
         
-        assert!(key_manager.verify_keypair(&child));
-        assert_ne!(parent.secret_key, child.secret_key);
+        // Non-canonical scalar, and a point off the curve or outside the subgroup
+        assert!(key_manager.import_keypair(&[0xff; 32], &other_public).is_err());
+        let mut off_curve = other_public.clone();
+        off_curve[47] ^= 1;
+        assert!(key_manager.import_keypair(&secret_bytes, &off_curve).is_err());
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn secret_bytes(keypair: &ExtendedKeyPair<Bls12_381>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        keypair.keypair.secret_key.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_hd_vectors() {
+        let hd_deriver = HDKeyDeriver::<Bls12_381>::new();
+        let master = hd_deriver.master_key(&from_hex("000102030405060708090a0b0c0d0e0f")).unwrap();
+        assert_eq!(secret_bytes(&master), from_hex("ab11c753112ea94c2afdb7d0f46ddf1ec189a75f3d89eb0669fc27d673a50d1d"));
+        assert_eq!(master.chain_code.to_vec(), from_hex("7fe079cdbde6d8d685fc0fab2c1d4a284877fed0ae0bf5610c3f9ccc453877dd"));
+
+        let child = hd_deriver.derive_path(&master, &"m/0'".parse().unwrap()).unwrap();
+        assert_eq!(secret_bytes(&child), from_hex("c34740c4bc5b5318fc398aba0133f6a8bef8ac6a0eb57a1a68f2e37e495cff57"));
+        assert_eq!(child.chain_code.to_vec(), from_hex("f2d8621996528b7ec443a98c07d0fe1242d2c82ea0837caeee80f56ac5fa4b02"));
+        assert_eq!(child.parent_fingerprint, master.public().fingerprint().unwrap());
+
+        let grandchild = hd_deriver.derive_path(&master, &"m/0'/1h".parse().unwrap()).unwrap();
+        assert_eq!(secret_bytes(&grandchild), from_hex("155bc6fb7d2c7d980ec4eb1029f5647b2919374c2bdc66213ac909c0c8d3164d"));
+        assert_eq!(grandchild.chain_code.to_vec(), from_hex("c2d9106b693e6015def20e769abe2e27c773cd1f8843c061f661661ec38c2915"));
+        assert_eq!((grandchild.depth, grandchild.index), (2, HARDENED_OFFSET + 1));
+    }
+
+    #[test]
+    fn test_hd_public_derivation() {
+        let hd_deriver = HDKeyDeriver::<Bls12_381>::new();
+        let key_manager = KeyManager::<Bls12_381>::new();
+        let master = hd_deriver.master_key(&[7u8; 32]).unwrap();
+
+        // Watch-only wallet derives the same addresses from the account key
+        let account = hd_deriver.derive_path(&master, &"m/44'/7'/0'".parse().unwrap()).unwrap();
+        let watch_only = ExtendedPublicKey::<Bls12_381>::decode(&account.public().encode().unwrap()).unwrap();
+        assert_eq!(watch_only, account.public());
+
+        let relative: DerivationPath = "m/0/5".parse().unwrap();
+        let child = hd_deriver.derive_path(&account, &relative).unwrap();
+        let public_child = hd_deriver.derive_public_path(&watch_only, &relative).unwrap();
+        assert!(key_manager.verify_keypair(&child.keypair));
+        assert_eq!(child.public(), public_child);
+
+        let full = hd_deriver.derive_path(&master, &"m/44'/7'/0'/0/5".parse().unwrap()).unwrap();
+        assert_eq!(full.keypair.secret_key, child.keypair.secret_key);
+
+        // Hardened children need the secret
+        assert!(hd_deriver.derive_public_child(&watch_only, HARDENED_OFFSET).is_err());
+        assert_ne!(
+            hd_deriver.derive_child_key(&account, 0).unwrap().keypair.secret_key,
+            hd_deriver.derive_child_key(&account, HARDENED_OFFSET).unwrap().keypair.secret_key,
+        );
+        assert!(hd_deriver.master_key(&[0u8; 8]).is_err());
+    }
+
+    #[test]
+    fn test_derivation_path_parsing() {
+        let path: DerivationPath = "m/44'/7'/0'/0/5".parse().unwrap();
+        assert_eq!(path.indices, vec![
+            HARDENED_OFFSET + 44, HARDENED_OFFSET + 7, HARDENED_OFFSET, 0, 5,
+        ]);
+        assert!(path.is_hardened());
+        assert_eq!(path.to_string(), "m/44'/7'/0'/0/5");
+        assert_eq!("m/1H/2h".parse::<DerivationPath>().unwrap().to_string(), "m/1'/2'");
+        assert_eq!("m".parse::<DerivationPath>().unwrap(), DerivationPath::default());
+
+        for invalid in ["", "44'/0", "m/", "m//1", "m/x", "m/-1", "m/+1", "m/2147483648", "m/1''"] {
+            assert!(invalid.parse::<DerivationPath>().is_err(), "{}", invalid);
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,436 @@
+use super::CryptoError;
+use super::encryption::{EncryptedData, EncryptionScheme};
+use super::keys::{KeyManager, KeyPair};
+use argon2::Argon2;
+use ark_ec::PairingEngine;
+use rand::Rng;
+use sha3::{Digest, Sha3_256};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Magic bytes opening a keystore file
+pub const KEYSTORE_MAGIC: &[u8; 4] = b"AZKS";
+
+/// Version of the keystore format
+pub const KEYSTORE_VERSION: u8 = 1;
+
+/// Size of the password KDF salt
+const KDF_SALT_SIZE: usize = 32;
+
+/// Size of the key derived from the password
+const DERIVED_KEY_SIZE: usize = 32;
+
+/// Size of the checksum closing the file
+const CHECKSUM_SIZE: usize = 32;
+
+/// Largest scrypt cost exponent accepted
+pub const MAX_SCRYPT_LOG_N: u8 = 20;
+
+/// Largest scrypt block size accepted
+pub const MAX_SCRYPT_R: u32 = 32;
+
+/// Largest scrypt parallelism accepted
+pub const MAX_SCRYPT_P: u32 = 16;
+
+/// Largest memory, in KiB, either KDF may use
+pub const MAX_KDF_MEMORY_KIB: u32 = 1 << 20;
+
+/// Largest number of Argon2id passes accepted
+pub const MAX_ARGON2_ITERATIONS: u32 = 16;
+
+/// Largest number of Argon2id lanes accepted
+pub const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+/// Password key derivation function and its cost parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// scrypt with cost `2^log_n`
+    Scrypt {
+        /// Log2 of the CPU and memory cost
+        log_n: u8,
+        /// Block size
+        r: u32,
+        /// Parallelism
+        p: u32,
+    },
+    /// Argon2id
+    Argon2id {
+        /// Memory in KiB
+        memory_kib: u32,
+        /// Number of passes
+        iterations: u32,
+        /// Lanes
+        parallelism: u32,
+    },
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::Argon2id {
+            memory_kib: argon2::Params::DEFAULT_M_COST,
+            iterations: argon2::Params::DEFAULT_T_COST,
+            parallelism: argon2::Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Kdf {
+    /// Check the cost parameters are within the accepted maxima
+    ///
+    /// A keystore file picks its own parameters, so they are bounded before
+    /// anything is derived to keep a crafted file from exhausting memory or
+    /// CPU.
+    fn check_bounds(&self) -> Result<(), CryptoError> {
+        let within = match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                // scrypt uses 128 * r * 2^log_n bytes
+                log_n <= MAX_SCRYPT_LOG_N
+                    && r <= MAX_SCRYPT_R
+                    && p <= MAX_SCRYPT_P
+                    && (r as u64) << log_n <= (MAX_KDF_MEMORY_KIB as u64) * 1024 / 128
+            }
+            Kdf::Argon2id { memory_kib, iterations, parallelism } => {
+                memory_kib <= MAX_KDF_MEMORY_KIB
+                    && iterations <= MAX_ARGON2_ITERATIONS
+                    && parallelism <= MAX_ARGON2_PARALLELISM
+            }
+        };
+        if !within {
+            return Err(CryptoError::ParameterError(format!("KDF parameters exceed maxima: {:?}", self)));
+        }
+        Ok(())
+    }
+
+    /// Derive the keystore encryption key from a password
+    fn derive(&self, password: &[u8], salt: &[u8]) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+        let mut key = Zeroizing::new(vec![0u8; DERIVED_KEY_SIZE]);
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, DERIVED_KEY_SIZE)
+                    .map_err(|e| CryptoError::ParameterError(e.to_string()))?;
+                scrypt::scrypt(password, salt, &params, &mut key)
+                    .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+            }
+            Kdf::Argon2id { memory_kib, iterations, parallelism } => {
+                let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(DERIVED_KEY_SIZE))
+                    .map_err(|e| CryptoError::ParameterError(e.to_string()))?;
+                Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, &mut key)
+                    .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+            }
+        }
+        Ok(key)
+    }
+
+    /// Append identifier and parameters
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                bytes.push(1);
+                bytes.push(log_n);
+                bytes.extend_from_slice(&r.to_be_bytes());
+                bytes.extend_from_slice(&p.to_be_bytes());
+            }
+            Kdf::Argon2id { memory_kib, iterations, parallelism } => {
+                bytes.push(2);
+                bytes.extend_from_slice(&memory_kib.to_be_bytes());
+                bytes.extend_from_slice(&iterations.to_be_bytes());
+                bytes.extend_from_slice(&parallelism.to_be_bytes());
+            }
+        }
+    }
+
+    /// Read identifier and parameters, rejecting costs above the maxima
+    fn decode(reader: &mut &[u8]) -> Result<Self, CryptoError> {
+        let kdf = match take(reader, 1)?[0] {
+            1 => Ok(Kdf::Scrypt {
+                log_n: take(reader, 1)?[0],
+                r: read_u32(reader)?,
+                p: read_u32(reader)?,
+            }),
+            2 => Ok(Kdf::Argon2id {
+                memory_kib: read_u32(reader)?,
+                iterations: read_u32(reader)?,
+                parallelism: read_u32(reader)?,
+            }),
+            id => Err(CryptoError::KeyError(format!("Unknown keystore KDF {}", id))),
+        }?;
+        kdf.check_bounds()?;
+        Ok(kdf)
+    }
+}
+
+/// Password-protected key pair
+///
+/// The file holds, in order: magic, version, KDF parameters, KDF salt, the
+/// public key in the clear, the encrypted secret key and a SHA3-256 checksum
+/// of everything before it. Everything before the encrypted secret is
+/// authenticated by the AEAD, so a keystore cannot be edited to claim
+/// another public key or weaker KDF parameters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keystore {
+    /// Password key derivation
+    pub kdf: Kdf,
+
+    /// Password KDF salt
+    pub salt: Vec<u8>,
+
+    /// Compressed public key
+    pub public_key: Vec<u8>,
+
+    /// Encrypted canonical secret key
+    pub encrypted: EncryptedData,
+}
+
+impl Keystore {
+    /// Encrypt a key pair under a password
+    pub fn encrypt<E: PairingEngine, R: Rng>(
+        keypair: &KeyPair<E>,
+        password: &[u8],
+        kdf: Kdf,
+        rng: &mut R,
+    ) -> Result<Self, CryptoError> {
+        let key_manager = KeyManager::<E>::new();
+        if !key_manager.verify_keypair(keypair) {
+            return Err(CryptoError::KeyError("Public key does not match secret key".to_string()));
+        }
+        kdf.check_bounds()?;
+        let (secret_bytes, public_key) = key_manager.export_keypair(keypair)?;
+        let secret_bytes = Zeroizing::new(secret_bytes);
+
+        let mut salt = vec![0u8; KDF_SALT_SIZE];
+        rng.fill_bytes(&mut salt);
+        let key = kdf.derive(password, &salt)?;
+        let encrypted = EncryptionScheme::<E>::new(256)?
+            .encrypt_with_aad(&secret_bytes, &header(&kdf, &salt, &public_key), &key, rng)?;
+
+        Ok(Self {
+            kdf,
+            salt,
+            public_key,
+            encrypted,
+        })
+    }
+
+    /// Decrypt and validate the key pair
+    pub fn decrypt<E: PairingEngine>(&self, password: &[u8]) -> Result<KeyPair<E>, CryptoError> {
+        let key = self.kdf.derive(password, &self.salt)?;
+        let secret_bytes = Zeroizing::new(
+            EncryptionScheme::<E>::new(256)?
+                .decrypt_with_aad(&self.encrypted, &self.header(), &key)
+                .map_err(|_| CryptoError::KeyError("Wrong password or corrupted keystore".to_string()))?,
+        );
+
+        KeyManager::<E>::new().import_keypair(&secret_bytes, &self.public_key)
+    }
+
+    /// Encode keystore file contents
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.header();
+        let encrypted = self.encrypted.encode();
+        bytes.extend_from_slice(&(encrypted.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&encrypted);
+
+        let checksum = Sha3_256::digest(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bytes
+    }
+
+    /// Decode keystore file contents, rejecting corrupted files
+    pub fn decode(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < CHECKSUM_SIZE {
+            return Err(CryptoError::KeyError("Keystore is truncated".to_string()));
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+        if Sha3_256::digest(body).as_slice() != checksum {
+            return Err(CryptoError::KeyError("Keystore checksum mismatch".to_string()));
+        }
+
+        let mut reader = body;
+        if take(&mut reader, KEYSTORE_MAGIC.len())? != KEYSTORE_MAGIC {
+            return Err(CryptoError::KeyError("Not a keystore file".to_string()));
+        }
+        let version = take(&mut reader, 1)?[0];
+        if version != KEYSTORE_VERSION {
+            return Err(CryptoError::KeyError(format!("Unsupported keystore version {}", version)));
+        }
+
+        let kdf = Kdf::decode(&mut reader)?;
+        let salt = take(&mut reader, KDF_SALT_SIZE)?.to_vec();
+        let public_len = read_u16(&mut reader)? as usize;
+        let public_key = take(&mut reader, public_len)?.to_vec();
+        let encrypted_len = read_u32(&mut reader)? as usize;
+        let encrypted = EncryptedData::decode(take(&mut reader, encrypted_len)?)?;
+        if !reader.is_empty() {
+            return Err(CryptoError::KeyError("Trailing bytes in keystore".to_string()));
+        }
+
+        Ok(Self {
+            kdf,
+            salt,
+            public_key,
+            encrypted,
+        })
+    }
+
+    /// Write keystore to a new file, readable only by the owner on Unix
+    pub fn save(&self, path: &Path) -> Result<(), CryptoError> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path)
+            .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+        file.write_all(&self.encode())
+            .and_then(|_| file.sync_all())
+            .map_err(|e| CryptoError::KeyError(e.to_string()))
+    }
+
+    /// Read keystore from a file
+    pub fn load(path: &Path) -> Result<Self, CryptoError> {
+        let bytes = fs::read(path)
+            .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+        Self::decode(&bytes)
+    }
+
+    /// Authenticated part of the file
+    fn header(&self) -> Vec<u8> {
+        header(&self.kdf, &self.salt, &self.public_key)
+    }
+}
+
+/// Magic, version, KDF, salt and public key
+fn header(kdf: &Kdf, salt: &[u8], public_key: &[u8]) -> Vec<u8> {
+    let mut header = KEYSTORE_MAGIC.to_vec();
+    header.push(KEYSTORE_VERSION);
+    kdf.encode(&mut header);
+    header.extend_from_slice(salt);
+    header.extend_from_slice(&(public_key.len() as u16).to_be_bytes());
+    header.extend_from_slice(public_key);
+    header
+}
+
+/// Split `n` bytes off the front of an encoding
+fn take<'a>(reader: &mut &'a [u8], n: usize) -> Result<&'a [u8], CryptoError> {
+    if reader.len() < n {
+        return Err(CryptoError::KeyError("Keystore is truncated".to_string()));
+    }
+    let (head, rest) = reader.split_at(n);
+    *reader = rest;
+    Ok(head)
+}
+
+/// Read a big-endian u16
+fn read_u16(reader: &mut &[u8]) -> Result<u16, CryptoError> {
+    let mut value = [0u8; 2];
+    value.copy_from_slice(take(reader, 2)?);
+    Ok(u16::from_be_bytes(value))
+}
+
+/// Read a big-endian u32
+fn read_u32(reader: &mut &[u8]) -> Result<u32, CryptoError> {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(take(reader, 4)?);
+    Ok(u32::from_be_bytes(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use rand::thread_rng;
+
+    /// Cheap parameters, for tests only
+    const TEST_SCRYPT: Kdf = Kdf::Scrypt { log_n: 4, r: 8, p: 1 };
+    const TEST_ARGON2: Kdf = Kdf::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 };
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let mut rng = thread_rng();
+        let keypair = KeyManager::<Bls12_381>::new().generate_keypair(&mut rng);
+
+        for kdf in [TEST_SCRYPT, TEST_ARGON2] {
+            let keystore = Keystore::encrypt(&keypair, b"correct horse", kdf, &mut rng).unwrap();
+            let decoded = Keystore::decode(&keystore.encode()).unwrap();
+            assert_eq!(decoded, keystore);
+
+            let restored = decoded.decrypt::<Bls12_381>(b"correct horse").unwrap();
+            assert_eq!(restored.secret_key, keypair.secret_key);
+            assert_eq!(restored.public_key, keypair.public_key);
+
+            assert!(decoded.decrypt::<Bls12_381>(b"wrong horse").is_err());
+        }
+    }
+
+    #[test]
+    fn test_keystore_rejects_tampering() {
+        let mut rng = thread_rng();
+        let key_manager = KeyManager::<Bls12_381>::new();
+        let keypair = key_manager.generate_keypair(&mut rng);
+        let keystore = Keystore::encrypt(&keypair, b"password", TEST_SCRYPT, &mut rng).unwrap();
+
+        // Corruption is caught by the checksum
+        let mut bytes = keystore.encode();
+        bytes[10] ^= 1;
+        assert!(Keystore::decode(&bytes).is_err());
+
+        // Swapping the public key is caught by the AEAD
+        let mut swapped = keystore.clone();
+        swapped.public_key = key_manager.export_keypair(&key_manager.generate_keypair(&mut rng)).unwrap().1;
+        assert!(swapped.decrypt::<Bls12_381>(b"password").is_err());
+
+        // Weakening the KDF is caught by the AEAD
+        let mut weakened = keystore;
+        weakened.kdf = Kdf::Scrypt { log_n: 2, r: 8, p: 1 };
+        assert!(weakened.decrypt::<Bls12_381>(b"password").is_err());
+    }
+
+    #[test]
+    fn test_keystore_rejects_excessive_kdf() {
+        let mut rng = thread_rng();
+        let keypair = KeyManager::<Bls12_381>::new().generate_keypair(&mut rng);
+        let keystore = Keystore::encrypt(&keypair, b"password", TEST_SCRYPT, &mut rng).unwrap();
+
+        let excessive = [
+            Kdf::Scrypt { log_n: MAX_SCRYPT_LOG_N + 1, r: 1, p: 1 },
+            Kdf::Scrypt { log_n: 4, r: MAX_SCRYPT_R + 1, p: 1 },
+            Kdf::Scrypt { log_n: 4, r: 8, p: MAX_SCRYPT_P + 1 },
+            Kdf::Scrypt { log_n: MAX_SCRYPT_LOG_N, r: MAX_SCRYPT_R, p: 1 },
+            Kdf::Argon2id { memory_kib: MAX_KDF_MEMORY_KIB + 1, iterations: 1, parallelism: 1 },
+            Kdf::Argon2id { memory_kib: 64, iterations: MAX_ARGON2_ITERATIONS + 1, parallelism: 1 },
+            Kdf::Argon2id { memory_kib: 64, iterations: 1, parallelism: MAX_ARGON2_PARALLELISM + 1 },
+        ];
+        for kdf in excessive {
+            // Rejected when reading the file, before any derivation
+            let mut crafted = keystore.clone();
+            crafted.kdf = kdf;
+            assert!(matches!(
+                Keystore::decode(&crafted.encode()),
+                Err(CryptoError::ParameterError(_))
+            ));
+
+            assert!(Keystore::encrypt(&keypair, b"password", kdf, &mut rng).is_err());
+        }
+    }
+
+    #[test]
+    fn test_keystore_file() {
+        let mut rng = thread_rng();
+        let keypair = KeyManager::<Bls12_381>::new().generate_keypair(&mut rng);
+        let keystore = Keystore::encrypt(&keypair, b"password", TEST_ARGON2, &mut rng).unwrap();
+
+        let path = std::env::temp_dir().join(format!("aporia-keystore-{}", rng.gen::<u64>()));
+        keystore.save(&path).unwrap();
+        assert!(keystore.save(&path).is_err());
+
+        let loaded = Keystore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.decrypt::<Bls12_381>(b"password").unwrap().secret_key, keypair.secret_key);
+    }
+}
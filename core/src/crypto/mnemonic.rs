@@ -0,0 +1,253 @@
+use super::CryptoError;
+use super::keys::{ExtendedKeyPair, HDKeyDeriver};
+use ark_ec::PairingEngine;
+use rand::Rng;
+use sha2::{Digest, Sha256, Sha512};
+use std::sync::OnceLock;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, Zeroizing};
+
+/// BIP39 English wordlist, one word per line
+const ENGLISH_WORDLIST: &str = include_str!("wordlists/english.txt");
+
+/// Number of words in a wordlist
+const WORDLIST_SIZE: usize = 2048;
+
+/// Bits encoded by each word
+const BITS_PER_WORD: usize = 11;
+
+/// PBKDF2 iterations of the seed derivation
+const SEED_ITERATIONS: u32 = 2048;
+
+/// Size of a derived seed
+pub const SEED_SIZE: usize = 64;
+
+/// Supported phrase lengths
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// BIP39 mnemonic phrase
+///
+/// Encodes 128 to 256 bits of entropy followed by a checksum of the first
+/// `entropy bits / 32` bits of its SHA-256 hash, 11 bits per word. The seed
+/// is PBKDF2-HMAC-SHA512 of the NFKD phrase with salt `"mnemonic" ||
+/// NFKD(passphrase)`, so phrases recover the same keys in any BIP39 wallet.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    /// Encoded entropy
+    entropy: Vec<u8>,
+}
+
+impl Mnemonic {
+    /// Generate a random phrase of `word_count` words
+    pub fn generate<R: Rng>(word_count: usize, rng: &mut R) -> Result<Self, CryptoError> {
+        if !WORD_COUNTS.contains(&word_count) {
+            return Err(CryptoError::KeyError(format!("Unsupported phrase length {}", word_count)));
+        }
+        let mut entropy = vec![0u8; word_count * BITS_PER_WORD * 32 / 33 / 8];
+        rng.fill_bytes(&mut entropy);
+        let mnemonic = Self::from_entropy(&entropy);
+        entropy.zeroize();
+        mnemonic
+    }
+
+    /// Phrase encoding `entropy`
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, CryptoError> {
+        if entropy.len() < 16 || entropy.len() > 32 || entropy.len() % 4 != 0 {
+            return Err(CryptoError::KeyError(format!("Unsupported entropy length {}", entropy.len())));
+        }
+        Ok(Self {
+            entropy: entropy.to_vec(),
+        })
+    }
+
+    /// Parse and validate a phrase
+    pub fn parse(phrase: &str) -> Result<Self, CryptoError> {
+        let normalized: Zeroizing<String> = Zeroizing::new(phrase.nfkd().collect());
+        let words: Vec<&str> = normalized.split_whitespace().collect();
+        if !WORD_COUNTS.contains(&words.len()) {
+            return Err(CryptoError::KeyError(format!("Unsupported phrase length {}", words.len())));
+        }
+
+        let mut bits = Zeroizing::new(Vec::with_capacity(words.len() * BITS_PER_WORD));
+        for word in &words {
+            let index = wordlist().binary_search(word)
+                .map_err(|_| CryptoError::KeyError(format!("Unknown word {}", word)))?;
+            bits.extend((0..BITS_PER_WORD).rev().map(|bit| (index >> bit) & 1 == 1));
+        }
+
+        let entropy_bits = bits.len() * 32 / 33;
+        let entropy: Vec<u8> = bits[..entropy_bits]
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+            .collect();
+        let mnemonic = Self { entropy };
+        if mnemonic.checksum_bits() != bits[entropy_bits..] {
+            return Err(CryptoError::KeyError("Invalid phrase checksum".to_string()));
+        }
+        Ok(mnemonic)
+    }
+
+    /// Encoded entropy
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    /// Words of the phrase
+    pub fn words(&self) -> Vec<&'static str> {
+        let mut bits: Vec<bool> = self.entropy.iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1))
+            .collect();
+        bits.extend(self.checksum_bits());
+
+        bits.chunks(BITS_PER_WORD)
+            .map(|chunk| wordlist()[chunk.iter().fold(0usize, |acc, bit| (acc << 1) | *bit as usize)])
+            .collect()
+    }
+
+    /// Space-separated phrase
+    pub fn phrase(&self) -> Zeroizing<String> {
+        Zeroizing::new(self.words().join(" "))
+    }
+
+    /// Seed for hierarchical key derivation, protected by an optional passphrase
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<[u8; SEED_SIZE]> {
+        let mut salt: Zeroizing<String> = Zeroizing::new("mnemonic".to_string());
+        salt.extend(passphrase.nfkd());
+
+        let mut seed = Zeroizing::new([0u8; SEED_SIZE]);
+        pbkdf2::pbkdf2_hmac::<Sha512>(self.phrase().as_bytes(), salt.as_bytes(), SEED_ITERATIONS, &mut seed[..]);
+        seed
+    }
+
+    /// Master key for hierarchical key derivation
+    pub fn to_master_key<E: PairingEngine>(&self, passphrase: &str) -> Result<ExtendedKeyPair<E>, CryptoError> {
+        HDKeyDeriver::<E>::new().master_key(&self.to_seed(passphrase)[..])
+    }
+
+    /// Leading bits of the entropy hash
+    fn checksum_bits(&self) -> Vec<bool> {
+        let hash = Sha256::digest(&self.entropy);
+        (0..self.entropy.len() / 4)
+            .map(|i| (hash[i / 8] >> (7 - i % 8)) & 1 == 1)
+            .collect()
+    }
+}
+
+impl Drop for Mnemonic {
+    fn drop(&mut self) {
+        self.entropy.zeroize();
+    }
+}
+
+impl std::fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Mnemonic({} words)", self.words().len())
+    }
+}
+
+/// Sorted English wordlist
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    let words = WORDS.get_or_init(|| ENGLISH_WORDLIST.lines().collect());
+    debug_assert_eq!(words.len(), WORDLIST_SIZE);
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::keys::KeyManager;
+    use ark_bls12_381::Bls12_381;
+    use rand::thread_rng;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Entropy, phrase and seed with passphrase "TREZOR", from the BIP39 reference vectors
+    const VECTORS: [(&str, &str, &str); 6] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+            "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+        ),
+        (
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+        ),
+        (
+            "9e885d952ad362caeb4efe34a8e91bd2",
+            "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+            "274ddc525802f7c828d8ef7ddbcdc5304e87ac3535913611fbbfa986d0c9e5476c91689f9c8a54fd55bd38606aa6a8595ad213d4c9c9f9aca3fb217069a41028",
+        ),
+        (
+            "f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f",
+            "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold",
+            "01f5bced59dec48e362f2c45b5de68b9fd6c92c6634f44d6d40aab69056506f0e35524a518034ddc1192e1dacd32c1ed3eaa3c3b131c88ed8e7e54c49a5d0998",
+        ),
+    ];
+
+    #[test]
+    fn test_reference_vectors() {
+        assert_eq!(wordlist().len(), WORDLIST_SIZE);
+        assert!(wordlist().windows(2).all(|pair| pair[0] < pair[1]));
+
+        for (entropy, phrase, seed) in VECTORS {
+            let mnemonic = Mnemonic::from_entropy(&from_hex(entropy)).unwrap();
+            assert_eq!(mnemonic.phrase().as_str(), phrase);
+            assert_eq!(mnemonic.to_seed("TREZOR").to_vec(), from_hex(seed));
+
+            let parsed = Mnemonic::parse(phrase).unwrap();
+            assert_eq!(parsed.entropy(), from_hex(entropy).as_slice());
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_phrases() {
+        // Bad checksum, unknown word, unsupported length
+        assert!(Mnemonic::parse("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon").is_err());
+        assert!(Mnemonic::parse("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon aboot").is_err());
+        assert!(Mnemonic::parse("abandon abandon abandon abandon abandon abandon abandon abandon abandon about").is_err());
+        assert!(Mnemonic::from_entropy(&[0u8; 15]).is_err());
+        assert!(Mnemonic::generate(13, &mut thread_rng()).is_err());
+
+        // Whitespace is not significant
+        assert!(Mnemonic::parse("  zoo zoo zoo zoo zoo zoo\tzoo zoo zoo zoo zoo\nwrong ").is_ok());
+    }
+
+    #[test]
+    fn test_recovery_restores_keys() {
+        let mut rng = thread_rng();
+        for word_count in WORD_COUNTS {
+            let mnemonic = Mnemonic::generate(word_count, &mut rng).unwrap();
+            assert_eq!(mnemonic.words().len(), word_count);
+
+            let recovered = Mnemonic::parse(&mnemonic.phrase()).unwrap();
+            let original = mnemonic.to_master_key::<Bls12_381>("passphrase").unwrap();
+            let restored = recovered.to_master_key::<Bls12_381>("passphrase").unwrap();
+            assert_eq!(original.keypair.secret_key, restored.keypair.secret_key);
+            assert!(KeyManager::<Bls12_381>::new().verify_keypair(&restored.keypair));
+
+            let other = recovered.to_master_key::<Bls12_381>("").unwrap();
+            assert_ne!(original.keypair.secret_key, other.keypair.secret_key);
+        }
+
+        // Passphrases are compared after NFKD normalization
+        let mnemonic = Mnemonic::from_entropy(&[1u8; 16]).unwrap();
+        assert_eq!(*mnemonic.to_seed("caf\u{e9}"), *mnemonic.to_seed("cafe\u{301}"));
+    }
+}
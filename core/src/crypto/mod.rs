@@ -3,10 +3,16 @@ use ark_ff::Field;
 use std::error::Error;
 
 pub mod hash;
+pub mod hash_to_curve;
 pub mod keys;
 pub mod zk;
 pub mod signature;
 pub mod encryption;
+pub mod keystore;
+pub mod mnemonic;
+pub mod range_proof;
+pub mod elgamal;
+pub mod threshold;
 pub mod utils;
 
 #[derive(Debug)]
@@ -0,0 +1,551 @@
+use super::CryptoError;
+use super::hash_to_curve::{self, DomainTag, HashToCurve};
+use super::utils::CryptoUtils;
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
+use rand::Rng;
+use std::marker::PhantomData;
+
+/// Bits of each proven value
+pub const RANGE_BITS: usize = 64;
+
+/// Most values in one aggregated proof
+pub const MAX_AGGREGATION: usize = 64;
+
+/// Generators for range proofs of up to `capacity` values
+///
+/// Bit generators are derived from public labels, so no discrete logarithm
+/// relation between them, `G` and the blinding generator `H` is known.
+#[derive(Clone, Debug)]
+pub struct RangeProofGens<E: PairingEngine> {
+    /// Value generator `G`
+    g: E::G1Projective,
+
+    /// Blinding generator `H`
+    h: E::G1Projective,
+
+    /// Bit generators `G_i`
+    g_vec: Vec<E::G1Projective>,
+
+    /// Bit generators `H_i`
+    h_vec: Vec<E::G1Projective>,
+}
+
+impl<E: HashToCurve> RangeProofGens<E> {
+    /// Generators for aggregated proofs of up to `capacity` values
+    ///
+    /// Derivation hashes `2·64·capacity` points, so generators are built once
+    /// and shared by every proof and verification.
+    pub fn new(capacity: usize) -> Result<Self, CryptoError> {
+        if capacity == 0 || capacity > MAX_AGGREGATION {
+            return Err(CryptoError::ParameterError(format!(
+                "Capacity must be between 1 and {} values", MAX_AGGREGATION
+            )));
+        }
+
+        let derive = |prefix: &[u8]| {
+            (0..capacity * RANGE_BITS)
+                .map(|i| {
+                    let mut label = prefix.to_vec();
+                    label.extend_from_slice(&(i as u32).to_be_bytes());
+                    CryptoUtils::derive_generator::<E>(&label)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            g: E::G1Projective::prime_subgroup_generator(),
+            h: CryptoUtils::blinding_generator::<E>()?,
+            g_vec: derive(b"range-proof-g")?,
+            h_vec: derive(b"range-proof-h")?,
+        })
+    }
+
+}
+
+impl<E: PairingEngine> RangeProofGens<E> {
+    /// Most values an aggregated proof may cover
+    pub fn capacity(&self) -> usize {
+        self.g_vec.len() / RANGE_BITS
+    }
+
+    /// Pedersen commitment `value·G + blinding·H` proofs are made against
+    pub fn commit(&self, value: &E::Fr, blinding: &E::Fr) -> E::G1Projective {
+        CryptoUtils::commit_to_field::<E>(value, blinding, &self.h)
+    }
+}
+
+/// Bulletproofs range proof that committed values lie in `[0, 2^64)`
+///
+/// Proves for commitments `V_j = v_j·G + γ_j·H`, as made by
+/// [`RangeProofGens::commit`], that each `v_j` has 64 bits, without
+/// revealing `v_j` or `γ_j`. An aggregated proof covers a power of two
+/// number of values in size logarithmic in their count.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProof<E: PairingEngine> {
+    /// Commitment `A` to the value bits
+    pub a: E::G1Projective,
+
+    /// Commitment `S` to the blinding vectors
+    pub s: E::G1Projective,
+
+    /// Commitment `T1` to the linear coefficient of `t(X)`
+    pub t1: E::G1Projective,
+
+    /// Commitment `T2` to the quadratic coefficient of `t(X)`
+    pub t2: E::G1Projective,
+
+    /// Evaluation `t(x)`
+    pub t_hat: E::Fr,
+
+    /// Blinding of `t(x)`
+    pub tau_x: E::Fr,
+
+    /// Blinding of `A + x·S`
+    pub mu: E::Fr,
+
+    /// Inner product argument points `L_j`
+    pub l: Vec<E::G1Projective>,
+
+    /// Inner product argument points `R_j`
+    pub r: Vec<E::G1Projective>,
+
+    /// Inner product argument final left scalar
+    pub ipp_a: E::Fr,
+
+    /// Inner product argument final right scalar
+    pub ipp_b: E::Fr,
+}
+
+impl<E: PairingEngine> RangeProof<E> {
+    /// Prove `value` is below `2^64`, returning the proof and commitment
+    pub fn prove<R: Rng>(
+        gens: &RangeProofGens<E>,
+        value: u64,
+        blinding: &E::Fr,
+        rng: &mut R,
+    ) -> Result<(Self, E::G1Projective), CryptoError> {
+        let (proof, commitments) = Self::prove_aggregated(gens, &[value], std::slice::from_ref(blinding), rng)?;
+        Ok((proof, commitments[0]))
+    }
+
+    /// Prove every value is below `2^64`, returning the proof and commitments
+    ///
+    /// The number of values must be a power of two within the generator capacity.
+    pub fn prove_aggregated<R: Rng>(
+        gens: &RangeProofGens<E>,
+        values: &[u64],
+        blindings: &[E::Fr],
+        rng: &mut R,
+    ) -> Result<(Self, Vec<E::G1Projective>), CryptoError> {
+        if values.len() != blindings.len() {
+            return Err(CryptoError::ParameterError(
+                "Number of values and blindings must match".to_string()
+            ));
+        }
+        check_count(gens, values.len())?;
+
+        let n = values.len() * RANGE_BITS;
+        let g_vec = &gens.g_vec[..n];
+        let h_vec = &gens.h_vec[..n];
+
+        let commitments: Vec<E::G1Projective> = values.iter()
+            .zip(blindings)
+            .map(|(value, blinding)| gens.commit(&E::Fr::from(*value), blinding))
+            .collect();
+        let mut transcript = Transcript::<E>::new(values.len());
+        for commitment in &commitments {
+            transcript.append_point(commitment)?;
+        }
+
+        // Bits a_L of the values and a_R = a_L - 1
+        let a_l: Vec<E::Fr> = values.iter()
+            .flat_map(|value| (0..RANGE_BITS).map(move |i| E::Fr::from((value >> i) & 1)))
+            .collect();
+        let a_r: Vec<E::Fr> = a_l.iter().map(|bit| *bit - E::Fr::one()).collect();
+
+        let alpha = E::Fr::rand(rng);
+        let a = gens.h.mul(alpha.into_repr()) + msm::<E>(g_vec, &a_l) + msm::<E>(h_vec, &a_r);
+
+        let s_l: Vec<E::Fr> = (0..n).map(|_| E::Fr::rand(rng)).collect();
+        let s_r: Vec<E::Fr> = (0..n).map(|_| E::Fr::rand(rng)).collect();
+        let rho = E::Fr::rand(rng);
+        let s = gens.h.mul(rho.into_repr()) + msm::<E>(g_vec, &s_l) + msm::<E>(h_vec, &s_r);
+
+        transcript.append_point(&a)?;
+        transcript.append_point(&s)?;
+        let y = transcript.challenge()?;
+        let z = transcript.challenge()?;
+
+        // l(X) = l0 + l1·X and r(X) = r0 + r1·X with t(X) = <l(X), r(X)>
+        let y_powers = powers(y, n);
+        let weights = value_weights(z, values.len());
+        let l0: Vec<E::Fr> = a_l.iter().map(|bit| *bit - z).collect();
+        let r0: Vec<E::Fr> = (0..n)
+            .map(|i| y_powers[i] * (a_r[i] + z) + bit_weight(&weights, i))
+            .collect();
+        let r1: Vec<E::Fr> = (0..n).map(|i| y_powers[i] * s_r[i]).collect();
+
+        let t1 = inner_product(&l0, &r1) + inner_product(&s_l, &r0);
+        let t2 = inner_product(&s_l, &r1);
+        let tau1 = E::Fr::rand(rng);
+        let tau2 = E::Fr::rand(rng);
+        let t1_commitment = gens.commit(&t1, &tau1);
+        let t2_commitment = gens.commit(&t2, &tau2);
+
+        transcript.append_point(&t1_commitment)?;
+        transcript.append_point(&t2_commitment)?;
+        let x = transcript.challenge()?;
+
+        let l: Vec<E::Fr> = l0.iter().zip(&s_l).map(|(l0, l1)| *l0 + *l1 * x).collect();
+        let r: Vec<E::Fr> = r0.iter().zip(&r1).map(|(r0, r1)| *r0 + *r1 * x).collect();
+        let t_hat = inner_product(&l, &r);
+        let tau_x = tau2 * x * x + tau1 * x
+            + weights.iter().zip(blindings).map(|(weight, blinding)| *weight * blinding).sum::<E::Fr>();
+        let mu = alpha + rho * x;
+
+        transcript.append_scalar(&t_hat)?;
+        transcript.append_scalar(&tau_x)?;
+        transcript.append_scalar(&mu)?;
+        let w = transcript.challenge()?;
+        let q = gens.g.mul(w.into_repr());
+
+        // Inner product argument over G_i and H'_i = y^-i·H_i
+        let y_inv = inverse(&y)?;
+        let h_prime: Vec<E::G1Projective> = h_vec.iter()
+            .zip(powers(y_inv, n))
+            .map(|(h, power)| h.mul(power.into_repr()))
+            .collect();
+        let (l_points, r_points, ipp_a, ipp_b) = inner_product_prove(&mut transcript, &q, g_vec.to_vec(), h_prime, l, r)?;
+
+        let proof = Self {
+            a,
+            s,
+            t1: t1_commitment,
+            t2: t2_commitment,
+            t_hat,
+            tau_x,
+            mu,
+            l: l_points,
+            r: r_points,
+            ipp_a,
+            ipp_b,
+        };
+        Ok((proof, commitments))
+    }
+
+    /// Verify the proof for `commitments`
+    pub fn verify(&self, gens: &RangeProofGens<E>, commitments: &[E::G1Projective]) -> Result<bool, CryptoError> {
+        Self::verify_batch(gens, &[(self, commitments)])
+    }
+
+    /// Verify proofs, each with its commitments, at once
+    ///
+    /// Every check of every proof is weighted by a random 128-bit scalar and
+    /// summed into one multi-scalar multiplication, which is zero for a batch
+    /// containing an invalid proof with probability at most 2^-127.
+    pub fn verify_batch(
+        gens: &RangeProofGens<E>,
+        proofs: &[(&Self, &[E::G1Projective])],
+    ) -> Result<bool, CryptoError> {
+        let mut rng = rand::thread_rng();
+        let count = proofs.iter().map(|(_, commitments)| commitments.len()).max().unwrap_or(0);
+        if count > gens.capacity() {
+            return Err(CryptoError::ParameterError(format!(
+                "Generators support {} values, proof has {}", gens.capacity(), count
+            )));
+        }
+
+        let mut g_scalar = E::Fr::zero();
+        let mut h_scalar = E::Fr::zero();
+        let mut g_vec_scalars = vec![E::Fr::zero(); count * RANGE_BITS];
+        let mut h_vec_scalars = vec![E::Fr::zero(); count * RANGE_BITS];
+        let mut bases = Vec::new();
+        let mut scalars = Vec::new();
+
+        for (proof, commitments) in proofs {
+            if commitments.is_empty() || !commitments.len().is_power_of_two() {
+                return Ok(false);
+            }
+            let n = commitments.len() * RANGE_BITS;
+            let rounds = n.trailing_zeros() as usize;
+            if proof.l.len() != rounds || proof.r.len() != rounds {
+                return Ok(false);
+            }
+
+            // Replay the prover's transcript
+            let mut transcript = Transcript::<E>::new(commitments.len());
+            for commitment in commitments.iter() {
+                transcript.append_point(commitment)?;
+            }
+            transcript.append_point(&proof.a)?;
+            transcript.append_point(&proof.s)?;
+            let y = transcript.challenge()?;
+            let z = transcript.challenge()?;
+            transcript.append_point(&proof.t1)?;
+            transcript.append_point(&proof.t2)?;
+            let x = transcript.challenge()?;
+            transcript.append_scalar(&proof.t_hat)?;
+            transcript.append_scalar(&proof.tau_x)?;
+            transcript.append_scalar(&proof.mu)?;
+            let w = transcript.challenge()?;
+
+            let mut challenges = Vec::with_capacity(rounds);
+            for (l, r) in proof.l.iter().zip(&proof.r) {
+                transcript.append_point(l)?;
+                transcript.append_point(r)?;
+                let u: E::Fr = transcript.challenge()?;
+                challenges.push((u, inverse(&u)?));
+            }
+
+            // Folded generator coefficients s_i, with 1/s_i = s_(n-1-i)
+            let mut s = Vec::with_capacity(n);
+            s.push(challenges.iter().map(|(_, u_inv)| *u_inv).product::<E::Fr>());
+            for i in 1..n {
+                let bit = (usize::BITS - 1 - i.leading_zeros()) as usize;
+                let (u, _) = challenges[rounds - 1 - bit];
+                s.push(s[i - (1 << bit)] * u * u);
+            }
+
+            // Weights of the proof and of its polynomial check within it
+            let beta = E::Fr::from(rng.gen::<u128>() | 1);
+            let c = beta * E::Fr::from(rng.gen::<u128>() | 1);
+
+            let y_inv_powers = powers(inverse(&y)?, n);
+            let weights = value_weights(z, commitments.len());
+            let delta = (z - z * z) * powers(y, n).iter().sum::<E::Fr>()
+                - z * weights.iter().sum::<E::Fr>() * E::Fr::from(u64::MAX);
+
+            // t(x)·G + τx·H = sum z^(2+j)·V_j + δ·G + x·T1 + x²·T2
+            g_scalar += c * (proof.t_hat - delta) + beta * w * (proof.t_hat - proof.ipp_a * proof.ipp_b);
+            h_scalar += c * proof.tau_x - beta * proof.mu;
+            for (commitment, weight) in commitments.iter().zip(&weights) {
+                bases.push(*commitment);
+                scalars.push(-(c * weight));
+            }
+            bases.extend_from_slice(&[proof.t1, proof.t2]);
+            scalars.extend_from_slice(&[-(c * x), -(c * x * x)]);
+
+            // A + x·S - z·<1, G> + <z·y^n + z^(2+j)·2^n, H'> - μ·H folds to the inner product
+            bases.extend_from_slice(&[proof.a, proof.s]);
+            scalars.extend_from_slice(&[beta, beta * x]);
+            for i in 0..n {
+                g_vec_scalars[i] -= beta * (z + proof.ipp_a * s[i]);
+                h_vec_scalars[i] += beta * (z + (bit_weight(&weights, i) - proof.ipp_b * s[n - 1 - i]) * y_inv_powers[i]);
+            }
+            for ((l, r), (u, u_inv)) in proof.l.iter().zip(&proof.r).zip(&challenges) {
+                bases.extend_from_slice(&[*l, *r]);
+                scalars.extend_from_slice(&[beta * u * u, beta * u_inv * u_inv]);
+            }
+        }
+
+        bases.extend_from_slice(&[gens.g, gens.h]);
+        scalars.extend_from_slice(&[g_scalar, h_scalar]);
+        bases.extend_from_slice(&gens.g_vec[..g_vec_scalars.len()]);
+        scalars.extend(g_vec_scalars);
+        bases.extend_from_slice(&gens.h_vec[..h_vec_scalars.len()]);
+        scalars.extend(h_vec_scalars);
+        Ok(msm::<E>(&bases, &scalars).is_zero())
+    }
+}
+
+/// Fiat-Shamir transcript, each challenge chaining into the next
+struct Transcript<E: PairingEngine> {
+    /// Messages since the last challenge, preceded by that challenge
+    state: Vec<u8>,
+
+    _engine: PhantomData<E>,
+}
+
+impl<E: PairingEngine> Transcript<E> {
+    /// Transcript bound to the proof size
+    fn new(count: usize) -> Self {
+        let mut state = (RANGE_BITS as u64).to_be_bytes().to_vec();
+        state.extend_from_slice(&(count as u64).to_be_bytes());
+        Self {
+            state,
+            _engine: PhantomData,
+        }
+    }
+
+    fn append_point(&mut self, point: &E::G1Projective) -> Result<(), CryptoError> {
+        point.into_affine().serialize(&mut self.state)
+            .map_err(|e| CryptoError::ProofError(e.to_string()))
+    }
+
+    fn append_scalar(&mut self, scalar: &E::Fr) -> Result<(), CryptoError> {
+        scalar.serialize(&mut self.state)
+            .map_err(|e| CryptoError::ProofError(e.to_string()))
+    }
+
+    fn challenge(&mut self) -> Result<E::Fr, CryptoError> {
+        let challenge: E::Fr = hash_to_curve::hash_to_scalar(&self.state, DomainTag::RANGE_PROOF)?;
+        self.state.clear();
+        self.append_scalar(&challenge)?;
+        Ok(challenge)
+    }
+}
+
+/// Reject value counts the generators cannot prove
+fn check_count<E: PairingEngine>(gens: &RangeProofGens<E>, count: usize) -> Result<(), CryptoError> {
+    if count == 0 || !count.is_power_of_two() || count > gens.capacity() {
+        return Err(CryptoError::ParameterError(format!(
+            "Number of values must be a power of two up to {}", gens.capacity()
+        )));
+    }
+    Ok(())
+}
+
+/// Inner product argument for `P = <a, G> + <b, H> + <a, b>·Q`
+///
+/// Halves the vectors each round, recording the cross terms `L` and `R`.
+fn inner_product_prove<E: PairingEngine>(
+    transcript: &mut Transcript<E>,
+    q: &E::G1Projective,
+    mut g: Vec<E::G1Projective>,
+    mut h: Vec<E::G1Projective>,
+    mut a: Vec<E::Fr>,
+    mut b: Vec<E::Fr>,
+) -> Result<(Vec<E::G1Projective>, Vec<E::G1Projective>, E::Fr, E::Fr), CryptoError> {
+    let mut l_points = Vec::new();
+    let mut r_points = Vec::new();
+
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_lo, a_hi) = a.split_at(half);
+        let (b_lo, b_hi) = b.split_at(half);
+        let (g_lo, g_hi) = g.split_at(half);
+        let (h_lo, h_hi) = h.split_at(half);
+
+        let l = msm::<E>(g_hi, a_lo) + msm::<E>(h_lo, b_hi) + q.mul(inner_product(a_lo, b_hi).into_repr());
+        let r = msm::<E>(g_lo, a_hi) + msm::<E>(h_hi, b_lo) + q.mul(inner_product(a_hi, b_lo).into_repr());
+        transcript.append_point(&l)?;
+        transcript.append_point(&r)?;
+        let u = transcript.challenge()?;
+        let u_inv = inverse(&u)?;
+
+        let next_a = (0..half).map(|i| a_lo[i] * u + a_hi[i] * u_inv).collect();
+        let next_b = (0..half).map(|i| b_lo[i] * u_inv + b_hi[i] * u).collect();
+        let next_g = (0..half).map(|i| g_lo[i].mul(u_inv.into_repr()) + g_hi[i].mul(u.into_repr())).collect();
+        let next_h = (0..half).map(|i| h_lo[i].mul(u.into_repr()) + h_hi[i].mul(u_inv.into_repr())).collect();
+        a = next_a;
+        b = next_b;
+        g = next_g;
+        h = next_h;
+
+        l_points.push(l);
+        r_points.push(r);
+    }
+
+    Ok((l_points, r_points, a[0], b[0]))
+}
+
+/// Weights `z^(2+j)` of each value's bits in `r(X)`
+fn value_weights<F: Field>(z: F, count: usize) -> Vec<F> {
+    powers(z, count).into_iter().map(|power| power * z * z).collect()
+}
+
+/// Weight `z^(2+j)·2^k` of bit `k` of value `j`
+fn bit_weight<F: Field>(weights: &[F], index: usize) -> F {
+    weights[index / RANGE_BITS] * F::from(2u64).pow([(index % RANGE_BITS) as u64])
+}
+
+/// Powers `1, base, .., base^(count-1)`
+fn powers<F: Field>(base: F, count: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(count);
+    let mut power = F::one();
+    for _ in 0..count {
+        powers.push(power);
+        power *= base;
+    }
+    powers
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+fn inverse<F: Field>(value: &F) -> Result<F, CryptoError> {
+    value.inverse()
+        .ok_or_else(|| CryptoError::ProofError("Challenge is zero".to_string()))
+}
+
+/// Multi-scalar multiplication `sum scalars[i]·bases[i]`
+fn msm<E: PairingEngine>(bases: &[E::G1Projective], scalars: &[E::Fr]) -> E::G1Projective {
+    let bases = E::G1Projective::batch_normalization_into_affine(bases);
+    let scalars: Vec<_> = scalars.iter().map(|scalar| scalar.into_repr()).collect();
+    VariableBaseMSM::multi_scalar_mul(&bases, &scalars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_single_proof() {
+        let mut rng = thread_rng();
+        let gens = RangeProofGens::<Bls12_381>::new(1).unwrap();
+
+        for value in [0u64, 1, 1 << 32, u64::MAX] {
+            let blinding = Fr::rand(&mut rng);
+            let (proof, commitment) = RangeProof::prove(&gens, value, &blinding, &mut rng).unwrap();
+            assert_eq!(commitment, gens.commit(&Fr::from(value), &blinding));
+            assert!(proof.verify(&gens, &[commitment]).unwrap());
+        }
+
+        // Commitment to another value, or to a value of 64 bits plus one
+        let blinding = Fr::rand(&mut rng);
+        let (proof, _) = RangeProof::prove(&gens, 42, &blinding, &mut rng).unwrap();
+        let other = gens.commit(&Fr::from(43u64), &blinding);
+        assert!(!proof.verify(&gens, &[other]).unwrap());
+        let wrapped = gens.commit(&(Fr::from(u64::MAX) + Fr::from(43u64)), &blinding);
+        assert!(!proof.verify(&gens, &[wrapped]).unwrap());
+    }
+
+    #[test]
+    fn test_aggregated_proof() {
+        let mut rng = thread_rng();
+        let gens = RangeProofGens::<Bls12_381>::new(4).unwrap();
+        let values = [7u64, 0, u64::MAX, 1 << 63];
+        let blindings: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+
+        let (proof, commitments) = RangeProof::prove_aggregated(&gens, &values, &blindings, &mut rng).unwrap();
+        assert_eq!(proof.l.len(), 8);
+        assert!(proof.verify(&gens, &commitments).unwrap());
+
+        // Reordered commitments or a tampered proof
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(!proof.verify(&gens, &swapped).unwrap());
+        let mut tampered = proof.clone();
+        tampered.t_hat += Fr::one();
+        assert!(!tampered.verify(&gens, &commitments).unwrap());
+
+        // Counts that are not a power of two or exceed the generators
+        assert!(RangeProof::prove_aggregated(&gens, &values[..3], &blindings[..3], &mut rng).is_err());
+        assert!(!proof.verify(&gens, &commitments[..3]).unwrap());
+        assert!(RangeProofGens::<Bls12_381>::new(MAX_AGGREGATION + 1).is_err());
+    }
+
+    #[test]
+    fn test_batch_verification() {
+        let mut rng = thread_rng();
+        let gens = RangeProofGens::<Bls12_381>::new(2).unwrap();
+
+        let (single, commitment) = RangeProof::prove(&gens, 1000, &Fr::rand(&mut rng), &mut rng).unwrap();
+        let blindings = [Fr::rand(&mut rng), Fr::rand(&mut rng)];
+        let (pair, commitments) = RangeProof::prove_aggregated(&gens, &[5, 6], &blindings, &mut rng).unwrap();
+
+        let commitment = [commitment];
+        assert!(RangeProof::verify_batch(&gens, &[(&single, &commitment), (&pair, &commitments)]).unwrap());
+        assert!(RangeProof::verify_batch(&gens, &[]).unwrap());
+
+        let mut tampered = pair.clone();
+        tampered.ipp_b += Fr::one();
+        assert!(!RangeProof::verify_batch(&gens, &[(&single, &commitment), (&tampered, &commitments)]).unwrap());
+    }
+}
@@ -1,15 +1,44 @@
 use super::CryptoError;
-use ark_ec::PairingEngine;
-use ark_ff::Field;
+use super::hash_to_curve::{self, DomainTag};
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{BigInteger, PrimeField, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use sha3::{Sha3_256, Digest};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::{Digest, Sha256};
 
-/// Digital signature scheme
+/// Batches at most this large are searched by verifying each signature
+const BATCH_SEARCH_CUTOFF: usize = 4;
+
+/// Size of nonce entropy in bytes
+pub const ENTROPY_SIZE: usize = 32;
+
+/// Schnorr signatures over G1
+///
+/// A signature on `m` under `P = x·G` is `(R, s)` with `R = k·G`,
+/// `e = H(R || P || m)` over compressed points and `s = k + e·x`; it verifies
+/// if `s·G = R + e·P`. `H` hashes to the scalar field under
+/// [`DomainTag::SIGNATURE_CHALLENGE`], and nonces follow RFC 6979 with fresh
+/// entropy as additional data.
 pub struct SignatureScheme<E: PairingEngine> {
     /// Security parameter
     security_level: usize,
 }
 
+/// Signature of a batch with the message and key it must verify against
+#[derive(Clone, Copy, Debug)]
+pub struct BatchItem<'a, E: PairingEngine> {
+    /// Signed message
+    pub message: &'a [u8],
+
+    /// Signature
+    pub signature: &'a Signature<E>,
+
+    /// Signer public key
+    pub public_key: &'a E::G1Projective,
+}
+
 /// Signature structure
 #[derive(Clone, Debug)]
 pub struct Signature<E: PairingEngine> {
@@ -38,20 +67,36 @@ impl<E: PairingEngine> SignatureScheme<E> {
         message: &[u8],
         private_key: &E::Fr,
     ) -> Result<Signature<E>, CryptoError> {
-        // Generate random nonce
-        let k = self.generate_nonce(message, private_key)?;
-        
+        let mut entropy = [0u8; ENTROPY_SIZE];
+        rand::thread_rng().fill(&mut entropy[..]);
+        self.sign_with_entropy(message, private_key, &entropy)
+    }
+
+    /// Sign message with caller-supplied nonce entropy
+    ///
+    /// The signature is deterministic in the entropy, so repeating it is safe
+    /// but gives up the protection fresh entropy offers against fault attacks.
+    pub fn sign_with_entropy(
+        &self,
+        message: &[u8],
+        private_key: &E::Fr,
+        entropy: &[u8; ENTROPY_SIZE],
+    ) -> Result<Signature<E>, CryptoError> {
+        if private_key.is_zero() {
+            return Err(CryptoError::SignatureError("Private key is zero".to_string()));
+        }
+
         // Compute R = kG
+        let k = self.generate_nonce(message, private_key, entropy)?;
         let g = E::G1Projective::prime_subgroup_generator();
         let r = g.mul(k.into_repr());
         
-        // Compute hash of message and R
-        let h = self.hash_message_and_point(message, &r)?;
-        
-        // Compute s = k - h * private_key
-        let s = k - (h * private_key);
+        // Compute challenge over R, public key and message
+        let public_key = g.mul(private_key.into_repr());
+        let e = self.challenge(message, &r, &public_key)?;
         
-        Ok(Signature { r, s })
+        // Compute s = k + e * private_key
+        Ok(Signature { r, s: k + e * private_key })
     }
 
     /// Verify signature using public key
@@ -61,13 +106,17 @@ impl<E: PairingEngine> SignatureScheme<E> {
         signature: &Signature<E>,
         public_key: &E::G1Projective,
     ) -> Result<bool, CryptoError> {
-        // Compute hash of message and R
-        let h = self.hash_message_and_point(message, &signature.r)?;
+        // Any signature satisfies the equation under the identity key
+        if public_key.is_zero() || signature.r.is_zero() {
+            return Ok(false);
+        }
+
+        let e = self.challenge(message, &signature.r, public_key)?;
         
-        // Verify equation: sG = R - hP
+        // Verify equation: sG = R + eP
         let g = E::G1Projective::prime_subgroup_generator();
         let left = g.mul(signature.s.into_repr());
-        let right = signature.r - public_key.mul(h.into_repr());
+        let right = signature.r + public_key.mul(e.into_repr());
         
         Ok(left == right)
     }
@@ -96,66 +145,159 @@ impl<E: PairingEngine> SignatureScheme<E> {
             ));
         }
 
-        // Verify all signatures
-        for (msg, sig) in messages.iter().zip(signatures) {
-            if !self.verify(msg, sig, public_key)? {
-                return Ok(false);
+        let items: Vec<_> = messages.iter()
+            .zip(signatures)
+            .map(|(message, signature)| BatchItem { message: *message, signature, public_key })
+            .collect();
+        Ok(self.verify_batch(&items)?.is_none())
+    }
+
+    /// Verify signatures under possibly different keys at once
+    ///
+    /// Returns the index of the first invalid signature, if any. All
+    /// signatures are checked with one randomized multi-scalar multiplication;
+    /// a failing batch is bisected to locate the invalid signature.
+    pub fn verify_batch(&self, items: &[BatchItem<E>]) -> Result<Option<usize>, CryptoError> {
+        if self.batch_holds(items)? {
+            return Ok(None);
+        }
+        self.find_invalid(items).map(Some)
+    }
+
+    /// Check random linear combination of the verification equations
+    ///
+    /// Each signature satisfies `sG - R - eP = 0`; with random 128-bit
+    /// weights `z`, `(sum z*s)G - sum z*R - sum (z*e)P = 0` holds for a batch
+    /// containing an invalid signature with probability at most 2^-128.
+    fn batch_holds(&self, items: &[BatchItem<E>]) -> Result<bool, CryptoError> {
+        if items.is_empty() {
+            return Ok(true);
+        }
+        if items.iter().any(|item| item.public_key.is_zero() || item.signature.r.is_zero()) {
+            return Ok(false);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut bases = Vec::with_capacity(2 * items.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * items.len() + 1);
+        let mut s_sum = E::Fr::zero();
+
+        bases.push(E::G1Projective::prime_subgroup_generator());
+        scalars.push(E::Fr::zero());
+        for item in items {
+            let z = E::Fr::from(rng.gen::<u128>() | 1);
+            let e = self.challenge(item.message, &item.signature.r, item.public_key)?;
+
+            s_sum += z * item.signature.s;
+            bases.push(item.signature.r);
+            scalars.push(-z);
+            bases.push(*item.public_key);
+            scalars.push(-(z * e));
+        }
+        scalars[0] = s_sum;
+
+        let bases = E::G1Projective::batch_normalization_into_affine(&bases);
+        let scalars: Vec<_> = scalars.iter().map(|scalar| scalar.into_repr()).collect();
+        Ok(VariableBaseMSM::multi_scalar_mul(&bases, &scalars).is_zero())
+    }
+
+    /// Locate first invalid signature of a failing batch
+    fn find_invalid(&self, items: &[BatchItem<E>]) -> Result<usize, CryptoError> {
+        if items.len() <= BATCH_SEARCH_CUTOFF {
+            for (index, item) in items.iter().enumerate() {
+                if !self.verify(item.message, item.signature, item.public_key)? {
+                    return Ok(index);
+                }
             }
+            return Err(CryptoError::SignatureError(
+                "Batch failed but every signature verifies".to_string()
+            ));
         }
 
-        Ok(true)
+        let (left, right) = items.split_at(items.len() / 2);
+        if !self.batch_holds(left)? {
+            self.find_invalid(left)
+        } else {
+            Ok(left.len() + self.find_invalid(right)?)
+        }
     }
 
-    /// Generate deterministic nonce (RFC 6979)
-    fn generate_nonce(&self, message: &[u8], private_key: &E::Fr) -> Result<E::Fr, CryptoError> {
-        let mut hasher = Sha3_256::new();
-        
-        // Add private key to hash
-        let pk_bytes = private_key.into_repr().to_bytes_le();
-        hasher.update(&pk_bytes);
-        
-        // Add message to hash
-        hasher.update(message);
-        
-        // Generate nonce
-        let hash = hasher.finalize();
-        
-        E::Fr::from_random_bytes(&hash).ok_or_else(|| {
-            CryptoError::SignatureError("Failed to generate nonce".to_string())
-        })
+    /// Hedged deterministic nonce (RFC 6979, section 3.6)
+    ///
+    /// HMAC-SHA256 DRBG seeded with the private key and SHA-256 of the
+    /// message, with the nonce domain tag and `entropy` as additional data.
+    fn generate_nonce(
+        &self,
+        message: &[u8],
+        private_key: &E::Fr,
+        entropy: &[u8; ENTROPY_SIZE],
+    ) -> Result<E::Fr, CryptoError> {
+        let bits = E::Fr::size_in_bits();
+        let digest = E::Fr::from_be_bytes_mod_order(&bits_to_int(&Sha256::digest(message), bits));
+
+        let mut seed = int_to_octets::<E>(private_key);
+        seed.extend_from_slice(&int_to_octets::<E>(&digest));
+        seed.extend_from_slice(DomainTag::SIGNATURE_NONCE.as_bytes());
+        seed.extend_from_slice(entropy);
+
+        let mut v = [0x01u8; 32];
+        let mut k = hmac_sha256(&[0x00u8; 32], &[&v, &[0x00], &seed])?;
+        v = hmac_sha256(&k, &[&v])?;
+        k = hmac_sha256(&k, &[&v, &[0x01], &seed])?;
+        v = hmac_sha256(&k, &[&v])?;
+
+        loop {
+            let mut t = Vec::new();
+            while t.len() * 8 < bits {
+                v = hmac_sha256(&k, &[&v])?;
+                t.extend_from_slice(&v);
+            }
+
+            // Accept only candidates in [1, q - 1], without reduction
+            let candidate = bits_to_int(&t, bits);
+            let nonce = E::Fr::from_be_bytes_mod_order(&candidate);
+            if !nonce.is_zero() && int_to_octets::<E>(&nonce) == candidate {
+                return Ok(nonce);
+            }
+
+            k = hmac_sha256(&k, &[&v, &[0x00]])?;
+            v = hmac_sha256(&k, &[&v])?;
+        }
     }
 
-    /// Hash message and elliptic curve point
-    fn hash_message_and_point(
+    /// Challenge `e = H(R || P || m)` over compressed points
+    ///
+    /// Reduced modulo the group order so every `R` has a challenge; threshold
+    /// signers compute it after spending their nonces and cannot retry.
+    pub(crate) fn challenge(
         &self,
         message: &[u8],
-        point: &E::G1Projective,
+        r: &E::G1Projective,
+        public_key: &E::G1Projective,
     ) -> Result<E::Fr, CryptoError> {
-        let mut hasher = Sha3_256::new();
-        
-        // Add point coordinates to hash
-        let point_bytes = point.into_affine().into_repr().to_bytes_le();
-        hasher.update(&point_bytes);
-        
-        // Add message to hash
-        hasher.update(message);
-        
-        // Generate field element
-        let hash = hasher.finalize();
+        let mut data = Vec::new();
+        for point in [r, public_key] {
+            point.into_affine().serialize(&mut data)
+                .map_err(|e| CryptoError::SignatureError(e.to_string()))?;
+        }
+        data.extend_from_slice(message);
         
-        E::Fr::from_random_bytes(&hash).ok_or_else(|| {
-            CryptoError::SignatureError("Failed to hash message and point".to_string())
-        })
+        hash_to_curve::hash_to_scalar(&data, DomainTag::SIGNATURE_CHALLENGE)
     }
 }
 
 impl<E: PairingEngine> Signature<E> {
-    /// Serialize signature to bytes
+    /// Size of an encoded signature
+    pub fn encoded_size() -> usize {
+        E::G1Affine::prime_subgroup_generator().serialized_size() + E::Fr::zero().serialized_size()
+    }
+
+    /// Serialize signature as compressed `R` followed by `s`
     pub fn to_bytes(&self) -> Result<Vec<u8>, CryptoError> {
-        let mut bytes = Vec::new();
+        let mut bytes = Vec::with_capacity(Self::encoded_size());
         
         // Serialize R
-        self.r.serialize(&mut bytes)
+        self.r.into_affine().serialize(&mut bytes)
             .map_err(|e| CryptoError::SignatureError(format!("Failed to serialize R: {}", e)))?;
         
         // Serialize s
@@ -165,16 +307,68 @@ impl<E: PairingEngine> Signature<E> {
         Ok(bytes)
     }
 
-    /// Deserialize signature from bytes
+    /// Deserialize signature, accepting only the canonical encoding
+    ///
+    /// `R` must be a compressed point of the prime order subgroup other than
+    /// the identity, and `s` must be reduced.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
-        let r = E::G1Projective::deserialize(bytes)
+        if bytes.len() != Self::encoded_size() {
+            return Err(CryptoError::SignatureError(format!(
+                "Signature must be {} bytes", Self::encoded_size()
+            )));
+        }
+
+        let mut reader = bytes;
+        let r = E::G1Affine::deserialize(&mut reader)
             .map_err(|e| CryptoError::SignatureError(format!("Failed to deserialize R: {}", e)))?;
-        
-        let s = E::Fr::deserialize(&bytes[96..])
+        let s = E::Fr::deserialize(&mut reader)
             .map_err(|e| CryptoError::SignatureError(format!("Failed to deserialize s: {}", e)))?;
-        
-        Ok(Self { r, s })
+        if r.is_zero() {
+            return Err(CryptoError::SignatureError("R is the identity".to_string()));
+        }
+
+        // Reject encodings that decode but do not re-encode to the same bytes
+        let signature = Self { r: r.into_projective(), s };
+        if signature.to_bytes()? != bytes {
+            return Err(CryptoError::SignatureError("Non-canonical signature".to_string()));
+        }
+        Ok(signature)
+    }
+}
+
+/// HMAC-SHA256 of the concatenated parts
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> Result<[u8; 32], CryptoError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|e| CryptoError::SignatureError(e.to_string()))?;
+    for part in parts {
+        mac.update(part);
     }
+    let mut output = [0u8; 32];
+    output.copy_from_slice(&mac.finalize().into_bytes());
+    Ok(output)
+}
+
+/// Leftmost `bits` bits of `bytes` as a `ceil(bits / 8)` byte integer (RFC 6979 `bits2int`)
+fn bits_to_int(bytes: &[u8], bits: usize) -> Vec<u8> {
+    let len = bits.div_ceil(8);
+    let take = bytes.len().min(len);
+    let mut int = vec![0u8; len];
+    int[len - take..].copy_from_slice(&bytes[..take]);
+
+    let excess = (take * 8).saturating_sub(bits);
+    if excess > 0 {
+        for i in (0..len).rev() {
+            let carry = if i > 0 { int[i - 1] << (8 - excess) } else { 0 };
+            int[i] = (int[i] >> excess) | carry;
+        }
+    }
+    int
+}
+
+/// Scalar as a `ceil(bits / 8)` byte big-endian integer (RFC 6979 `int2octets`)
+fn int_to_octets<E: PairingEngine>(scalar: &E::Fr) -> Vec<u8> {
+    let bytes = scalar.into_repr().to_bytes_be();
+    bytes[bytes.len() - E::Fr::size_in_bits().div_ceil(8)..].to_vec()
 }
 
 #[cfg(test)]
@@ -183,6 +377,15 @@ mod tests {
     use ark_bls12_381::{Bls12_381, Fr};
     use rand::thread_rng;
 
+    const SIGNATURE_VECTORS: &str = include_str!("../../../tools/tests/vectors/signature.json");
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
     #[test]
     fn test_signature_scheme() {
         let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
@@ -197,6 +400,12 @@ mod tests {
         let signature = scheme.sign(message, &private_key).unwrap();
         let valid = scheme.verify(message, &signature, &public_key).unwrap();
         assert!(valid);
+
+        // Fresh entropy gives a fresh nonce
+        let other = scheme.sign(message, &private_key).unwrap();
+        assert_ne!(signature.r, other.r);
+        assert!(scheme.verify(message, &other, &public_key).unwrap());
+        assert!(scheme.sign(message, &Fr::zero()).is_err());
     }
 
     #[test]
@@ -229,7 +438,71 @@ mod tests {
         let bytes = signature.to_bytes().unwrap();
         let deserialized = Signature::from_bytes(&bytes).unwrap();
         
+        assert_eq!(bytes.len(), Signature::<Bls12_381>::encoded_size());
         assert_eq!(signature.r, deserialized.r);
         assert_eq!(signature.s, deserialized.s);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_signature_vectors() {
+        let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
+        let vectors: serde_json::Value = serde_json::from_str(SIGNATURE_VECTORS).unwrap();
+        let hex = |value: &serde_json::Value| from_hex(value.as_str().unwrap());
+        let public_key = |bytes: &[u8]| {
+            <Bls12_381 as PairingEngine>::G1Affine::deserialize(bytes).unwrap().into_projective()
+        };
+
+        for vector in vectors["valid"].as_array().unwrap() {
+            let name = vector["name"].as_str().unwrap();
+            let private_key = Fr::deserialize(&hex(&vector["secret_key"])[..]).unwrap();
+            let message = hex(&vector["message"]);
+            let mut entropy = [0u8; ENTROPY_SIZE];
+            entropy.copy_from_slice(&hex(&vector["entropy"]));
+            let expected = hex(&vector["signature"]);
+
+            let signature = scheme.sign_with_entropy(&message, &private_key, &entropy).unwrap();
+            assert_eq!(signature.to_bytes().unwrap(), expected, "{}", name);
+
+            let public_key = public_key(&hex(&vector["public_key"]));
+            assert_eq!(public_key, Bls12_381::G1Projective::prime_subgroup_generator().mul(private_key.into_repr()), "{}", name);
+            let decoded = Signature::from_bytes(&expected).unwrap();
+            assert!(scheme.verify(&message, &decoded, &public_key).unwrap(), "{}", name);
+        }
+
+        // Each invalid vector fails to decode or to verify
+        for vector in vectors["invalid"].as_array().unwrap() {
+            let name = vector["name"].as_str().unwrap();
+            let public_key = public_key(&hex(&vector["public_key"]));
+            let message = hex(&vector["message"]);
+            let valid = Signature::<Bls12_381>::from_bytes(&hex(&vector["signature"]))
+                .map_or(false, |signature| scheme.verify(&message, &signature, &public_key).unwrap());
+            assert!(!valid, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_finds_invalid_signature() {
+        let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
+        let g = Bls12_381::G1Projective::prime_subgroup_generator();
+
+        let messages: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 8]).collect();
+        let secrets: Vec<Fr> = (0..10).map(|_| Fr::rand(&mut thread_rng())).collect();
+        let keys: Vec<_> = secrets.iter().map(|sk| g.mul(sk.into_repr())).collect();
+        let mut signatures: Vec<_> = messages.iter()
+            .zip(&secrets)
+            .map(|(message, sk)| scheme.sign(message, sk).unwrap())
+            .collect();
+
+        let batch: Vec<_> = (0..10)
+            .map(|i| BatchItem { message: &messages[i][..], signature: &signatures[i], public_key: &keys[i] })
+            .collect();
+        assert_eq!(scheme.verify_batch(&batch).unwrap(), None);
+
+        // Signature under the wrong key is located
+        signatures[7] = scheme.sign(&messages[7], &secrets[6]).unwrap();
+        let batch: Vec<_> = (0..10)
+            .map(|i| BatchItem { message: &messages[i][..], signature: &signatures[i], public_key: &keys[i] })
+            .collect();
+        assert_eq!(scheme.verify_batch(&batch).unwrap(), Some(7));
+    }
+}
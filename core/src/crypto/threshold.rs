@@ -0,0 +1,871 @@
+use super::CryptoError;
+use super::hash_to_curve::{self, DomainTag};
+use super::signature::{Signature, SignatureScheme};
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Message exchanged during distributed key generation
+///
+/// `Share` messages must reach their recipient over a confidential channel;
+/// all other messages are broadcast. The transport authenticates senders, so
+/// the dealer or complainer named in a message is the party that sent it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DkgMessage<E: PairingEngine> {
+    /// Feldman commitments to the dealer's polynomial coefficients
+    Commitments {
+        /// Dealing participant
+        dealer: u32,
+        /// Coefficient commitments `a_k·G`, constant term first
+        commitments: Vec<E::G1Projective>,
+        /// Schnorr proof of knowledge of `a_0`, commitment
+        proof_commitment: E::G1Projective,
+        /// Schnorr proof of knowledge of `a_0`, response
+        proof_response: E::Fr,
+    },
+    /// Dealer's polynomial evaluated at the recipient's index
+    Share {
+        /// Dealing participant
+        dealer: u32,
+        /// Receiving participant
+        recipient: u32,
+        /// Secret share
+        share: E::Fr,
+    },
+    /// Complaint that a dealer's share is missing or fails verification
+    Complaint {
+        /// Complaining participant
+        complainer: u32,
+        /// Accused dealer
+        dealer: u32,
+    },
+    /// Dealer's public reveal of a disputed share
+    Justification {
+        /// Accused dealer
+        dealer: u32,
+        /// Complaining participant
+        complainer: u32,
+        /// Revealed share
+        share: E::Fr,
+    },
+}
+
+impl<E: PairingEngine> DkgMessage<E> {
+    /// Participant sending the message
+    pub fn sender(&self) -> u32 {
+        match self {
+            DkgMessage::Commitments { dealer, .. }
+            | DkgMessage::Share { dealer, .. }
+            | DkgMessage::Justification { dealer, .. } => *dealer,
+            DkgMessage::Complaint { complainer, .. } => *complainer,
+        }
+    }
+
+    /// Recipient of a private message, `None` for broadcasts
+    pub fn recipient(&self) -> Option<u32> {
+        match self {
+            DkgMessage::Share { recipient, .. } => Some(*recipient),
+            _ => None,
+        }
+    }
+}
+
+/// Progress of a distributed key generation participant
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DkgPhase {
+    /// Waiting to deal
+    Created,
+    /// Dealt; collecting commitments and shares
+    Dealing,
+    /// Complaints sent; collecting complaints and justifications
+    Complaining,
+    /// Key share produced
+    Finished,
+}
+
+/// Key share produced by distributed key generation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThresholdKey<E: PairingEngine> {
+    /// Participant index, starting at 1
+    pub index: u32,
+
+    /// Number of participants needed to sign
+    pub threshold: u32,
+
+    /// Secret share of the group key
+    pub secret_share: E::Fr,
+
+    /// Group public key
+    pub group_public_key: E::G1Projective,
+
+    /// Public key of every participant's secret share
+    pub verification_shares: BTreeMap<u32, E::G1Projective>,
+
+    /// Dealers whose polynomials make up the group key
+    pub qualified: BTreeSet<u32>,
+}
+
+/// Participant in Pedersen distributed key generation with Feldman VSS
+///
+/// Each participant deals a random polynomial of degree `threshold - 1`,
+/// broadcasting commitments to its coefficients and sending every other
+/// participant its evaluation. Shares failing verification against the
+/// commitments draw a complaint, which the dealer answers by revealing the
+/// share publicly. Dealers with invalid commitments, unanswered complaints or
+/// invalid justifications are disqualified, and each participant's key share
+/// is the sum of the shares dealt by the remaining qualified dealers.
+///
+/// The protocol is driven by `deal`, `handle`, `complain` and `finish`;
+/// every message returned must be delivered to the participants it names.
+pub struct DkgParticipant<E: PairingEngine> {
+    /// Participant index, starting at 1
+    index: u32,
+
+    /// Number of participants needed to sign
+    threshold: u32,
+
+    /// Number of participants
+    participants: u32,
+
+    /// Current phase
+    phase: DkgPhase,
+
+    /// Own polynomial coefficients
+    coefficients: Vec<E::Fr>,
+
+    /// Valid coefficient commitments by dealer
+    commitments: BTreeMap<u32, Vec<E::G1Projective>>,
+
+    /// Shares received by dealer
+    shares: BTreeMap<u32, E::Fr>,
+
+    /// Complaints by dealer and complainer, and whether they were answered
+    complaints: BTreeMap<(u32, u32), bool>,
+
+    /// Disqualified dealers
+    disqualified: BTreeSet<u32>,
+}
+
+impl<E: PairingEngine> DkgParticipant<E> {
+    /// Create participant `index` of `participants`, `threshold` of which must sign
+    pub fn new(index: u32, threshold: u32, participants: u32) -> Result<Self, CryptoError> {
+        if threshold == 0 || threshold > participants {
+            return Err(CryptoError::ParameterError(
+                "Threshold must be between 1 and the number of participants".to_string()
+            ));
+        }
+        if index == 0 || index > participants {
+            return Err(CryptoError::ParameterError(format!(
+                "Participant index must be between 1 and {}", participants
+            )));
+        }
+
+        Ok(Self {
+            index,
+            threshold,
+            participants,
+            phase: DkgPhase::Created,
+            coefficients: Vec::new(),
+            commitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+            complaints: BTreeMap::new(),
+            disqualified: BTreeSet::new(),
+        })
+    }
+
+    /// Participant index
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Current phase
+    pub fn phase(&self) -> DkgPhase {
+        self.phase
+    }
+
+    /// Dealers disqualified so far
+    pub fn disqualified(&self) -> &BTreeSet<u32> {
+        &self.disqualified
+    }
+
+    /// Deal a random polynomial: one broadcast and a share for every other participant
+    pub fn deal<R: Rng>(&mut self, rng: &mut R) -> Result<Vec<DkgMessage<E>>, CryptoError> {
+        self.expect_phase(DkgPhase::Created)?;
+
+        let g = E::G1Projective::prime_subgroup_generator();
+        self.coefficients = (0..self.threshold).map(|_| E::Fr::rand(rng)).collect();
+        let commitments: Vec<_> = self.coefficients.iter()
+            .map(|coefficient| g.mul(coefficient.into_repr()))
+            .collect();
+
+        // Proving knowledge of a_0 stops a dealer choosing its commitment
+        // after seeing the others' to cancel them out of the group key
+        let k = E::Fr::rand(rng);
+        let proof_commitment = g.mul(k.into_repr());
+        let challenge = self.proof_challenge(self.index, &commitments[0], &proof_commitment)?;
+        let proof_response = k + challenge * self.coefficients[0];
+
+        let mut messages = vec![DkgMessage::Commitments {
+            dealer: self.index,
+            commitments: commitments.clone(),
+            proof_commitment,
+            proof_response,
+        }];
+        for recipient in 1..=self.participants {
+            if recipient != self.index {
+                messages.push(DkgMessage::Share {
+                    dealer: self.index,
+                    recipient,
+                    share: self.evaluate(recipient),
+                });
+            }
+        }
+
+        self.commitments.insert(self.index, commitments);
+        self.shares.insert(self.index, self.evaluate(self.index));
+        self.phase = DkgPhase::Dealing;
+        Ok(messages)
+    }
+
+    /// Process a message from another participant, returning any replies
+    pub fn handle(&mut self, message: DkgMessage<E>) -> Result<Vec<DkgMessage<E>>, CryptoError> {
+        let sender = message.sender();
+        if sender == 0 || sender > self.participants || sender == self.index {
+            return Err(CryptoError::KeyError(format!("Unexpected message from participant {}", sender)));
+        }
+
+        match message {
+            DkgMessage::Commitments { dealer, commitments, proof_commitment, proof_response } => {
+                self.expect_phase(DkgPhase::Dealing)?;
+                if self.commitments.contains_key(&dealer) || self.disqualified.contains(&dealer) {
+                    // Equivocating dealers are disqualified
+                    if self.commitments.get(&dealer) != Some(&commitments) {
+                        self.disqualify(dealer);
+                    }
+                    return Ok(Vec::new());
+                }
+
+                let valid = commitments.len() == self.threshold as usize && {
+                    let g = E::G1Projective::prime_subgroup_generator();
+                    let challenge = self.proof_challenge(dealer, &commitments[0], &proof_commitment)?;
+                    g.mul(proof_response.into_repr()) == proof_commitment + commitments[0].mul(challenge.into_repr())
+                };
+                if valid {
+                    self.commitments.insert(dealer, commitments);
+                } else {
+                    self.disqualify(dealer);
+                }
+                Ok(Vec::new())
+            }
+            DkgMessage::Share { dealer, recipient, share } => {
+                self.expect_phase(DkgPhase::Dealing)?;
+                if recipient != self.index {
+                    return Err(CryptoError::KeyError(format!("Share for participant {} delivered to {}", recipient, self.index)));
+                }
+                self.shares.entry(dealer).or_insert(share);
+                Ok(Vec::new())
+            }
+            DkgMessage::Complaint { complainer, dealer } => {
+                self.expect_phase(DkgPhase::Complaining)?;
+                if dealer == 0 || dealer > self.participants {
+                    return Ok(Vec::new());
+                }
+                self.complaints.entry((dealer, complainer)).or_insert(false);
+
+                if dealer == self.index && !self.disqualified.contains(&self.index) {
+                    self.complaints.insert((dealer, complainer), true);
+                    return Ok(vec![DkgMessage::Justification {
+                        dealer,
+                        complainer,
+                        share: self.evaluate(complainer),
+                    }]);
+                }
+                Ok(Vec::new())
+            }
+            DkgMessage::Justification { dealer, complainer, share } => {
+                self.expect_phase(DkgPhase::Complaining)?;
+                if complainer == 0 || complainer > self.participants {
+                    self.disqualify(dealer);
+                    return Ok(Vec::new());
+                }
+                if self.verify_share(dealer, complainer, &share) {
+                    self.complaints.insert((dealer, complainer), true);
+                    if complainer == self.index {
+                        self.shares.insert(dealer, share);
+                    }
+                } else {
+                    self.disqualify(dealer);
+                }
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// End dealing and complain about dealers whose shares are missing or invalid
+    ///
+    /// Call once every commitment and share has been delivered. Dealers that
+    /// sent no valid commitments are disqualified.
+    pub fn complain(&mut self) -> Result<Vec<DkgMessage<E>>, CryptoError> {
+        self.expect_phase(DkgPhase::Dealing)?;
+        for dealer in 1..=self.participants {
+            if !self.commitments.contains_key(&dealer) {
+                self.disqualify(dealer);
+            }
+        }
+
+        let mut messages = Vec::new();
+        for dealer in 1..=self.participants {
+            if dealer == self.index || self.disqualified.contains(&dealer) {
+                continue;
+            }
+            let valid = self.shares.get(&dealer)
+                .map_or(false, |share| self.verify_share(dealer, self.index, share));
+            if !valid {
+                self.complaints.insert((dealer, self.index), false);
+                messages.push(DkgMessage::Complaint {
+                    complainer: self.index,
+                    dealer,
+                });
+            }
+        }
+
+        self.phase = DkgPhase::Complaining;
+        Ok(messages)
+    }
+
+    /// Disqualify dealers with unanswered complaints and compute the key share
+    ///
+    /// Call once every complaint and justification has been delivered.
+    pub fn finish(&mut self) -> Result<ThresholdKey<E>, CryptoError> {
+        self.expect_phase(DkgPhase::Complaining)?;
+        let unanswered: Vec<u32> = self.complaints.iter()
+            .filter(|(_, answered)| !**answered)
+            .map(|((dealer, _), _)| *dealer)
+            .collect();
+        for dealer in unanswered {
+            self.disqualify(dealer);
+        }
+
+        let qualified: BTreeSet<u32> = self.commitments.keys()
+            .filter(|dealer| !self.disqualified.contains(dealer))
+            .copied()
+            .collect();
+        if qualified.len() < self.threshold as usize {
+            return Err(CryptoError::KeyError(format!(
+                "Only {} qualified dealers, {} required", qualified.len(), self.threshold
+            )));
+        }
+
+        let mut secret_share = E::Fr::zero();
+        for dealer in &qualified {
+            let share = self.shares.get(dealer)
+                .filter(|share| self.verify_share(*dealer, self.index, share))
+                .ok_or_else(|| CryptoError::KeyError(format!("No valid share from dealer {}", dealer)))?;
+            secret_share += share;
+        }
+
+        let group_public_key = qualified.iter()
+            .map(|dealer| self.commitments[dealer][0])
+            .sum();
+        let verification_shares = (1..=self.participants)
+            .map(|participant| {
+                let share = qualified.iter()
+                    .map(|dealer| feldman_evaluate::<E>(&self.commitments[dealer], participant))
+                    .sum();
+                (participant, share)
+            })
+            .collect();
+
+        self.coefficients.clear();
+        self.phase = DkgPhase::Finished;
+        Ok(ThresholdKey {
+            index: self.index,
+            threshold: self.threshold,
+            secret_share,
+            group_public_key,
+            verification_shares,
+            qualified,
+        })
+    }
+
+    /// Reject calls out of protocol order
+    fn expect_phase(&self, phase: DkgPhase) -> Result<(), CryptoError> {
+        if self.phase != phase {
+            return Err(CryptoError::KeyError(format!(
+                "Expected phase {:?}, participant is in {:?}", phase, self.phase
+            )));
+        }
+        Ok(())
+    }
+
+    /// Remove a dealer from the qualified set
+    fn disqualify(&mut self, dealer: u32) {
+        self.disqualified.insert(dealer);
+    }
+
+    /// Own polynomial evaluated at a participant index
+    fn evaluate(&self, participant: u32) -> E::Fr {
+        let x = E::Fr::from(participant as u64);
+        self.coefficients.iter()
+            .rev()
+            .fold(E::Fr::zero(), |acc, coefficient| acc * x + coefficient)
+    }
+
+    /// Check a dealer's share for a participant against its commitments
+    fn verify_share(&self, dealer: u32, participant: u32, share: &E::Fr) -> bool {
+        self.commitments.get(&dealer).map_or(false, |commitments| {
+            E::G1Projective::prime_subgroup_generator().mul(share.into_repr())
+                == feldman_evaluate::<E>(commitments, participant)
+        })
+    }
+
+    /// Challenge of a dealer's proof of knowledge, bound to the session parameters
+    fn proof_challenge(
+        &self,
+        dealer: u32,
+        constant: &E::G1Projective,
+        proof_commitment: &E::G1Projective,
+    ) -> Result<E::Fr, CryptoError> {
+        let mut bytes = self.threshold.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&self.participants.to_be_bytes());
+        bytes.extend_from_slice(&dealer.to_be_bytes());
+        for point in [constant, proof_commitment] {
+            point.into_affine().serialize(&mut bytes)
+                .map_err(|e| CryptoError::KeyError(e.to_string()))?;
+        }
+        hash_to_curve::hash_to_scalar(&bytes, DomainTag::DKG_PROOF)
+    }
+}
+
+/// Message exchanged during threshold signing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SigningMessage<E: PairingEngine> {
+    /// Commitments to the signer's hiding and binding nonces
+    Commitment {
+        /// Signing participant
+        signer: u32,
+        /// Hiding nonce commitment `d·G`
+        hiding: E::G1Projective,
+        /// Binding nonce commitment `e·G`
+        binding: E::G1Projective,
+    },
+    /// Signer's share of the signature
+    Share {
+        /// Signing participant
+        signer: u32,
+        /// Signature share
+        share: E::Fr,
+    },
+}
+
+/// Participant in two-round threshold signing
+///
+/// Signatures verify under the group key with `SignatureScheme::verify`.
+/// Following FROST, each signer commits to two nonces; once every commitment
+/// is known, the group nonce is `R = sum(D_i + rho_i·E_i)` where `rho_i`
+/// binds each signer's nonces to the message and the full commitment list.
+/// Signer `i` then sends `z_i = d_i + rho_i·e_i + h·lambda_i·x_i`, which every
+/// participant checks against the signer's verification share.
+pub struct ThresholdSigner<'a, E: PairingEngine> {
+    /// Key share
+    key: &'a ThresholdKey<E>,
+
+    /// Signature scheme
+    scheme: SignatureScheme<E>,
+
+    /// Message being signed
+    message: Vec<u8>,
+
+    /// Participants signing
+    signers: BTreeSet<u32>,
+
+    /// Own hiding and binding nonces, cleared once used
+    nonces: Option<(E::Fr, E::Fr)>,
+
+    /// Whether this signer has committed
+    committed: bool,
+
+    /// Nonce commitments by signer
+    commitments: BTreeMap<u32, (E::G1Projective, E::G1Projective)>,
+
+    /// Signature shares by signer
+    shares: BTreeMap<u32, E::Fr>,
+
+    /// Signers that sent invalid or conflicting messages
+    faulty: BTreeSet<u32>,
+}
+
+impl<'a, E: PairingEngine> ThresholdSigner<'a, E> {
+    /// Start signing `message` with the given set of signers
+    pub fn new(key: &'a ThresholdKey<E>, message: &[u8], signers: &[u32]) -> Result<Self, CryptoError> {
+        let signer_set: BTreeSet<u32> = signers.iter().copied().collect();
+        if signer_set.len() != signers.len() {
+            return Err(CryptoError::SignatureError("Duplicate signer".to_string()));
+        }
+        if signer_set.len() < key.threshold as usize {
+            return Err(CryptoError::SignatureError(format!(
+                "{} signers given, {} required", signer_set.len(), key.threshold
+            )));
+        }
+        if !signer_set.contains(&key.index) {
+            return Err(CryptoError::SignatureError("Signer set does not include this key".to_string()));
+        }
+        if let Some(unknown) = signer_set.iter().find(|signer| !key.verification_shares.contains_key(signer)) {
+            return Err(CryptoError::SignatureError(format!("Unknown signer {}", unknown)));
+        }
+
+        Ok(Self {
+            key,
+            scheme: SignatureScheme::new(128)?,
+            message: message.to_vec(),
+            signers: signer_set,
+            nonces: None,
+            committed: false,
+            commitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+            faulty: BTreeSet::new(),
+        })
+    }
+
+    /// Signers that sent invalid or conflicting messages
+    pub fn faulty_signers(&self) -> &BTreeSet<u32> {
+        &self.faulty
+    }
+
+    /// First round: commit to fresh nonces
+    pub fn commit<R: Rng>(&mut self, rng: &mut R) -> Result<SigningMessage<E>, CryptoError> {
+        if self.committed {
+            return Err(CryptoError::SignatureError("Nonces already committed".to_string()));
+        }
+
+        let g = E::G1Projective::prime_subgroup_generator();
+        let (d, e) = (E::Fr::rand(rng), E::Fr::rand(rng));
+        let (hiding, binding) = (g.mul(d.into_repr()), g.mul(e.into_repr()));
+        self.nonces = Some((d, e));
+        self.committed = true;
+        self.commitments.insert(self.key.index, (hiding, binding));
+
+        Ok(SigningMessage::Commitment {
+            signer: self.key.index,
+            hiding,
+            binding,
+        })
+    }
+
+    /// Process a message from another signer, returning this signer's share once all commitments are in
+    pub fn handle(&mut self, message: SigningMessage<E>) -> Result<Option<SigningMessage<E>>, CryptoError> {
+        match message {
+            SigningMessage::Commitment { signer, hiding, binding } => {
+                self.expect_signer(signer)?;
+                if let Some(existing) = self.commitments.get(&signer) {
+                    if *existing != (hiding, binding) {
+                        self.faulty.insert(signer);
+                        return Err(CryptoError::SignatureError(format!("Conflicting commitments from signer {}", signer)));
+                    }
+                    return Ok(None);
+                }
+                if hiding.is_zero() || binding.is_zero() {
+                    self.faulty.insert(signer);
+                    return Err(CryptoError::SignatureError(format!("Invalid commitment from signer {}", signer)));
+                }
+                self.commitments.insert(signer, (hiding, binding));
+                self.sign_share()
+            }
+            SigningMessage::Share { signer, share } => {
+                self.expect_signer(signer)?;
+                if self.commitments.len() < self.signers.len() {
+                    return Err(CryptoError::SignatureError("Share received before all commitments".to_string()));
+                }
+                if self.shares.contains_key(&signer) {
+                    return Ok(None);
+                }
+
+                let (group_commitment, binding_factors) = self.group_commitment()?;
+                let challenge = self.scheme.challenge(&self.message, &group_commitment, &self.key.group_public_key)?;
+                let (hiding, binding) = self.commitments[&signer];
+                let expected = hiding + binding.mul(binding_factors[&signer].into_repr())
+                    + self.key.verification_shares[&signer]
+                        .mul((challenge * lagrange_coefficient::<E>(signer, &self.signers)).into_repr());
+                if E::G1Projective::prime_subgroup_generator().mul(share.into_repr()) != expected {
+                    self.faulty.insert(signer);
+                    return Err(CryptoError::SignatureError(format!("Invalid signature share from signer {}", signer)));
+                }
+
+                self.shares.insert(signer, share);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Aggregate signature, once every share has been received
+    pub fn signature(&self) -> Result<Signature<E>, CryptoError> {
+        if self.shares.len() < self.signers.len() {
+            return Err(CryptoError::SignatureError(format!(
+                "{} of {} signature shares received", self.shares.len(), self.signers.len()
+            )));
+        }
+
+        let (r, _) = self.group_commitment()?;
+        let signature = Signature {
+            r,
+            s: self.shares.values().sum(),
+        };
+        if !self.scheme.verify(&self.message, &signature, &self.key.group_public_key)? {
+            return Err(CryptoError::SignatureError("Aggregated signature does not verify".to_string()));
+        }
+        Ok(signature)
+    }
+
+    /// Second round: produce own share once every commitment is known
+    fn sign_share(&mut self) -> Result<Option<SigningMessage<E>>, CryptoError> {
+        if self.commitments.len() < self.signers.len() {
+            return Ok(None);
+        }
+        let (d, e) = match self.nonces.take() {
+            Some(nonces) => nonces,
+            None => return Ok(None),
+        };
+
+        let (group_commitment, binding_factors) = self.group_commitment()?;
+        let challenge = self.scheme.challenge(&self.message, &group_commitment, &self.key.group_public_key)?;
+        let lambda = lagrange_coefficient::<E>(self.key.index, &self.signers);
+        let share = d + binding_factors[&self.key.index] * e + challenge * lambda * self.key.secret_share;
+
+        self.shares.insert(self.key.index, share);
+        Ok(Some(SigningMessage::Share {
+            signer: self.key.index,
+            share,
+        }))
+    }
+
+    /// Group nonce commitment and each signer's binding factor
+    fn group_commitment(&self) -> Result<(E::G1Projective, BTreeMap<u32, E::Fr>), CryptoError> {
+        let mut transcript = self.key.group_public_key_bytes()?;
+        transcript.extend_from_slice(&(self.message.len() as u64).to_be_bytes());
+        transcript.extend_from_slice(&self.message);
+        for (signer, (hiding, binding)) in &self.commitments {
+            transcript.extend_from_slice(&signer.to_be_bytes());
+            for point in [hiding, binding] {
+                point.into_affine().serialize(&mut transcript)
+                    .map_err(|e| CryptoError::SignatureError(e.to_string()))?;
+            }
+        }
+
+        let mut group_commitment = E::G1Projective::zero();
+        let mut binding_factors = BTreeMap::new();
+        for (signer, (hiding, binding)) in &self.commitments {
+            let mut bytes = transcript.clone();
+            bytes.extend_from_slice(&signer.to_be_bytes());
+            let rho: E::Fr = hash_to_curve::hash_to_scalar(&bytes, DomainTag::THRESHOLD_BINDING)?;
+            group_commitment += *hiding + binding.mul(rho.into_repr());
+            binding_factors.insert(*signer, rho);
+        }
+        Ok((group_commitment, binding_factors))
+    }
+
+    /// Reject messages from outside the signer set
+    fn expect_signer(&self, signer: u32) -> Result<(), CryptoError> {
+        if signer == self.key.index || !self.signers.contains(&signer) {
+            return Err(CryptoError::SignatureError(format!("Unexpected message from signer {}", signer)));
+        }
+        Ok(())
+    }
+}
+
+impl<E: PairingEngine> ThresholdKey<E> {
+    /// Compressed group public key
+    fn group_public_key_bytes(&self) -> Result<Vec<u8>, CryptoError> {
+        let mut bytes = Vec::new();
+        self.group_public_key.into_affine().serialize(&mut bytes)
+            .map_err(|e| CryptoError::SignatureError(e.to_string()))?;
+        Ok(bytes)
+    }
+}
+
+/// Committed polynomial evaluated at a participant index, `sum C_k·i^k`
+fn feldman_evaluate<E: PairingEngine>(commitments: &[E::G1Projective], participant: u32) -> E::G1Projective {
+    let x = E::Fr::from(participant as u64).into_repr();
+    commitments.iter()
+        .rev()
+        .fold(E::G1Projective::zero(), |acc, commitment| acc.mul(x) + commitment)
+}
+
+/// Lagrange coefficient at zero of `index` within `signers`
+fn lagrange_coefficient<E: PairingEngine>(index: u32, signers: &BTreeSet<u32>) -> E::Fr {
+    let mut numerator = E::Fr::one();
+    let mut denominator = E::Fr::one();
+    for signer in signers.iter().filter(|signer| **signer != index) {
+        numerator *= E::Fr::from(*signer as u64);
+        denominator *= E::Fr::from(*signer as u64) - E::Fr::from(index as u64);
+    }
+    // Signers are distinct, so the denominator is non-zero
+    numerator * denominator.inverse().unwrap_or_else(E::Fr::zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use rand::thread_rng;
+    use std::collections::VecDeque;
+
+    type Message = DkgMessage<Bls12_381>;
+
+    /// Deliver messages and their replies, rewriting them in flight with `tamper`
+    fn deliver(
+        parties: &mut [DkgParticipant<Bls12_381>],
+        messages: Vec<Message>,
+        tamper: &dyn Fn(&mut Message),
+    ) {
+        let mut queue: VecDeque<Message> = messages.into();
+        while let Some(mut message) = queue.pop_front() {
+            tamper(&mut message);
+            for party in parties.iter_mut() {
+                let addressed = message.recipient().map_or(true, |recipient| recipient == party.index());
+                if party.index() != message.sender() && addressed {
+                    queue.extend(party.handle(message.clone()).unwrap());
+                }
+            }
+        }
+    }
+
+    /// Run key generation among `n` participants with a `t` threshold
+    fn run_dkg(t: u32, n: u32, tamper: &dyn Fn(&mut Message)) -> Vec<ThresholdKey<Bls12_381>> {
+        let mut rng = thread_rng();
+        let mut parties: Vec<_> = (1..=n)
+            .map(|index| DkgParticipant::<Bls12_381>::new(index, t, n).unwrap())
+            .collect();
+
+        let dealt: Vec<_> = parties.iter_mut().flat_map(|party| party.deal(&mut rng).unwrap()).collect();
+        deliver(&mut parties, dealt, tamper);
+        let complaints: Vec<_> = parties.iter_mut().flat_map(|party| party.complain().unwrap()).collect();
+        deliver(&mut parties, complaints, tamper);
+
+        parties.iter_mut().map(|party| party.finish().unwrap()).collect()
+    }
+
+    /// Sign with the keys of `signers`, returning the signature
+    fn sign(keys: &[ThresholdKey<Bls12_381>], signers: &[u32], message: &[u8]) -> Signature<Bls12_381> {
+        let mut rng = thread_rng();
+        let mut sessions: Vec<_> = signers.iter()
+            .map(|index| ThresholdSigner::new(&keys[*index as usize - 1], message, signers).unwrap())
+            .collect();
+
+        let commitments: Vec<_> = sessions.iter_mut().map(|session| session.commit(&mut rng).unwrap()).collect();
+        let mut shares = Vec::new();
+        for (from, commitment) in signers.iter().zip(commitments) {
+            for (to, session) in signers.iter().zip(sessions.iter_mut()) {
+                if from != to {
+                    shares.extend(session.handle(commitment.clone()).unwrap());
+                }
+            }
+        }
+        for share in shares {
+            for (to, session) in signers.iter().zip(sessions.iter_mut()) {
+                if let SigningMessage::Share { signer, .. } = share {
+                    if signer != *to {
+                        session.handle(share.clone()).unwrap();
+                    }
+                }
+            }
+        }
+
+        let signature = sessions[0].signature().unwrap();
+        for session in &sessions {
+            assert_eq!(session.signature().unwrap().r, signature.r);
+        }
+        signature
+    }
+
+    fn assert_consistent(keys: &[ThresholdKey<Bls12_381>]) {
+        let g = <Bls12_381 as PairingEngine>::G1Projective::prime_subgroup_generator();
+        for key in keys {
+            assert_eq!(key.group_public_key, keys[0].group_public_key);
+            assert_eq!(key.verification_shares, keys[0].verification_shares);
+            assert_eq!(key.qualified, keys[0].qualified);
+            assert_eq!(g.mul(key.secret_share.into_repr()), key.verification_shares[&key.index]);
+        }
+    }
+
+    #[test]
+    fn test_dkg_and_threshold_signing() {
+        let keys = run_dkg(3, 5, &|_| {});
+        assert_consistent(&keys);
+
+        // Any three shares interpolate to the group secret
+        let signers: BTreeSet<u32> = [1, 3, 5].into_iter().collect();
+        let secret: Fr = signers.iter()
+            .map(|index| lagrange_coefficient::<Bls12_381>(*index, &signers) * keys[*index as usize - 1].secret_share)
+            .sum();
+        let g = <Bls12_381 as PairingEngine>::G1Projective::prime_subgroup_generator();
+        assert_eq!(g.mul(secret.into_repr()), keys[0].group_public_key);
+
+        let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
+        for signers in [&[1, 3, 5][..], &[2, 4, 5], &[1, 2, 3, 4]] {
+            let signature = sign(&keys, signers, b"block 42");
+            assert!(scheme.verify(b"block 42", &signature, &keys[0].group_public_key).unwrap());
+            assert!(!scheme.verify(b"block 43", &signature, &keys[0].group_public_key).unwrap());
+        }
+
+        // Too few signers
+        assert!(ThresholdSigner::new(&keys[0], b"block 42", &[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_dkg_complaints() {
+        // Dealer 2 sends 4 a bad share but answers the complaint honestly
+        let keys = run_dkg(2, 4, &|message| {
+            if let DkgMessage::Share { dealer: 2, recipient: 4, share } = message {
+                *share += Fr::one();
+            }
+        });
+        assert_consistent(&keys);
+        assert!(keys[0].qualified.contains(&2));
+        assert_eq!(keys[0].qualified.len(), 4);
+
+        // Dealer 3 sends 1 a bad share and repeats it in its justification
+        let keys = run_dkg(2, 4, &|message| match message {
+            DkgMessage::Share { dealer: 3, recipient: 1, share }
+            | DkgMessage::Justification { dealer: 3, complainer: 1, share } => *share += Fr::one(),
+            _ => {}
+        });
+        // Everyone but the cheating dealer agrees to disqualify it
+        let honest: Vec<_> = keys.iter().filter(|key| key.index != 3).cloned().collect();
+        assert_consistent(&honest);
+        assert_eq!(honest[0].qualified, BTreeSet::from([1, 2, 4]));
+
+        let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
+        let signature = sign(&keys, &[1, 4], b"checkpoint");
+        assert!(scheme.verify(b"checkpoint", &signature, &keys[0].group_public_key).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_signature_share_is_attributed() {
+        let keys = run_dkg(2, 3, &|_| {});
+        let mut rng = thread_rng();
+        let signers = [1, 2, 3];
+
+        let mut sessions: Vec<_> = signers.iter()
+            .map(|index| ThresholdSigner::new(&keys[*index as usize - 1], b"vote", &signers).unwrap())
+            .collect();
+        let commitments: Vec<_> = sessions.iter_mut().map(|session| session.commit(&mut rng).unwrap()).collect();
+
+        // Signer 1 produces its share once it has every commitment
+        assert!(sessions[0].handle(commitments[1].clone()).unwrap().is_none());
+        let share = sessions[0].handle(commitments[2].clone()).unwrap().unwrap();
+        assert!(sessions[0].commit(&mut rng).is_err());
+
+        // Signer 2 rejects a tampered share and blames signer 1
+        sessions[1].handle(commitments[0].clone()).unwrap();
+        sessions[1].handle(commitments[2].clone()).unwrap();
+        let tampered = match share {
+            SigningMessage::Share { signer, share } => SigningMessage::Share { signer, share: share + Fr::one() },
+            _ => unreachable!(),
+        };
+        assert!(sessions[1].handle(tampered).is_err());
+        assert_eq!(sessions[1].faulty_signers(), &BTreeSet::from([1]));
+        assert!(sessions[1].signature().is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use super::CryptoError;
-use ark_ec::PairingEngine;
-use ark_ff::Field;
+use super::hash_to_curve::{self, DomainTag, HashToCurve};
+use super::range_proof::{RangeProof, RangeProofGens};
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::Rng;
-use sha3::{Sha3_256, Digest};
 
 /// Cryptographic utilities
 pub struct CryptoUtils;
@@ -16,15 +17,9 @@ impl CryptoUtils {
         bytes
     }
 
-    /// Hash to field element
-    pub fn hash_to_field<F: Field>(data: &[u8]) -> Result<F, CryptoError> {
-        let mut hasher = Sha3_256::new();
-        hasher.update(data);
-        let hash = hasher.finalize();
-        
-        F::from_random_bytes(&hash).ok_or_else(|| {
-            CryptoError::HashError("Failed to convert hash to field element".to_string())
-        })
+    /// Hash to field element under a domain tag
+    pub fn hash_to_field<F: Field>(data: &[u8], tag: DomainTag) -> Result<F, CryptoError> {
+        hash_to_curve::hash_to_scalar(data, tag)
     }
 
     /// Serialize field element to bytes
@@ -52,49 +47,57 @@ impl CryptoUtils {
         Ok(a.iter().zip(b.iter()).map(|(&x, &y)| x ^ y).collect())
     }
 
-    /// Generate commitment to field element
+    /// Group element with unknown discrete logarithm, derived from `label`
+    ///
+    /// Hashes the label to G1 under the generator domain tag.
+    pub fn derive_generator<E: HashToCurve>(label: &[u8]) -> Result<E::G1Projective, CryptoError> {
+        E::hash_to_g1(label, DomainTag::GENERATOR)
+    }
+
+    /// Blinding generator `H` of Pedersen commitments
+    pub fn blinding_generator<E: HashToCurve>() -> Result<E::G1Projective, CryptoError> {
+        Self::derive_generator::<E>(b"pedersen-blinding")
+    }
+
+    /// Generate Pedersen commitment `value·G + randomness·h` to field element
+    ///
+    /// `h` is the blinding generator, derived once with [`Self::blinding_generator`].
     pub fn commit_to_field<E: PairingEngine>(
         value: &E::Fr,
         randomness: &E::Fr,
+        h: &E::G1Projective,
     ) -> E::G1Projective {
         let g = E::G1Projective::prime_subgroup_generator();
-        let h = E::G1Projective::prime_subgroup_generator().mul(E::Fr::from(2u32).into_repr());
-        
         g.mul(value.into_repr()) + h.mul(randomness.into_repr())
     }
 
-    /// Verify field element is in range [0, max)
-    pub fn verify_field_range<F: Field>(value: &F, max: &F) -> bool {
-        value < max
-    }
-
-    /// Generate zero-knowledge range proof
-    pub fn generate_range_proof<E: PairingEngine>(
-        value: &E::Fr,
-        max: &E::Fr,
+    /// Prove the value committed with `randomness` lies in `[0, 2^64)`
+    pub fn generate_range_proof<E: PairingEngine, R: Rng>(
+        gens: &RangeProofGens<E>,
+        value: u64,
         randomness: &E::Fr,
-    ) -> Result<Vec<u8>, CryptoError> {
-        // This is a placeholder for actual range proof implementation
-        // In practice, you would use Bulletproofs or another range proof system
-        
-        if !Self::verify_field_range(value, max) {
-            return Err(CryptoError::ParameterError(
-                "Value out of range".to_string()
-            ));
-        }
-
-        let mut proof = Vec::new();
-        proof.extend_from_slice(&Self::serialize_field(value)?);
-        proof.extend_from_slice(&Self::serialize_field(randomness)?);
-        
+        rng: &mut R,
+    ) -> Result<RangeProof<E>, CryptoError> {
+        let (proof, _) = RangeProof::prove(gens, value, randomness, rng)?;
         Ok(proof)
     }
+
+    /// Verify a range proof against a commitment, without the value
+    pub fn verify_range_proof<E: PairingEngine>(
+        gens: &RangeProofGens<E>,
+        proof: &RangeProof<E>,
+        commitment: &E::G1Projective,
+    ) -> Result<bool, CryptoError> {
+        proof.verify(gens, std::slice::from_ref(commitment))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_ec::AffineCurve;
+    use ark_ff::{UniformRand, Zero};
     use rand::thread_rng;
 
     #[test]
@@ -107,7 +110,7 @@ mod tests {
     #[test]
     fn test_hash_to_field() {
         let data = b"test data";
-        let result = CryptoUtils::hash_to_field::<Fr>(data);
+        let result = CryptoUtils::hash_to_field::<Fr>(data, DomainTag::ACCOUNT_HASH);
         assert!(result.is_ok());
         let field_element = result.unwrap();
         assert!(!field_element.is_zero());
@@ -125,18 +128,28 @@ mod tests {
     fn test_commitment() {
         let value = Fr::from(42u32);
         let randomness = Fr::from(123u32);
-        let commitment = CryptoUtils::commit_to_field::<Bls12_381>(&value, &randomness);
-        assert!(commitment.is_in_correct_subgroup_assuming_on_curve());
+        let h = CryptoUtils::blinding_generator::<Bls12_381>().unwrap();
+        let commitment = CryptoUtils::commit_to_field::<Bls12_381>(&value, &randomness, &h);
+        assert!(commitment.into_affine().is_in_correct_subgroup_assuming_on_curve());
+
+        // Blinding generator is hashed to G1, independent of G
+        assert_eq!(h, Bls12_381::hash_to_g1(b"pedersen-blinding", DomainTag::GENERATOR).unwrap());
+        assert_ne!(h, G1Projective::prime_subgroup_generator());
+        assert_ne!(h, G1Projective::prime_subgroup_generator().double());
     }
 
     #[test]
     fn test_range_proof() {
-        let value = Fr::from(42u32);
-        let max = Fr::from(100u32);
-        let randomness = Fr::from(123u32);
-        
-        let proof = CryptoUtils::generate_range_proof::<Bls12_381>(&value, &max, &randomness);
-        assert!(proof.is_ok());
+        let mut rng = thread_rng();
+        let gens = RangeProofGens::<Bls12_381>::new(1).unwrap();
+        let randomness = Fr::rand(&mut rng);
+        let commitment = gens.commit(&Fr::from(42u32), &randomness);
+
+        let proof = CryptoUtils::generate_range_proof(&gens, 42, &randomness, &mut rng).unwrap();
+        assert!(CryptoUtils::verify_range_proof(&gens, &proof, &commitment).unwrap());
+
+        let other = gens.commit(&Fr::from(41u32), &randomness);
+        assert!(!CryptoUtils::verify_range_proof(&gens, &proof, &other).unwrap());
     }
 
     #[test]
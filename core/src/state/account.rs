@@ -1,9 +1,12 @@
 use super::StateError;
+use super::admission::CreditUsage;
+use super::auth::AuthPolicy;
+use super::storage_trie::StorageTrie;
 use crate::crypto::keys::KeyPair;
 use ark_ec::PairingEngine;
 use ark_ff::Field;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Account identifier
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
@@ -24,14 +27,26 @@ pub struct Account<E: PairingEngine> {
     /// Account public key
     pub public_key: E::G1Projective,
     
-    /// Account state root (for smart contracts)
+    /// Root of the contract storage tree (for smart contracts)
     pub state_root: E::Fr,
     
     /// Account code hash (for smart contracts)
     pub code_hash: Option<E::Fr>,
     
-    /// Account storage
+    /// Account storage, committed to by `state_root` and kept out of the account record
     pub storage: HashMap<E::Fr, E::Fr>,
+    
+    /// Account metadata
+    pub metadata: BTreeMap<String, Vec<u8>>,
+    
+    /// Rule deciding which signatures authorize transactions
+    pub auth_policy: AuthPolicy<E>,
+    
+    /// Block the account was created at
+    pub created_at: u64,
+    
+    /// Computational credit in use
+    pub credit: CreditUsage,
 }
 
 impl<E: PairingEngine> Account<E> {
@@ -45,6 +60,10 @@ impl<E: PairingEngine> Account<E> {
             state_root: E::Fr::zero(),
             code_hash: None,
             storage: HashMap::new(),
+            metadata: BTreeMap::new(),
+            auth_policy: AuthPolicy::Single,
+            created_at: 0,
+            credit: CreditUsage::default(),
         }
     }
 
@@ -62,6 +81,10 @@ impl<E: PairingEngine> Account<E> {
             state_root: E::Fr::zero(),
             code_hash: Some(code_hash),
             storage: HashMap::new(),
+            metadata: BTreeMap::new(),
+            auth_policy: AuthPolicy::Single,
+            created_at: 0,
+            credit: CreditUsage::default(),
         }
     }
 
@@ -80,7 +103,7 @@ impl<E: PairingEngine> Account<E> {
         Ok(())
     }
 
-    /// Set storage value
+    /// Set storage value, `commit_storage` updates `state_root`
     pub fn set_storage(&mut self, key: E::Fr, value: E::Fr) {
         self.storage.insert(key, value);
     }
@@ -95,7 +118,81 @@ impl<E: PairingEngine> Account<E> {
         self.code_hash.is_some()
     }
 
-    /// Serialize account state
+    /// Calculate root of the storage tree
+    pub fn storage_root(&self) -> Result<E::Fr, StateError> {
+        Ok(StorageTrie::from_slots(&self.storage)?.root())
+    }
+
+    /// Recalculate `state_root` from storage
+    pub fn commit_storage(&mut self) -> Result<(), StateError> {
+        self.state_root = self.storage_root()?;
+        Ok(())
+    }
+
+    /// Serialize storage slots ordered by key
+    pub fn serialize_storage(&self) -> Result<Vec<u8>, StateError> {
+        let mut slots: Vec<(Vec<u8>, &E::Fr, &E::Fr)> = Vec::with_capacity(self.storage.len());
+        for (key, value) in &self.storage {
+            let mut key_bytes = Vec::new();
+            key.serialize(&mut key_bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            slots.push((key_bytes, key, value));
+        }
+        slots.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut bytes = Vec::new();
+        (slots.len() as u64).serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        for (_, key, value) in slots {
+            key.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            value.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Deserialize storage slots
+    pub fn deserialize_storage(bytes: &[u8]) -> Result<HashMap<E::Fr, E::Fr>, StateError> {
+        let mut reader = bytes;
+
+        let count: u64 = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        let mut storage = HashMap::new();
+        for _ in 0..count {
+            let key: E::Fr = CanonicalDeserialize::deserialize(&mut reader)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            let value: E::Fr = CanonicalDeserialize::deserialize(&mut reader)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            storage.insert(key, value);
+        }
+
+        Ok(storage)
+    }
+
+    /// Serialize account record together with its storage
+    pub fn encode_with_storage(&self) -> Result<Vec<u8>, StateError> {
+        let mut bytes = Vec::new();
+        self.serialize()?.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        bytes.extend_from_slice(&self.serialize_storage()?);
+        Ok(bytes)
+    }
+
+    /// Deserialize account record together with its storage
+    pub fn decode_with_storage(bytes: &[u8]) -> Result<Self, StateError> {
+        let mut reader = bytes;
+        let record: Vec<u8> = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        let mut account = Self::deserialize(&record)?;
+        account.storage = Self::deserialize_storage(reader)?;
+        Ok(account)
+    }
+
+    /// Serialize account record, storage is committed through `state_root`
     pub fn serialize(&self) -> Result<Vec<u8>, StateError> {
         let mut bytes = Vec::new();
         
@@ -122,21 +219,31 @@ impl<E: PairingEngine> Account<E> {
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
         }
         
-        // Serialize storage
-        (self.storage.len() as u64).serialize(&mut bytes)
+        // Serialize metadata
+        (self.metadata.len() as u64).serialize(&mut bytes)
             .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        
-        for (key, value) in &self.storage {
-            key.serialize(&mut bytes)
+        for (key, value) in &self.metadata {
+            key.as_bytes().to_vec().serialize(&mut bytes)
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
             value.serialize(&mut bytes)
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
         }
         
+        // Serialize authorization policy
+        self.auth_policy.serialize(&mut bytes)?;
+        
+        // Serialize age and credit usage
+        self.created_at.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.credit.used.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.credit.updated_at.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        
         Ok(bytes)
     }
 
-    /// Deserialize account state
+    /// Deserialize account record, storage is left empty
     pub fn deserialize(bytes: &[u8]) -> Result<Self, StateError> {
         let mut offset = 0;
         
@@ -175,24 +282,38 @@ impl<E: PairingEngine> Account<E> {
             None
         };
         
-        // Deserialize storage
-        let storage_len: u64 = CanonicalDeserialize::deserialize(&bytes[offset..])
+        // Deserialize metadata
+        let metadata_len: u64 = CanonicalDeserialize::deserialize(&bytes[offset..])
             .map_err(|e| StateError::SerializationError(e.to_string()))?;
         offset += std::mem::size_of::<u64>();
         
-        let mut storage = HashMap::new();
-        for _ in 0..storage_len {
-            let key: E::Fr = CanonicalDeserialize::deserialize(&bytes[offset..])
+        let mut metadata = BTreeMap::new();
+        for _ in 0..metadata_len {
+            let key: Vec<u8> = CanonicalDeserialize::deserialize(&bytes[offset..])
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
             offset += key.serialized_size();
             
-            let value: E::Fr = CanonicalDeserialize::deserialize(&bytes[offset..])
+            let value: Vec<u8> = CanonicalDeserialize::deserialize(&bytes[offset..])
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
             offset += value.serialized_size();
             
-            storage.insert(key, value);
+            let key = String::from_utf8(key)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            metadata.insert(key, value);
         }
         
+        // Deserialize authorization policy
+        let mut reader = &bytes[offset..];
+        let auth_policy = AuthPolicy::deserialize(&mut reader)?;
+        
+        // Deserialize age and credit usage
+        let created_at: u64 = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let used: u64 = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let updated_at: u64 = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        
         Ok(Self {
             id: AccountId(id_bytes),
             nonce,
@@ -200,7 +321,11 @@ impl<E: PairingEngine> Account<E> {
             public_key,
             state_root,
             code_hash,
-            storage,
+            storage: HashMap::new(),
+            metadata,
+            auth_policy,
+            created_at,
+            credit: CreditUsage { used, updated_at },
         })
     }
 }
@@ -274,12 +399,40 @@ mod tests {
             Bls12_381::Fr::from(42u32)
         );
         
-        // Test serialization/deserialization
+        account.commit_storage().unwrap();
+        
+        // Account record commits to storage through the state root
         let bytes = account.serialize().unwrap();
         let deserialized = Account::deserialize(&bytes).unwrap();
         
         assert_eq!(account.id.0, deserialized.id.0);
         assert_eq!(account.balance, deserialized.balance);
+        assert_eq!(account.state_root, deserialized.state_root);
+        assert!(deserialized.storage.is_empty());
+        
+        // Full encoding carries storage
+        let deserialized = Account::<Bls12_381>::decode_with_storage(
+            &account.encode_with_storage().unwrap()
+        ).unwrap();
         assert_eq!(account.storage, deserialized.storage);
     }
+
+    #[test]
+    fn test_storage_root() {
+        let id = AccountId(vec![1, 2, 3]);
+        let g = Bls12_381::G1Projective::prime_subgroup_generator();
+        let mut account = Account::<Bls12_381>::new_contract(id, Bls12_381::Fr::from(1u32), g);
+        
+        account.commit_storage().unwrap();
+        assert!(account.state_root.is_zero());
+        
+        account.set_storage(Bls12_381::Fr::from(1u32), Bls12_381::Fr::from(42u32));
+        account.commit_storage().unwrap();
+        assert!(!account.state_root.is_zero());
+        
+        // Zeroed slots are removed from the tree
+        account.set_storage(Bls12_381::Fr::from(1u32), Bls12_381::Fr::zero());
+        account.commit_storage().unwrap();
+        assert!(account.state_root.is_zero());
+    }
 }
\ No newline at end of file
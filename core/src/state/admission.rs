@@ -0,0 +1,248 @@
+use super::{Account, StateError, Transaction};
+use super::computation::{self, ComputationProof, Difficulty};
+use ark_ec::PairingEngine;
+
+/// Computation recently charged to an account
+///
+/// Usage drains by the policy's regeneration amount every block, so an
+/// account's credit refills while it is idle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CreditUsage {
+    /// Computation charged and not yet regenerated
+    pub used: u64,
+
+    /// Block `used` was last updated at
+    pub updated_at: u64,
+}
+
+impl CreditUsage {
+    /// Usage left at a block after regeneration
+    pub fn at(&self, block_number: u64, regeneration: u64) -> u64 {
+        let blocks = block_number.saturating_sub(self.updated_at);
+        self.used.saturating_sub(blocks.saturating_mul(regeneration))
+    }
+}
+
+/// Admission rules shared by block execution, block producers and the mempool
+///
+/// Each account may spend a computational credit of `base_capacity` plus
+/// `credits_per_stake` per unit of balance held. Charged computation
+/// regenerates at `regeneration` per block. Pending transactions are ordered
+/// by a priority score favouring extra proof of computation work, older and
+/// better staked accounts, and accounts with little recent usage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdmissionPolicy {
+    /// Credit of an account without stake
+    pub base_capacity: u64,
+
+    /// Credit added per unit of balance
+    pub credits_per_stake: u64,
+
+    /// Credit regenerated per block
+    pub regeneration: u64,
+
+    /// Score per leading zero bit of proof work
+    pub work_weight: u64,
+
+    /// Score per doubling of account age in blocks
+    pub age_weight: u64,
+
+    /// Score per doubling of stake
+    pub stake_weight: u64,
+
+    /// Score removed per percent of capacity in use
+    pub usage_weight: u64,
+}
+
+impl Default for AdmissionPolicy {
+    fn default() -> Self {
+        Self {
+            base_capacity: 1_000_000,
+            credits_per_stake: 1,
+            regeneration: 100_000,
+            work_weight: 100,
+            age_weight: 20,
+            stake_weight: 20,
+            usage_weight: 10,
+        }
+    }
+}
+
+impl AdmissionPolicy {
+    /// Total credit of an account
+    pub fn capacity<E: PairingEngine>(&self, account: &Account<E>) -> u64 {
+        self.base_capacity
+            .saturating_add(account.balance.saturating_mul(self.credits_per_stake))
+    }
+
+    /// Credit an account can spend at a block
+    pub fn available<E: PairingEngine>(&self, account: &Account<E>, block_number: u64) -> u64 {
+        self.capacity(account)
+            .saturating_sub(account.credit.at(block_number, self.regeneration))
+    }
+
+    /// Check the account can pay for the transaction's computation
+    pub fn check_credit<E: PairingEngine>(
+        &self,
+        account: &Account<E>,
+        transaction: &Transaction<E>,
+        block_number: u64,
+    ) -> Result<(), StateError> {
+        let cost = computation::computation_cost(transaction);
+        let available = self.available(account, block_number);
+        if cost > available {
+            return Err(StateError::ValidationError(format!(
+                "Insufficient computational credit: {} required, {} available",
+                cost, available
+            )));
+        }
+        Ok(())
+    }
+
+    /// Charge computation used at a block to the account
+    pub fn charge<E: PairingEngine>(&self, account: &mut Account<E>, computation_used: u64, block_number: u64) {
+        account.credit = CreditUsage {
+            used: account.credit.at(block_number, self.regeneration).saturating_add(computation_used),
+            updated_at: block_number,
+        };
+    }
+
+    /// Priority score of a pending transaction, higher is served first
+    pub fn priority<E: PairingEngine>(
+        &self,
+        account: &Account<E>,
+        transaction: &Transaction<E>,
+        block_number: u64,
+    ) -> Result<u64, StateError> {
+        let proof = transaction.computation_proof.as_ref()
+            .ok_or_else(|| StateError::ValidationError("Missing computation proof".to_string()))?;
+        let work_bits = ComputationProof::decode(proof)?.work_bits(transaction)? as u64;
+
+        let age = block_number.saturating_sub(account.created_at);
+        let capacity = self.capacity(account);
+        let usage_percent = (account.credit.at(block_number, self.regeneration) as u128 * 100
+            / capacity.max(1) as u128)
+            .min(100) as u64;
+
+        Ok(work_bits.saturating_mul(self.work_weight)
+            .saturating_add(log2(age).saturating_mul(self.age_weight))
+            .saturating_add(log2(account.balance).saturating_mul(self.stake_weight))
+            .saturating_sub(usage_percent.saturating_mul(self.usage_weight)))
+    }
+
+    /// Check a pending transaction and return its priority
+    ///
+    /// Block producers and the mempool admit exactly the transactions block
+    /// execution accepts for proof of computation and credit.
+    pub fn admit<E: PairingEngine>(
+        &self,
+        account: &Account<E>,
+        transaction: &Transaction<E>,
+        difficulty: &Difficulty,
+        block_number: u64,
+    ) -> Result<u64, StateError> {
+        if !transaction.verify_computation(difficulty)? {
+            return Err(StateError::ValidationError("Invalid computation proof".to_string()));
+        }
+        self.check_credit(account, transaction, block_number)?;
+        self.priority(account, transaction, block_number)
+    }
+}
+
+/// Number of bits needed to represent a value
+fn log2(value: u64) -> u64 {
+    (64 - value.leading_zeros()) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{AccountId, TransactionType};
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::ProjectiveCurve;
+
+    fn account(balance: u64) -> Account<Bls12_381> {
+        let mut account = Account::new(
+            AccountId(vec![1]),
+            <Bls12_381 as PairingEngine>::G1Projective::prime_subgroup_generator(),
+        );
+        account.balance = balance;
+        account
+    }
+
+    fn transfer(nonce: u64) -> Transaction<Bls12_381> {
+        let mut tx = Transaction::new(
+            TransactionType::Transfer,
+            AccountId(vec![1]),
+            Some(AccountId(vec![2])),
+            10,
+            nonce,
+            vec![],
+        );
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        tx
+    }
+
+    #[test]
+    fn test_credit_regenerates() {
+        let policy = AdmissionPolicy {
+            base_capacity: 5_000,
+            credits_per_stake: 0,
+            regeneration: 1_000,
+            ..AdmissionPolicy::default()
+        };
+        let mut account = account(0);
+        let tx = transfer(0);
+
+        // A transfer costs 2000
+        policy.check_credit(&account, &tx, 10).unwrap();
+        policy.charge(&mut account, 2_000, 10);
+        policy.charge(&mut account, 2_000, 10);
+        assert_eq!(policy.available(&account, 10), 1_000);
+        assert!(policy.check_credit(&account, &tx, 10).is_err());
+
+        // One block later enough has regenerated
+        policy.check_credit(&account, &tx, 11).unwrap();
+        assert_eq!(policy.available(&account, 20), 5_000);
+    }
+
+    #[test]
+    fn test_stake_extends_capacity() {
+        let policy = AdmissionPolicy::default();
+        assert_eq!(policy.capacity(&account(0)), 1_000_000);
+        assert_eq!(policy.capacity(&account(500)), 1_000_500);
+    }
+
+    #[test]
+    fn test_priority_components() {
+        let policy = AdmissionPolicy::default();
+        let tx = transfer(0);
+
+        // Older and better staked accounts rank higher
+        let mut young = account(0);
+        young.created_at = 1_000;
+        let mut old = account(1_000);
+        old.created_at = 0;
+        assert!(
+            policy.priority(&old, &tx, 1_000).unwrap() > policy.priority(&young, &tx, 1_000).unwrap()
+        );
+
+        // Recent usage lowers priority until it regenerates, compared with an
+        // idle account of the same age
+        let idle = account(0);
+        let base = policy.priority(&idle, &tx, 0).unwrap();
+        let mut busy = account(0);
+        policy.charge(&mut busy, 900_000, 0);
+        assert!(policy.priority(&busy, &tx, 0).unwrap() < base);
+        assert_eq!(
+            policy.priority(&busy, &tx, 9).unwrap(),
+            policy.priority(&idle, &tx, 9).unwrap()
+        );
+
+        // Admission verifies the proof
+        let mut unproven = tx.clone();
+        unproven.add_computation_proof(vec![1, 2, 3]);
+        assert!(policy.admit(&idle, &unproven, &Difficulty::default(), 0).is_err());
+        assert_eq!(policy.admit(&idle, &tx, &Difficulty::default(), 0).unwrap(), base);
+    }
+}
@@ -0,0 +1,410 @@
+use super::StateError;
+use super::transaction::SignerSignature;
+use crate::crypto::signature::{Signature, SignatureScheme};
+use ark_ec::PairingEngine;
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+/// Maximum number of keys in an authorization policy
+pub const MAX_POLICY_SIGNERS: usize = 16;
+
+/// Key of a weighted policy
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedSigner<E: PairingEngine> {
+    /// Signer public key
+    pub public_key: E::G1Projective,
+
+    /// Weight counted when the signer signs
+    pub weight: u32,
+}
+
+/// Rule deciding which signatures authorize transactions of an account
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthPolicy<E: PairingEngine> {
+    /// One signature by the account `public_key`
+    Single,
+
+    /// Signer signatures whose weights add up to `threshold`
+    Weighted {
+        /// Policy keys, referenced by index from signer signatures
+        signers: Vec<WeightedSigner<E>>,
+
+        /// Weight required to authorize
+        threshold: u64,
+    },
+
+    /// One signature by the sum of the member keys, produced jointly by the members
+    Aggregated {
+        /// Member public keys
+        members: Vec<E::G1Projective>,
+    },
+}
+
+/// Outcome of checking transaction signatures against a policy
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthorizationReport {
+    /// Weight of valid signatures
+    pub signed_weight: u64,
+
+    /// Weight required
+    pub threshold: u64,
+
+    /// Policy signers without a valid signature, by index
+    pub missing: Vec<u32>,
+
+    /// Signers whose signature did not verify, by index
+    pub invalid: Vec<u32>,
+}
+
+impl AuthorizationReport {
+    /// Check if the signatures authorize the transaction
+    ///
+    /// Any invalid signature rejects the transaction, even if the valid
+    /// ones reach the threshold.
+    pub fn is_authorized(&self) -> bool {
+        self.invalid.is_empty() && self.signed_weight >= self.threshold
+    }
+}
+
+impl<E: PairingEngine> AuthPolicy<E> {
+    /// Create weighted M-of-N policy
+    pub fn weighted(signers: Vec<(E::G1Projective, u32)>, threshold: u64) -> Result<Self, StateError> {
+        let policy = AuthPolicy::Weighted {
+            signers: signers.into_iter()
+                .map(|(public_key, weight)| WeightedSigner { public_key, weight })
+                .collect(),
+            threshold,
+        };
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Create aggregated key policy
+    pub fn aggregated(members: Vec<E::G1Projective>) -> Result<Self, StateError> {
+        let policy = AuthPolicy::Aggregated { members };
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Keys registered by the policy, in index order
+    pub fn keys(&self) -> Vec<E::G1Projective> {
+        match self {
+            AuthPolicy::Single => Vec::new(),
+            AuthPolicy::Weighted { signers, .. } => signers.iter().map(|s| s.public_key).collect(),
+            AuthPolicy::Aggregated { members } => members.clone(),
+        }
+    }
+
+    /// Key verifying single signatures, `None` for weighted policies
+    pub fn signing_key(&self, account_key: &E::G1Projective) -> Option<E::G1Projective> {
+        match self {
+            AuthPolicy::Single => Some(*account_key),
+            AuthPolicy::Weighted { .. } => None,
+            AuthPolicy::Aggregated { members } => {
+                Some(members.iter().fold(E::G1Projective::zero(), |sum, key| sum + key))
+            }
+        }
+    }
+
+    /// Check policy is well formed and satisfiable
+    pub fn check(&self) -> Result<(), StateError> {
+        let keys = self.keys();
+        if keys.len() > MAX_POLICY_SIGNERS {
+            return Err(StateError::AccountError(format!(
+                "Policy has {} keys, limit is {}", keys.len(), MAX_POLICY_SIGNERS
+            )));
+        }
+        for (i, key) in keys.iter().enumerate() {
+            if key.is_zero() || keys[..i].contains(key) {
+                return Err(StateError::AccountError("Policy keys must be distinct".to_string()));
+            }
+        }
+
+        match self {
+            AuthPolicy::Single => Ok(()),
+            AuthPolicy::Weighted { signers, threshold } => {
+                let total: u64 = signers.iter().map(|s| s.weight as u64).sum();
+                if signers.iter().any(|s| s.weight == 0) {
+                    return Err(StateError::AccountError("Signer weight is zero".to_string()));
+                }
+                if *threshold == 0 || *threshold > total {
+                    return Err(StateError::AccountError(format!(
+                        "Threshold {} is not reachable with total weight {}", threshold, total
+                    )));
+                }
+                Ok(())
+            }
+            AuthPolicy::Aggregated { members } => {
+                if members.len() < 2 {
+                    return Err(StateError::AccountError(
+                        "Aggregated policy needs at least two members".to_string()
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check transaction signatures over `message`
+    pub fn authorize(
+        &self,
+        scheme: &SignatureScheme<E>,
+        account_key: &E::G1Projective,
+        message: &[u8],
+        signature: Option<&Signature<E>>,
+        cosignatures: &[SignerSignature<E>],
+    ) -> Result<AuthorizationReport, StateError> {
+        let valid = self.signature_checks(account_key, signature, cosignatures)?
+            .into_iter()
+            .map(|(key, signature)| scheme.verify(message, signature, &key)
+                .map_err(|e| StateError::ValidationError(e.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(self.report(cosignatures, &valid))
+    }
+
+    /// Pair each transaction signature with the key it must verify against
+    ///
+    /// Fails if the signatures do not fit the policy shape.
+    pub fn signature_checks<'a>(
+        &self,
+        account_key: &E::G1Projective,
+        signature: Option<&'a Signature<E>>,
+        cosignatures: &'a [SignerSignature<E>],
+    ) -> Result<Vec<(E::G1Projective, &'a Signature<E>)>, StateError> {
+        let signers = match self {
+            AuthPolicy::Weighted { signers, .. } => signers,
+            _ => {
+                if !cosignatures.is_empty() {
+                    return Err(StateError::ValidationError(
+                        "Policy takes a single signature".to_string()
+                    ));
+                }
+                let key = self.signing_key(account_key).unwrap();
+                return Ok(signature.into_iter().map(|signature| (key, signature)).collect());
+            }
+        };
+
+        if signature.is_some() {
+            return Err(StateError::ValidationError(
+                "Weighted policy takes signer signatures".to_string()
+            ));
+        }
+
+        let mut seen = vec![false; signers.len()];
+        let mut checks = Vec::with_capacity(cosignatures.len());
+        for cosignature in cosignatures {
+            let index = cosignature.signer as usize;
+            let signer = signers.get(index).ok_or_else(|| StateError::ValidationError(
+                format!("Unknown signer {}", cosignature.signer)
+            ))?;
+            if std::mem::replace(&mut seen[index], true) {
+                return Err(StateError::ValidationError(
+                    format!("Duplicate signature by signer {}", cosignature.signer)
+                ));
+            }
+            checks.push((signer.public_key, &cosignature.signature));
+        }
+
+        Ok(checks)
+    }
+
+    /// Build report from the outcome of each `signature_checks` entry
+    pub fn report(&self, cosignatures: &[SignerSignature<E>], valid: &[bool]) -> AuthorizationReport {
+        let (signers, threshold) = match self {
+            AuthPolicy::Weighted { signers, threshold } => (signers, *threshold),
+            _ => {
+                let signed = valid.first().copied().unwrap_or(false);
+                return AuthorizationReport {
+                    signed_weight: signed as u64,
+                    threshold: 1,
+                    missing: if signed { Vec::new() } else { vec![0] },
+                    invalid: if valid.first() == Some(&false) { vec![0] } else { Vec::new() },
+                };
+            }
+        };
+
+        let mut signed = vec![false; signers.len()];
+        let mut invalid = Vec::new();
+        for (cosignature, valid) in cosignatures.iter().zip(valid) {
+            if *valid {
+                signed[cosignature.signer as usize] = true;
+            } else {
+                invalid.push(cosignature.signer);
+            }
+        }
+
+        AuthorizationReport {
+            signed_weight: signers.iter()
+                .zip(&signed)
+                .filter(|(_, signed)| **signed)
+                .map(|(signer, _)| signer.weight as u64)
+                .sum(),
+            threshold,
+            missing: (0..signers.len() as u32).filter(|i| !signed[*i as usize]).collect(),
+            invalid,
+        }
+    }
+
+    /// Serialize policy into an account record
+    pub(crate) fn serialize(&self, bytes: &mut Vec<u8>) -> Result<(), StateError> {
+        let tag: u8 = match self {
+            AuthPolicy::Single => 0,
+            AuthPolicy::Weighted { .. } => 1,
+            AuthPolicy::Aggregated { .. } => 2,
+        };
+        tag.serialize(&mut *bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        match self {
+            AuthPolicy::Single => {}
+            AuthPolicy::Weighted { signers, threshold } => {
+                threshold.serialize(&mut *bytes)
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                (signers.len() as u64).serialize(&mut *bytes)
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                for signer in signers {
+                    signer.public_key.serialize(&mut *bytes)
+                        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                    signer.weight.serialize(&mut *bytes)
+                        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                }
+            }
+            AuthPolicy::Aggregated { members } => {
+                members.serialize(&mut *bytes)
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deserialize policy from an account record
+    pub(crate) fn deserialize(reader: &mut &[u8]) -> Result<Self, StateError> {
+        let tag: u8 = CanonicalDeserialize::deserialize(&mut *reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        match tag {
+            0 => Ok(AuthPolicy::Single),
+            1 => {
+                let threshold: u64 = CanonicalDeserialize::deserialize(&mut *reader)
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                let count: u64 = CanonicalDeserialize::deserialize(&mut *reader)
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                if count as usize > MAX_POLICY_SIGNERS {
+                    return Err(StateError::SerializationError("Too many policy signers".to_string()));
+                }
+
+                let mut signers = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let public_key = CanonicalDeserialize::deserialize(&mut *reader)
+                        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                    let weight = CanonicalDeserialize::deserialize(&mut *reader)
+                        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                    signers.push(WeightedSigner { public_key, weight });
+                }
+                Ok(AuthPolicy::Weighted { signers, threshold })
+            }
+            2 => {
+                let members = CanonicalDeserialize::deserialize(&mut *reader)
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                Ok(AuthPolicy::Aggregated { members })
+            }
+            tag => Err(StateError::SerializationError(format!("Unknown policy tag {}", tag))),
+        }
+    }
+}
+
+impl<E: PairingEngine> Default for AuthPolicy<E> {
+    fn default() -> Self {
+        AuthPolicy::Single
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::PrimeField;
+
+    fn key(secret: u64) -> <Bls12_381 as PairingEngine>::G1Projective {
+        Bls12_381::G1Projective::prime_subgroup_generator().mul(Fr::from(secret).into_repr())
+    }
+
+    fn cosign(scheme: &SignatureScheme<Bls12_381>, message: &[u8], signer: u32, secret: u64) -> SignerSignature<Bls12_381> {
+        SignerSignature {
+            signer,
+            signature: scheme.sign(message, &Fr::from(secret)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_weighted_policy() {
+        let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
+        let policy = AuthPolicy::weighted(vec![(key(1), 1), (key(2), 1), (key(3), 2)], 3).unwrap();
+        let account_key = key(1);
+        let message = b"transfer";
+
+        // Signer 0 alone is short, signer 2 is missing
+        let report = policy.authorize(&scheme, &account_key, message, None, &[
+            cosign(&scheme, message, 0, 1),
+        ]).unwrap();
+        assert!(!report.is_authorized());
+        assert_eq!(report.signed_weight, 1);
+        assert_eq!(report.missing, vec![1, 2]);
+
+        // Signers 0 and 2 reach the threshold
+        let report = policy.authorize(&scheme, &account_key, message, None, &[
+            cosign(&scheme, message, 0, 1),
+            cosign(&scheme, message, 2, 3),
+        ]).unwrap();
+        assert!(report.is_authorized());
+        assert_eq!(report.missing, vec![1]);
+
+        // A bad signature by 1 is reported and rejects the transaction
+        let report = policy.authorize(&scheme, &account_key, message, None, &[
+            cosign(&scheme, message, 0, 1),
+            cosign(&scheme, message, 1, 9),
+            cosign(&scheme, message, 2, 3),
+        ]).unwrap();
+        assert!(!report.is_authorized());
+        assert_eq!(report.invalid, vec![1]);
+
+        // Unknown signers and single signatures are rejected
+        assert!(policy.authorize(&scheme, &account_key, message, None, &[
+            cosign(&scheme, message, 3, 1),
+        ]).is_err());
+        let signature = scheme.sign(message, &Fr::from(1u64)).unwrap();
+        assert!(policy.authorize(&scheme, &account_key, message, Some(&signature), &[]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_policies() {
+        assert!(AuthPolicy::<Bls12_381>::weighted(vec![(key(1), 1), (key(2), 1)], 3).is_err());
+        assert!(AuthPolicy::<Bls12_381>::weighted(vec![(key(1), 1), (key(1), 1)], 1).is_err());
+        assert!(AuthPolicy::<Bls12_381>::weighted(vec![(key(1), 0)], 1).is_err());
+        assert!(AuthPolicy::<Bls12_381>::aggregated(vec![key(1)]).is_err());
+    }
+
+    #[test]
+    fn test_aggregated_policy() {
+        let scheme = SignatureScheme::<Bls12_381>::new(128).unwrap();
+        let policy = AuthPolicy::aggregated(vec![key(2), key(5)]).unwrap();
+        let message = b"transfer";
+
+        // Jointly produced signature under the aggregate secret
+        let signature = scheme.sign(message, &Fr::from(7u64)).unwrap();
+        let report = policy.authorize(&scheme, &key(1), message, Some(&signature), &[]).unwrap();
+        assert!(report.is_authorized());
+
+        let signature = scheme.sign(message, &Fr::from(2u64)).unwrap();
+        let report = policy.authorize(&scheme, &key(1), message, Some(&signature), &[]).unwrap();
+        assert_eq!(report.invalid, vec![0]);
+
+        // Policy survives the account record encoding
+        let mut bytes = Vec::new();
+        policy.serialize(&mut bytes).unwrap();
+        assert_eq!(AuthPolicy::<Bls12_381>::deserialize(&mut &bytes[..]).unwrap(), policy);
+    }
+}
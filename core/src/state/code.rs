@@ -0,0 +1,124 @@
+use super::StateError;
+use crate::crypto::hash_to_curve::{self, DomainTag};
+use ark_ec::PairingEngine;
+use std::collections::HashMap;
+
+/// Maximum size of deployed contract code in bytes
+pub const MAX_CODE_SIZE: usize = 24 * 1024;
+
+/// Content hash of contract code
+pub fn code_hash<E: PairingEngine>(code: &[u8]) -> Result<E::Fr, StateError> {
+    hash_to_curve::hash_to_scalar(code, DomainTag::CODE_HASH)
+        .map_err(|e| StateError::SerializationError(e.to_string()))
+}
+
+/// Check code against the deploy size limit
+pub fn check_code_size(code: &[u8]) -> Result<(), StateError> {
+    if code.is_empty() {
+        return Err(StateError::ValidationError("Contract code is empty".to_string()));
+    }
+
+    if code.len() > MAX_CODE_SIZE {
+        return Err(StateError::ValidationError(format!(
+            "Contract code is {} bytes, limit is {}", code.len(), MAX_CODE_SIZE
+        )));
+    }
+
+    Ok(())
+}
+
+/// Content-addressed contract code store
+#[derive(Clone, Debug)]
+pub struct CodeStore<E: PairingEngine> {
+    /// Code by code hash, shared by all contracts deploying it
+    code: HashMap<E::Fr, Vec<u8>>,
+}
+
+impl<E: PairingEngine> CodeStore<E> {
+    /// Create empty store
+    pub fn new() -> Self {
+        Self {
+            code: HashMap::new(),
+        }
+    }
+
+    /// Store code, returning its hash; identical code is stored once
+    pub fn insert(&mut self, code: &[u8]) -> Result<E::Fr, StateError> {
+        let hash = code_hash::<E>(code)?;
+        self.code.entry(hash).or_insert_with(|| code.to_vec());
+        Ok(hash)
+    }
+
+    /// Store code under a claimed hash
+    pub fn insert_with_hash(&mut self, hash: E::Fr, code: &[u8]) -> Result<(), StateError> {
+        if code_hash::<E>(code)? != hash {
+            return Err(StateError::StorageError("Code does not match its hash".to_string()));
+        }
+        self.code.entry(hash).or_insert_with(|| code.to_vec());
+        Ok(())
+    }
+
+    /// Get code by hash
+    pub fn get(&self, hash: &E::Fr) -> Option<&Vec<u8>> {
+        self.code.get(hash)
+    }
+
+    /// Check if code is stored
+    pub fn contains(&self, hash: &E::Fr) -> bool {
+        self.code.contains_key(hash)
+    }
+
+    /// Number of distinct code blobs
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    /// Check if store is empty
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    /// Get all code by hash
+    pub fn to_map(&self) -> HashMap<E::Fr, Vec<u8>> {
+        self.code.clone()
+    }
+
+    /// Remove all code
+    pub fn clear(&mut self) {
+        self.code.clear();
+    }
+}
+
+impl<E: PairingEngine> Default for CodeStore<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    #[test]
+    fn test_code_deduplication() {
+        let mut store = CodeStore::<Bls12_381>::new();
+
+        let first = store.insert(&[1, 2, 3]).unwrap();
+        let second = store.insert(&[1, 2, 3]).unwrap();
+        let other = store.insert(&[4, 5]).unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&first), Some(&vec![1, 2, 3]));
+        assert!(store.insert_with_hash(Fr::from(1u64), &[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_code_size_limit() {
+        assert!(check_code_size(&[0u8; MAX_CODE_SIZE]).is_ok());
+        assert!(check_code_size(&[0u8; MAX_CODE_SIZE + 1]).is_err());
+        assert!(check_code_size(&[]).is_err());
+    }
+}
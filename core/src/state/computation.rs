@@ -0,0 +1,351 @@
+use super::{StateError, Transaction, TransactionType};
+use ark_ec::PairingEngine;
+use ark_serialize::CanonicalSerialize;
+use sha3::{Digest, Sha3_256};
+
+/// Version of the computation proof encoding
+pub const COMPUTATION_PROOF_VERSION: u8 = 1;
+
+/// Computation charged to every transaction
+pub const BASE_COMPUTATION: u64 = 1000;
+
+/// Difficulty for the cheapest transactions when the network is idle
+pub const DEFAULT_BASE_BITS: u32 = 8;
+
+/// Upper bound on the difficulty of any single proof
+pub const MAX_DIFFICULTY_BITS: u32 = 64;
+
+/// Computation per block the default adjuster considers normal
+pub const DEFAULT_TARGET_LOAD: u64 = 1_000_000;
+
+/// Blocks the default adjuster averages
+pub const DEFAULT_LOAD_WINDOW: usize = 64;
+
+/// Difficulty cap of the default adjuster
+pub const DEFAULT_MAX_BASE_BITS: u32 = 24;
+
+/// Domain separating the puzzle from other uses of the hash
+const PUZZLE_DOMAIN: &[u8] = b"aporia-zero/proof-of-computation/v1";
+
+/// Encoded proof size: version and solution
+const PROOF_SIZE: usize = 1 + 8;
+
+/// Computation a transaction is charged before execution
+///
+/// Contract calls are charged their execution on top of this.
+pub fn computation_cost<E: PairingEngine>(transaction: &Transaction<E>) -> u64 {
+    let type_cost = match transaction.tx_type {
+        TransactionType::Transfer => 1000,
+        TransactionType::Deploy => 50000,
+        TransactionType::Call => 5000,
+        TransactionType::CreateAccount => 2000,
+        TransactionType::UpdateAccount => 3000,
+    };
+
+    BASE_COMPUTATION
+        .saturating_add((transaction.data.len() as u64).saturating_mul(10))
+        .saturating_add(type_cost)
+}
+
+/// Puzzle difficulty in leading zero bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Difficulty {
+    /// Bits required of a transaction costing `BASE_COMPUTATION`
+    base_bits: u32,
+}
+
+impl Difficulty {
+    /// Create difficulty from base bits
+    pub fn new(base_bits: u32) -> Self {
+        Self {
+            base_bits: base_bits.min(MAX_DIFFICULTY_BITS),
+        }
+    }
+
+    /// Bits required of a transaction costing `BASE_COMPUTATION`
+    pub fn base_bits(&self) -> u32 {
+        self.base_bits
+    }
+
+    /// Bits required for a transaction
+    ///
+    /// Every doubling of the cost adds one bit, so expected work grows
+    /// linearly with the computation the network spends on it.
+    pub fn required_bits<E: PairingEngine>(&self, transaction: &Transaction<E>) -> u32 {
+        let ratio = computation_cost(transaction) / BASE_COMPUTATION;
+        let extra = 63 - ratio.max(1).leading_zeros();
+        (self.base_bits + extra).min(MAX_DIFFICULTY_BITS)
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::new(DEFAULT_BASE_BITS)
+    }
+}
+
+/// Solution to the puzzle of one transaction
+///
+/// The puzzle is bound to the transaction hash and the sender nonce, so a
+/// solution can be neither reused for another transaction nor replayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputationProof {
+    /// Value making the puzzle hash meet the difficulty
+    pub solution: u64,
+}
+
+impl ComputationProof {
+    /// Search for a solution meeting the difficulty
+    pub fn generate<E: PairingEngine>(
+        transaction: &Transaction<E>,
+        difficulty: &Difficulty,
+    ) -> Result<Self, StateError> {
+        let bits = difficulty.required_bits(transaction);
+        let challenge = Self::challenge(transaction)?;
+
+        (0..=u64::MAX)
+            .find(|solution| leading_zero_bits(&Self::work(&challenge, *solution)) >= bits)
+            .map(|solution| Self { solution })
+            .ok_or_else(|| StateError::ValidationError("No computation proof found".to_string()))
+    }
+
+    /// Check the solution meets the difficulty for the transaction
+    pub fn verify<E: PairingEngine>(
+        &self,
+        transaction: &Transaction<E>,
+        difficulty: &Difficulty,
+    ) -> Result<bool, StateError> {
+        Ok(self.work_bits(transaction)? >= difficulty.required_bits(transaction))
+    }
+
+    /// Leading zero bits the solution achieves for the transaction
+    pub fn work_bits<E: PairingEngine>(&self, transaction: &Transaction<E>) -> Result<u32, StateError> {
+        let challenge = Self::challenge(transaction)?;
+        Ok(leading_zero_bits(&Self::work(&challenge, self.solution)))
+    }
+
+    /// Encode proof
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PROOF_SIZE);
+        bytes.push(COMPUTATION_PROOF_VERSION);
+        bytes.extend_from_slice(&self.solution.to_be_bytes());
+        bytes
+    }
+
+    /// Decode proof
+    pub fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+        if bytes.len() != PROOF_SIZE {
+            return Err(StateError::ValidationError(format!(
+                "Computation proof must be {} bytes",
+                PROOF_SIZE
+            )));
+        }
+        if bytes[0] != COMPUTATION_PROOF_VERSION {
+            return Err(StateError::ValidationError(format!(
+                "Unsupported computation proof version {}",
+                bytes[0]
+            )));
+        }
+
+        let mut solution = [0u8; 8];
+        solution.copy_from_slice(&bytes[1..]);
+        Ok(Self {
+            solution: u64::from_be_bytes(solution),
+        })
+    }
+
+    /// Hash binding the puzzle to the transaction and sender nonce
+    fn challenge<E: PairingEngine>(transaction: &Transaction<E>) -> Result<[u8; 32], StateError> {
+        let mut hash = Vec::new();
+        transaction.hash()?.serialize(&mut hash)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(PUZZLE_DOMAIN);
+        hasher.update(&hash);
+        hasher.update(transaction.nonce.to_be_bytes());
+        Ok(hasher.finalize().into())
+    }
+
+    /// Hash a candidate solution
+    fn work(challenge: &[u8; 32], solution: u64) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(challenge);
+        hasher.update(solution.to_be_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Count leading zero bits of a hash
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte != 0 {
+            return bits + byte.leading_zeros();
+        }
+        bits += 8;
+    }
+    bits
+}
+
+/// Difficulty derived from the load of recent blocks
+///
+/// While the average computation per block stays at or below the target the
+/// minimum difficulty applies. Above it, every doubling of the load adds one
+/// bit. The adjuster holds no state: the loads are read from the blocks
+/// recorded on chain and passed in, so nodes following the same chain derive
+/// the same difficulty for each block, also after a restart or re-execution.
+#[derive(Clone, Debug)]
+pub struct DifficultyAdjuster {
+    /// Computation per block considered normal
+    target_load: u64,
+
+    /// Number of blocks averaged
+    window: usize,
+
+    /// Difficulty when load is at or below target
+    min_bits: u32,
+
+    /// Difficulty cap
+    max_bits: u32,
+}
+
+impl DifficultyAdjuster {
+    /// Create adjuster
+    pub fn new(target_load: u64, window: usize, min_bits: u32, max_bits: u32) -> Result<Self, StateError> {
+        if target_load == 0 || window == 0 {
+            return Err(StateError::ValidationError(
+                "Target load and window must be positive".to_string(),
+            ));
+        }
+        if min_bits > max_bits || max_bits > MAX_DIFFICULTY_BITS {
+            return Err(StateError::ValidationError(format!(
+                "Difficulty bounds must satisfy min <= max <= {}",
+                MAX_DIFFICULTY_BITS
+            )));
+        }
+
+        Ok(Self {
+            target_load,
+            window,
+            min_bits,
+            max_bits,
+        })
+    }
+
+    /// Adjuster pinned to one difficulty whatever the load
+    pub fn fixed(difficulty: Difficulty) -> Self {
+        Self {
+            target_load: DEFAULT_TARGET_LOAD,
+            window: 1,
+            min_bits: difficulty.base_bits(),
+            max_bits: difficulty.base_bits(),
+        }
+    }
+
+    /// Average computation of the last blocks in the window
+    ///
+    /// `recent_loads` is the computation used by the preceding blocks, oldest first.
+    pub fn average_load(&self, recent_loads: &[u64]) -> u64 {
+        let window = &recent_loads[recent_loads.len().saturating_sub(self.window)..];
+        if window.is_empty() {
+            return 0;
+        }
+        let total: u128 = window.iter().map(|load| *load as u128).sum();
+        (total / window.len() as u128) as u64
+    }
+
+    /// Difficulty for the block following `recent_loads`
+    pub fn difficulty(&self, recent_loads: &[u64]) -> Difficulty {
+        let ratio = self.average_load(recent_loads) / self.target_load;
+        let extra = 63 - ratio.max(1).leading_zeros();
+        Difficulty::new((self.min_bits + extra).min(self.max_bits))
+    }
+}
+
+impl Default for DifficultyAdjuster {
+    fn default() -> Self {
+        Self {
+            target_load: DEFAULT_TARGET_LOAD,
+            window: DEFAULT_LOAD_WINDOW,
+            min_bits: DEFAULT_BASE_BITS,
+            max_bits: DEFAULT_MAX_BASE_BITS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AccountId;
+    use ark_bls12_381::Bls12_381;
+
+    fn transaction(tx_type: TransactionType, nonce: u64, data: Vec<u8>) -> Transaction<Bls12_381> {
+        Transaction::new(
+            tx_type,
+            AccountId(vec![1, 2, 3]),
+            Some(AccountId(vec![4, 5, 6])),
+            100,
+            nonce,
+            data,
+        )
+    }
+
+    #[test]
+    fn test_generate_and_verify() {
+        let difficulty = Difficulty::new(6);
+        let tx = transaction(TransactionType::Transfer, 0, vec![]);
+
+        let proof = ComputationProof::generate(&tx, &difficulty).unwrap();
+        assert!(proof.verify(&tx, &difficulty).unwrap());
+        assert_eq!(ComputationProof::decode(&proof.encode()).unwrap(), proof);
+
+        // Bound to the sender nonce
+        let replayed = transaction(TransactionType::Transfer, 1, vec![]);
+        assert_ne!(
+            ComputationProof::challenge(&tx).unwrap(),
+            ComputationProof::challenge(&replayed).unwrap()
+        );
+
+        // Not enough work for a harder puzzle
+        assert!(!proof.verify(&tx, &Difficulty::new(MAX_DIFFICULTY_BITS)).unwrap());
+    }
+
+    #[test]
+    fn test_difficulty_scales_with_cost() {
+        let difficulty = Difficulty::new(8);
+
+        // 2000 for a transfer, 51000 for an empty deploy
+        assert_eq!(difficulty.required_bits(&transaction(TransactionType::Transfer, 0, vec![])), 9);
+        assert_eq!(difficulty.required_bits(&transaction(TransactionType::Deploy, 0, vec![])), 13);
+        assert_eq!(
+            difficulty.required_bits(&transaction(TransactionType::Deploy, 0, vec![0; 20000])),
+            15
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_proof() {
+        assert!(ComputationProof::decode(&[1, 2, 3]).is_err());
+        assert!(ComputationProof::decode(&[2, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_difficulty_follows_load() {
+        let adjuster = DifficultyAdjuster::new(10_000, 4, 8, 12).unwrap();
+        assert_eq!(adjuster.difficulty(&[]).base_bits(), 8);
+        assert_eq!(adjuster.difficulty(&[5_000]).base_bits(), 8);
+
+        // Average of 4 blocks at 40000 is 4x the target
+        let mut loads = vec![5_000, 40_000, 40_000, 40_000, 40_000];
+        assert_eq!(adjuster.difficulty(&loads).base_bits(), 10);
+
+        // Capped
+        loads.extend([10_000_000; 4]);
+        assert_eq!(adjuster.difficulty(&loads).base_bits(), 12);
+
+        // Old blocks leave the window
+        loads.extend([0; 4]);
+        assert_eq!(adjuster.difficulty(&loads).base_bits(), 8);
+    }
+}
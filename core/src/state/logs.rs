@@ -0,0 +1,346 @@
+use super::{AccountId, StateError};
+use ark_ec::PairingEngine;
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::BTreeMap;
+
+/// Size of a logs bloom in bytes
+pub const BLOOM_SIZE: usize = 256;
+
+/// Number of bits set per bloom entry
+const BLOOM_HASHES: usize = 3;
+
+/// Event log emitted during transaction execution
+#[derive(Clone, Debug, PartialEq)]
+pub struct Log<E: PairingEngine> {
+    /// Emitting contract address
+    pub address: AccountId,
+
+    /// Indexed topics
+    pub topics: Vec<E::Fr>,
+
+    /// Log data
+    pub data: Vec<u8>,
+
+    /// Block number
+    pub block_number: u64,
+
+    /// Transaction hash
+    pub transaction_hash: E::Fr,
+}
+
+impl<E: PairingEngine> Log<E> {
+    /// Create new log, block context is filled in by the state transition
+    pub fn new(address: AccountId, topics: Vec<E::Fr>, data: Vec<u8>) -> Self {
+        Self {
+            address,
+            topics,
+            data,
+            block_number: 0,
+            transaction_hash: E::Fr::zero(),
+        }
+    }
+
+    /// Serialize log
+    pub fn encode(&self) -> Result<Vec<u8>, StateError> {
+        let mut bytes = Vec::new();
+
+        self.address.0.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.topics.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.data.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.block_number.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.transaction_hash.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        Ok(bytes)
+    }
+
+    /// Deserialize log
+    pub fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+        let mut reader = bytes;
+
+        let address: Vec<u8> = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let topics: Vec<E::Fr> = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let data: Vec<u8> = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let block_number: u64 = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let transaction_hash: E::Fr = CanonicalDeserialize::deserialize(&mut reader)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        Ok(Self {
+            address: AccountId(address),
+            topics,
+            data,
+            block_number,
+            transaction_hash,
+        })
+    }
+}
+
+/// Bloom filter over log addresses and topics
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogsBloom(pub [u8; BLOOM_SIZE]);
+
+impl LogsBloom {
+    /// Create empty bloom
+    pub fn new() -> Self {
+        Self([0u8; BLOOM_SIZE])
+    }
+
+    /// Build bloom from logs
+    pub fn from_logs<E: PairingEngine>(logs: &[Log<E>]) -> Result<Self, StateError> {
+        let mut bloom = Self::new();
+        for log in logs {
+            bloom.accrue_log(log)?;
+        }
+        Ok(bloom)
+    }
+
+    /// Add log address and topics
+    pub fn accrue_log<E: PairingEngine>(&mut self, log: &Log<E>) -> Result<(), StateError> {
+        self.accrue(&log.address.0);
+        for topic in &log.topics {
+            self.accrue(&topic_bytes::<E>(topic)?);
+        }
+        Ok(())
+    }
+
+    /// Add raw entry
+    pub fn accrue(&mut self, entry: &[u8]) {
+        for bit in Self::bit_positions(entry) {
+            self.0[BLOOM_SIZE - 1 - bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Check if entry may be present
+    pub fn contains(&self, entry: &[u8]) -> bool {
+        Self::bit_positions(entry)
+            .iter()
+            .all(|bit| self.0[BLOOM_SIZE - 1 - bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Merge another bloom into this one
+    pub fn union(&mut self, other: &LogsBloom) {
+        for (byte, other_byte) in self.0.iter_mut().zip(other.0.iter()) {
+            *byte |= other_byte;
+        }
+    }
+
+    /// Bit positions set for entry
+    fn bit_positions(entry: &[u8]) -> [usize; BLOOM_HASHES] {
+        let hash = Sha3_256::digest(entry);
+        let mut positions = [0usize; BLOOM_HASHES];
+        for (i, position) in positions.iter_mut().enumerate() {
+            *position = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) % (BLOOM_SIZE * 8);
+        }
+        positions
+    }
+}
+
+impl Default for LogsBloom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Query over indexed logs
+#[derive(Clone, Debug)]
+pub struct LogFilter<E: PairingEngine> {
+    /// First block height (inclusive)
+    pub from_block: u64,
+
+    /// Last block height (inclusive)
+    pub to_block: u64,
+
+    /// Matching addresses, empty matches any
+    pub addresses: Vec<AccountId>,
+
+    /// Topics by position, `None` matches any
+    pub topics: Vec<Option<E::Fr>>,
+}
+
+impl<E: PairingEngine> LogFilter<E> {
+    /// Create filter over a block range
+    pub fn new(from_block: u64, to_block: u64) -> Self {
+        Self {
+            from_block,
+            to_block,
+            addresses: Vec::new(),
+            topics: Vec::new(),
+        }
+    }
+
+    /// Restrict to address
+    pub fn address(mut self, address: AccountId) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    /// Restrict topic at position
+    pub fn topic(mut self, position: usize, topic: E::Fr) -> Self {
+        if self.topics.len() <= position {
+            self.topics.resize(position + 1, None);
+        }
+        self.topics[position] = Some(topic);
+        self
+    }
+
+    /// Check if a block bloom may contain matching logs
+    pub fn may_match(&self, bloom: &LogsBloom) -> Result<bool, StateError> {
+        if !self.addresses.is_empty()
+            && !self.addresses.iter().any(|address| bloom.contains(&address.0))
+        {
+            return Ok(false);
+        }
+
+        for topic in self.topics.iter().flatten() {
+            if !bloom.contains(&topic_bytes::<E>(topic)?) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Check if log matches
+    pub fn matches(&self, log: &Log<E>) -> bool {
+        if log.block_number < self.from_block || log.block_number > self.to_block {
+            return false;
+        }
+
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false;
+        }
+
+        self.topics.iter().enumerate().all(|(i, topic)| match topic {
+            Some(topic) => log.topics.get(i) == Some(topic),
+            None => true,
+        })
+    }
+}
+
+/// Logs of one block
+#[derive(Clone, Debug)]
+pub struct BlockLogs<E: PairingEngine> {
+    /// Bloom over all logs in the block
+    pub bloom: LogsBloom,
+
+    /// Logs in execution order
+    pub logs: Vec<Log<E>>,
+}
+
+/// In-memory log index keyed by block height
+pub struct LogIndex<E: PairingEngine> {
+    /// Logs by block height
+    blocks: BTreeMap<u64, BlockLogs<E>>,
+}
+
+impl<E: PairingEngine> LogIndex<E> {
+    /// Create empty index
+    pub fn new() -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Index logs of a block, replacing any previous entry
+    pub fn insert(&mut self, height: u64, logs: Vec<Log<E>>) -> Result<LogsBloom, StateError> {
+        let bloom = LogsBloom::from_logs(&logs)?;
+        self.blocks.insert(height, BlockLogs { bloom: bloom.clone(), logs });
+        Ok(bloom)
+    }
+
+    /// Get bloom of a block
+    pub fn bloom(&self, height: u64) -> Option<&LogsBloom> {
+        self.blocks.get(&height).map(|block| &block.bloom)
+    }
+
+    /// Find logs matching filter, skipping blocks ruled out by their bloom
+    pub fn query(&self, filter: &LogFilter<E>) -> Result<Vec<Log<E>>, StateError> {
+        let mut result = Vec::new();
+        if filter.from_block > filter.to_block {
+            return Ok(result);
+        }
+
+        for block in self.blocks.range(filter.from_block..=filter.to_block).map(|(_, block)| block) {
+            if !filter.may_match(&block.bloom)? {
+                continue;
+            }
+            result.extend(block.logs.iter().filter(|log| filter.matches(log)).cloned());
+        }
+
+        Ok(result)
+    }
+
+    /// Drop blocks below height
+    pub fn prune_below(&mut self, height: u64) {
+        self.blocks = self.blocks.split_off(&height);
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+}
+
+/// Canonical bytes of a topic for bloom hashing
+pub fn topic_bytes<E: PairingEngine>(topic: &E::Fr) -> Result<Vec<u8>, StateError> {
+    let mut bytes = Vec::new();
+    topic.serialize(&mut bytes)
+        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    fn test_log(address: u8, topic: u64, block_number: u64) -> Log<Bls12_381> {
+        let mut log = Log::new(AccountId(vec![address]), vec![Fr::from(topic)], vec![address]);
+        log.block_number = block_number;
+        log
+    }
+
+    #[test]
+    fn test_bloom() {
+        let log = test_log(1, 7, 1);
+        let bloom = LogsBloom::from_logs(&[log]).unwrap();
+
+        assert!(bloom.contains(&[1]));
+        assert!(bloom.contains(&topic_bytes::<Bls12_381>(&Fr::from(7u64)).unwrap()));
+        assert!(!bloom.contains(&[2]));
+    }
+
+    #[test]
+    fn test_log_encoding() {
+        let log = test_log(3, 9, 42);
+        let decoded = Log::<Bls12_381>::decode(&log.encode().unwrap()).unwrap();
+        assert_eq!(log, decoded);
+    }
+
+    #[test]
+    fn test_log_query() {
+        let mut index = LogIndex::<Bls12_381>::new();
+        for height in 1..=10 {
+            index.insert(height, vec![test_log(1, height % 2, height), test_log(2, 5, height)]).unwrap();
+        }
+
+        let filter = LogFilter::new(3, 7)
+            .address(AccountId(vec![1]))
+            .topic(0, Fr::from(1u64));
+        let logs = index.query(&filter).unwrap();
+
+        let heights: Vec<u64> = logs.iter().map(|log| log.block_number).collect();
+        assert_eq!(heights, vec![3, 5, 7]);
+        assert!(logs.iter().all(|log| log.address == AccountId(vec![1])));
+    }
+}
@@ -1,5 +1,6 @@
 use super::StateError;
 use crate::crypto::hash::{HashFunction, CryptoHash, HashConfig};
+use crate::crypto::hash_to_curve::DomainTag;
 use ark_ec::PairingEngine;
 use ark_ff::Field;
 use std::collections::HashMap;
@@ -79,19 +80,207 @@ impl<E: PairingEngine> MerkleTree<E> {
         value: &[u8],
         proof: &MerkleProof<E>,
     ) -> Result<bool, StateError> {
+        self.verify_proof_against(&self.root.hash, key, Some(value), proof)
+    }
+
+    /// Verify Merkle proof against an arbitrary root; `None` proves the key is absent
+    pub fn verify_proof_against(
+        &self,
+        root: &E::Fr,
+        key: &[u8],
+        value: Option<&[u8]>,
+        proof: &MerkleProof<E>,
+    ) -> Result<bool, StateError> {
+        if proof.proof.len() != self.depth {
+            return Ok(false);
+        }
+
         let path = self.get_path(key);
-        let mut current_hash = self.hash_leaf(value)?;
+        let mut current_hash = match value {
+            Some(value) => self.hash_leaf(value)?,
+            None => E::Fr::zero(),
+        };
         
-        for (i, sibling) in proof.proof.iter().enumerate() {
+        // Siblings are ordered from the root down
+        for (i, sibling) in proof.proof.iter().enumerate().rev() {
             let (left, right) = if path[i] {
                 (sibling, &current_hash)
             } else {
                 (&current_hash, sibling)
             };
-            current_hash = self.hash_nodes(left, right)?;
+            current_hash = if left.is_zero() && right.is_zero() {
+                E::Fr::zero()
+            } else {
+                self.hash_nodes(left, right)?
+            };
         }
         
-        Ok(current_hash == self.root.hash)
+        Ok(current_hash == *root)
+    }
+
+    /// Get root hash
+    pub fn root(&self) -> E::Fr {
+        self.root.hash
+    }
+
+    /// Get leaf path for key
+    pub fn key_path(&self, key: &[u8]) -> Vec<bool> {
+        let mut path = self.get_path(key);
+        path.truncate(self.depth);
+        path
+    }
+
+    /// Get proof that the leaves with paths in `[start, end]` are exactly
+    /// those stored in the tree
+    pub fn get_range_proof(
+        &self,
+        start: &[bool],
+        end: &[bool],
+    ) -> Result<RangeProof<E>, StateError> {
+        if start.len() != self.depth || end.len() != self.depth || start > end {
+            return Err(StateError::MerkleError("Invalid proof range".to_string()));
+        }
+
+        let mut prefix = Vec::with_capacity(self.depth);
+        let mut hashes = Vec::new();
+        self.build_range_proof(Some(&self.root), &mut prefix, start, end, &mut hashes);
+        Ok(RangeProof { hashes })
+    }
+
+    /// Verify range proof for sorted `(path, value)` leaves against `root`
+    pub fn verify_range_proof(
+        &self,
+        root: &E::Fr,
+        start: &[bool],
+        end: &[bool],
+        leaves: &[(Vec<bool>, Vec<u8>)],
+        proof: &RangeProof<E>,
+    ) -> Result<bool, StateError> {
+        if start.len() != self.depth || end.len() != self.depth || start > end {
+            return Err(StateError::MerkleError("Invalid proof range".to_string()));
+        }
+
+        // Leaves must be strictly ordered and inside the range
+        for (i, (path, _)) in leaves.iter().enumerate() {
+            if path.len() != self.depth || path.as_slice() < start || path.as_slice() > end {
+                return Ok(false);
+            }
+            if i > 0 && leaves[i - 1].0 >= *path {
+                return Ok(false);
+            }
+        }
+
+        let mut prefix = Vec::with_capacity(self.depth);
+        let mut hashes = proof.hashes.iter();
+        let computed = self.compute_range_hash(&mut prefix, start, end, leaves, &mut hashes)?;
+
+        Ok(hashes.next().is_none() && computed == *root)
+    }
+
+    /// Hash of an optional child, zero for empty subtrees
+    fn child_hash(child: &Option<Box<Node<E>>>) -> E::Fr {
+        child.as_ref().map_or(E::Fr::zero(), |node| node.hash)
+    }
+
+    /// Collect hashes of maximal subtrees lying outside the range
+    fn build_range_proof(
+        &self,
+        node: Option<&Node<E>>,
+        prefix: &mut Vec<bool>,
+        start: &[bool],
+        end: &[bool],
+        hashes: &mut Vec<E::Fr>,
+    ) {
+        match Self::range_overlap(prefix, start, end) {
+            RangeOverlap::Outside => {
+                hashes.push(node.map_or(E::Fr::zero(), |n| n.hash));
+            }
+            RangeOverlap::Inside => {}
+            RangeOverlap::Partial => {
+                prefix.push(false);
+                self.build_range_proof(
+                    node.and_then(|n| n.left.as_deref()),
+                    prefix,
+                    start,
+                    end,
+                    hashes,
+                );
+                prefix.pop();
+
+                prefix.push(true);
+                self.build_range_proof(
+                    node.and_then(|n| n.right.as_deref()),
+                    prefix,
+                    start,
+                    end,
+                    hashes,
+                );
+                prefix.pop();
+            }
+        }
+    }
+
+    /// Recompute subtree hash from in-range leaves and proof hashes
+    fn compute_range_hash<'a>(
+        &self,
+        prefix: &mut Vec<bool>,
+        start: &[bool],
+        end: &[bool],
+        leaves: &[(Vec<bool>, Vec<u8>)],
+        hashes: &mut impl Iterator<Item = &'a E::Fr>,
+    ) -> Result<E::Fr, StateError>
+    where
+        E::Fr: 'a,
+    {
+        let overlap = Self::range_overlap(prefix, start, end);
+
+        if overlap == RangeOverlap::Outside {
+            return hashes.next().copied().ok_or_else(|| {
+                StateError::MerkleError("Range proof is too short".to_string())
+            });
+        }
+
+        if leaves.is_empty() && overlap == RangeOverlap::Inside {
+            return Ok(E::Fr::zero());
+        }
+
+        if prefix.len() == self.depth {
+            return self.hash_leaf(&leaves[0].1);
+        }
+
+        let split = leaves.partition_point(|(path, _)| !path[prefix.len()]);
+
+        prefix.push(false);
+        let left = self.compute_range_hash(prefix, start, end, &leaves[..split], hashes)?;
+        prefix.pop();
+
+        prefix.push(true);
+        let right = self.compute_range_hash(prefix, start, end, &leaves[split..], hashes)?;
+        prefix.pop();
+
+        if left.is_zero() && right.is_zero() {
+            Ok(E::Fr::zero())
+        } else {
+            self.hash_nodes(&left, &right)
+        }
+    }
+
+    /// Classify the subtree under `prefix` against the range `[start, end]`
+    fn range_overlap(prefix: &[bool], start: &[bool], end: &[bool]) -> RangeOverlap {
+        let len = prefix.len();
+
+        if prefix < &start[..len] || prefix > &end[..len] {
+            return RangeOverlap::Outside;
+        }
+
+        let above_start = prefix > &start[..len] || start[len..].iter().all(|bit| !bit);
+        let below_end = prefix < &end[..len] || end[len..].iter().all(|bit| *bit);
+
+        if above_start && below_end {
+            RangeOverlap::Inside
+        } else {
+            RangeOverlap::Partial
+        }
     }
 
     /// Update leaf node
@@ -127,9 +316,9 @@ impl<E: PairingEngine> MerkleTree<E> {
         )?;
 
         node.hash = if path[depth] {
-            self.hash_nodes(&node.left.as_ref().unwrap().hash, &child_hash)?
+            self.hash_nodes(&Self::child_hash(&node.left), &child_hash)?
         } else {
-            self.hash_nodes(&child_hash, &node.right.as_ref().unwrap().hash)?
+            self.hash_nodes(&child_hash, &Self::child_hash(&node.right))?
         };
 
         Ok(node.hash)
@@ -171,9 +360,9 @@ impl<E: PairingEngine> MerkleTree<E> {
         }
 
         proof.push(if path[depth] {
-            node.left.as_ref().unwrap().hash
+            Self::child_hash(&node.left)
         } else {
-            node.right.as_ref().unwrap().hash
+            Self::child_hash(&node.right)
         });
 
         let child = if path[depth] {
@@ -184,7 +373,11 @@ impl<E: PairingEngine> MerkleTree<E> {
 
         match child {
             Some(child) => self.build_proof(child, path, depth + 1, proof),
-            None => Ok(()),
+            None => {
+                // Remaining siblings lie in an empty subtree
+                proof.resize(self.depth, E::Fr::zero());
+                Ok(())
+            }
         }
     }
 
@@ -202,7 +395,7 @@ impl<E: PairingEngine> MerkleTree<E> {
 
     /// Hash leaf node
     fn hash_leaf(&self, value: &[u8]) -> Result<E::Fr, StateError> {
-        self.hasher.hash_to_field(value)
+        self.hasher.hash_to_field(value, DomainTag::MERKLE_LEAF)
             .map_err(|e| StateError::MerkleError(e.to_string()))
     }
 
@@ -212,7 +405,7 @@ impl<E: PairingEngine> MerkleTree<E> {
         data.extend_from_slice(&left.to_repr());
         data.extend_from_slice(&right.to_repr());
         
-        self.hasher.hash_to_field(&data)
+        self.hasher.hash_to_field(&data, DomainTag::MERKLE_NODE)
             .map_err(|e| StateError::MerkleError(e.to_string()))
     }
 }
@@ -224,6 +417,21 @@ pub struct MerkleProof<E: PairingEngine> {
     proof: Vec<E::Fr>,
 }
 
+/// Merkle range proof structure
+#[derive(Clone, Debug)]
+pub struct RangeProof<E: PairingEngine> {
+    /// Hashes of subtrees outside the range, in left-to-right order
+    pub hashes: Vec<E::Fr>,
+}
+
+/// Position of a subtree relative to a key range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RangeOverlap {
+    Outside,
+    Inside,
+    Partial,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,5 +466,37 @@ mod tests {
         
         // Verify proof
         assert!(tree.verify_proof(key, value, &proof).unwrap());
+        assert!(!tree.verify_proof(key, b"other_value", &proof).unwrap());
+        
+        // Absent key proves empty
+        let absent = tree.get_proof(b"absent_key").unwrap();
+        assert!(tree.verify_proof_against(&tree.root(), b"absent_key", None, &absent).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_range_proof() {
+        let mut tree = MerkleTree::<Bls12_381>::new(16);
+        let mut leaves = Vec::new();
+
+        for i in 0..6u8 {
+            let key = vec![i];
+            let value = vec![i, i];
+            tree.update(&key, &value).unwrap();
+            leaves.push((tree.key_path(&key), value));
+        }
+        leaves.sort();
+
+        let start = leaves[1].0.clone();
+        let end = leaves[4].0.clone();
+        let proof = tree.get_range_proof(&start, &end).unwrap();
+        let root = tree.root();
+
+        // Complete range verifies
+        assert!(tree.verify_range_proof(&root, &start, &end, &leaves[1..5], &proof).unwrap());
+
+        // Omitting a leaf inside the range is detected
+        let mut partial = leaves[1..5].to_vec();
+        partial.remove(1);
+        assert!(!tree.verify_range_proof(&root, &start, &end, &partial, &proof).unwrap());
+    }
+}
@@ -10,10 +10,30 @@ pub mod transaction;
 pub mod storage;
 pub mod transition;
 pub mod types;
+pub mod sync;
+pub mod overlay;
+pub mod logs;
+pub mod vm;
+pub mod code;
+pub mod storage_trie;
+pub mod payload;
+pub mod auth;
+pub mod parallel;
+pub mod computation;
+pub mod admission;
 
-pub use types::{State, StateRoot, StateUpdate, Account, AccountId};
-pub use storage::StateStorage;
+pub use types::{State, StateRoot, StateUpdate, StateProof, Account, AccountId};
+pub use storage::{StateStorage, PruningMode};
 pub use transition::StateTransition;
+pub use overlay::StateOverlay;
+pub use logs::{Log, LogFilter, LogsBloom};
+pub use sync::{StateSyncClient, StateSyncServer, SyncProgress, SyncTransport};
+pub use vm::{ContractVm, VmConfig};
+pub use storage_trie::StorageProof;
+pub use auth::{AuthPolicy, AuthorizationReport};
+pub use parallel::ParallelExecutor;
+pub use computation::{ComputationProof, Difficulty, DifficultyAdjuster};
+pub use admission::{AdmissionPolicy, CreditUsage};
 
 #[derive(Debug)]
 pub enum StateError {
@@ -23,6 +43,32 @@ pub enum StateError {
     ValidationError(String),
     AccountError(String),
     SerializationError(String),
+    ExecutionError(String),
+    PayloadError(String),
+    /// Contract call reverted after metered execution
+    Reverted {
+        reason: String,
+        computation_used: u64,
+    },
+}
+
+impl StateError {
+    /// Check if the error is an execution failure that still lets the
+    /// transaction be included in a block
+    pub fn is_execution_failure(&self) -> bool {
+        matches!(
+            self,
+            StateError::ExecutionError(_) | StateError::AccountError(_) | StateError::Reverted { .. }
+        )
+    }
+
+    /// Computation the VM metered before the failure
+    pub fn vm_computation_used(&self) -> u64 {
+        match self {
+            StateError::Reverted { computation_used, .. } => *computation_used,
+            _ => 0,
+        }
+    }
 }
 
 impl fmt::Display for StateError {
@@ -34,6 +80,9 @@ impl fmt::Display for StateError {
             StateError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             StateError::AccountError(msg) => write!(f, "Account error: {}", msg),
             StateError::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
+            StateError::ExecutionError(msg) => write!(f, "Execution error: {}", msg),
+            StateError::PayloadError(msg) => write!(f, "Payload error: {}", msg),
+            StateError::Reverted { reason, .. } => write!(f, "Execution error: Contract reverted: {}", reason),
         }
     }
 }
@@ -85,8 +134,8 @@ impl<E: PairingEngine> StateManager<E> {
             update.clone(),
         )?;
         
-        // Save new state
-        self.storage.write().await.save_state(&new_state)
+        // Save new state version
+        self.storage.write().await.save_state_version(&new_state)
             .map_err(|e| StateError::StorageError(e.to_string()))?;
         
         // Update current state
@@ -105,6 +154,59 @@ impl<E: PairingEngine> StateManager<E> {
         self.state.read().await.get_account(id)
     }
 
+    /// Get account state at block height
+    pub async fn get_account_at(
+        &self,
+        id: &AccountId,
+        height: u64,
+    ) -> Result<Option<Account<E>>, StateError> {
+        self.storage.read().await.get_account_at(id, height)
+    }
+
+    /// Get account proof against the state root at block height
+    pub async fn get_account_proof_at(
+        &self,
+        id: &AccountId,
+        height: u64,
+    ) -> Result<StateProof<E>, StateError> {
+        let state = self.storage.read().await.load_state_at(height)?;
+        state.get_account_proof(id)
+    }
+
+    /// Get proof of a contract storage slot against the current state root
+    pub async fn get_storage_proof(
+        &self,
+        id: &AccountId,
+        slot: &E::Fr,
+    ) -> Result<StorageProof<E>, StateError> {
+        self.state.read().await.get_storage_proof(id, slot)
+    }
+
+    /// Index logs emitted in a block
+    pub async fn index_logs(&self, height: u64, logs: Vec<Log<E>>) -> Result<LogsBloom, StateError> {
+        self.storage.write().await.index_logs(height, logs)
+    }
+
+    /// Find logs matching filter
+    pub async fn get_logs(&self, filter: &LogFilter<E>) -> Result<Vec<Log<E>>, StateError> {
+        self.storage.read().await.get_logs(filter)
+    }
+
+    /// Get contract code by code hash
+    pub async fn get_code(&self, code_hash: &E::Fr) -> Result<Option<Vec<u8>>, StateError> {
+        self.storage.read().await.get_code(code_hash)
+    }
+
+    /// Get code of the contract at account ID
+    pub async fn get_contract_code(&self, id: &AccountId) -> Result<Option<Vec<u8>>, StateError> {
+        self.storage.read().await.get_contract_code(id)
+    }
+
+    /// Mark block height as finalized
+    pub async fn finalize_height(&self, height: u64) -> Result<(), StateError> {
+        self.storage.write().await.set_finalized_height(height)
+    }
+
     /// Validate state update
     async fn validate_update(&self, update: &StateUpdate<E>) -> Result<(), StateError> {
         // Verify update signature
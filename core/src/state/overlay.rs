@@ -0,0 +1,200 @@
+use super::{State, Account, AccountId, StateError};
+use ark_ec::PairingEngine;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+/// Journal position to revert to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// Undo record for a single overlay write
+enum JournalEntry<E: PairingEngine> {
+    /// Account write with the previous overlay value
+    Account(AccountId, Option<Account<E>>),
+    /// Newly stored code
+    Code(E::Fr),
+}
+
+/// Copy-on-write view over a state for executing a block
+pub struct StateOverlay<'a, E: PairingEngine> {
+    /// Underlying state, never modified
+    base: &'a State<E>,
+
+    /// Accounts written through the overlay
+    dirty: HashMap<AccountId, Account<E>>,
+
+    /// Code stored through the overlay
+    dirty_code: HashMap<E::Fr, Vec<u8>>,
+
+    /// Undo records for every write, in write order
+    journal: Vec<JournalEntry<E>>,
+
+    /// Accounts read through the overlay, if tracked
+    reads: Option<RefCell<HashSet<AccountId>>>,
+}
+
+impl<'a, E: PairingEngine> StateOverlay<'a, E> {
+    /// Create new overlay over state
+    pub fn new(base: &'a State<E>) -> Self {
+        Self {
+            base,
+            dirty: HashMap::new(),
+            dirty_code: HashMap::new(),
+            journal: Vec::new(),
+            reads: None,
+        }
+    }
+
+    /// Create new overlay recording every account it reads
+    pub fn with_read_tracking(base: &'a State<E>) -> Self {
+        Self {
+            reads: Some(RefCell::new(HashSet::new())),
+            ..Self::new(base)
+        }
+    }
+
+    /// Get underlying state
+    pub fn base(&self) -> &State<E> {
+        self.base
+    }
+
+    /// Get account, preferring overlay writes
+    pub fn get_account(&self, id: &AccountId) -> Option<Account<E>> {
+        if let Some(reads) = &self.reads {
+            reads.borrow_mut().insert(id.clone());
+        }
+        self.dirty.get(id).cloned().or_else(|| self.base.get_account(id))
+    }
+
+    /// Write account into the overlay
+    pub fn set_account(&mut self, account: Account<E>) {
+        let previous = self.dirty.insert(account.id.clone(), account.clone());
+        self.journal.push(JournalEntry::Account(account.id, previous));
+    }
+
+    /// Write several accounts into the overlay
+    pub fn set_accounts(&mut self, accounts: HashMap<AccountId, Account<E>>) {
+        for account in accounts.into_values() {
+            self.set_account(account);
+        }
+    }
+
+    /// Get contract code, preferring overlay writes
+    pub fn get_code(&self, code_hash: &E::Fr) -> Option<&Vec<u8>> {
+        self.dirty_code.get(code_hash).or_else(|| self.base.get_code(code_hash))
+    }
+
+    /// Store contract code under its hash
+    pub fn set_code(&mut self, code_hash: E::Fr, code: Vec<u8>) {
+        if self.get_code(&code_hash).is_none() {
+            self.dirty_code.insert(code_hash, code);
+            self.journal.push(JournalEntry::Code(code_hash));
+        }
+    }
+
+    /// Mark current journal position
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.journal.len())
+    }
+
+    /// Undo all writes made after checkpoint
+    pub fn revert_to(&mut self, checkpoint: Checkpoint) -> Result<(), StateError> {
+        if checkpoint.0 > self.journal.len() {
+            return Err(StateError::TransitionError("Unknown checkpoint".to_string()));
+        }
+
+        while self.journal.len() > checkpoint.0 {
+            match self.journal.pop().unwrap() {
+                JournalEntry::Account(id, Some(account)) => {
+                    self.dirty.insert(id, account);
+                }
+                JournalEntry::Account(id, None) => {
+                    self.dirty.remove(&id);
+                }
+                JournalEntry::Code(code_hash) => {
+                    self.dirty_code.remove(&code_hash);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get accounts modified so far
+    pub fn modified_accounts(&self) -> &HashMap<AccountId, Account<E>> {
+        &self.dirty
+    }
+
+    /// Get accounts read or written so far, `None` unless reads are tracked
+    pub fn accessed_accounts(&self) -> Option<HashSet<AccountId>> {
+        let mut accessed = self.reads.as_ref()?.borrow().clone();
+        accessed.extend(self.dirty.keys().cloned());
+        Some(accessed)
+    }
+
+    /// Get code stored so far
+    pub fn new_code(&self) -> &HashMap<E::Fr, Vec<u8>> {
+        &self.dirty_code
+    }
+
+    /// Calculate state root as if the overlay were committed
+    pub fn calculate_root(&self) -> Result<E::Fr, StateError> {
+        self.base.calculate_root(&self.dirty)
+    }
+
+    /// Consume overlay, returning all modified accounts and new code
+    pub fn commit(self) -> (HashMap<AccountId, Account<E>>, HashMap<E::Fr, Vec<u8>>) {
+        (self.dirty, self.dirty_code)
+    }
+
+    /// Consume overlay, dropping all writes
+    pub fn discard(self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+
+    fn test_account(id: u8, balance: u64) -> Account<Bls12_381> {
+        let mut account = Account::new(
+            AccountId(vec![id]),
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        );
+        account.balance = balance;
+        account
+    }
+
+    #[test]
+    fn test_overlay_reads_through() {
+        let mut state = State::<Bls12_381>::new();
+        state.set_account(test_account(1, 100));
+
+        let mut overlay = StateOverlay::new(&state);
+        assert_eq!(overlay.get_account(&AccountId(vec![1])).unwrap().balance, 100);
+
+        overlay.set_account(test_account(1, 50));
+        assert_eq!(overlay.get_account(&AccountId(vec![1])).unwrap().balance, 50);
+        assert_eq!(state.get_account(&AccountId(vec![1])).unwrap().balance, 100);
+    }
+
+    #[test]
+    fn test_checkpoint_revert() {
+        let mut state = State::<Bls12_381>::new();
+        state.set_account(test_account(1, 100));
+
+        let mut overlay = StateOverlay::new(&state);
+        overlay.set_account(test_account(1, 90));
+
+        let checkpoint = overlay.checkpoint();
+        overlay.set_account(test_account(1, 80));
+        overlay.set_account(test_account(2, 10));
+        overlay.revert_to(checkpoint).unwrap();
+
+        assert_eq!(overlay.get_account(&AccountId(vec![1])).unwrap().balance, 90);
+        assert!(overlay.get_account(&AccountId(vec![2])).is_none());
+
+        let (modified, _) = overlay.commit();
+        assert_eq!(modified.len(), 1);
+    }
+}
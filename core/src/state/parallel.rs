@@ -0,0 +1,375 @@
+use super::{State, Account, AccountId, Transaction, TransactionType, StateError};
+use super::overlay::StateOverlay;
+use super::computation::Difficulty;
+use super::transaction::TransactionReceipt;
+use super::transition::{StateTransition, TransitionResult};
+use super::logs::{Log, LogsBloom};
+use ark_ec::PairingEngine;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+
+/// Effects of one transaction executed on its own overlay
+struct TxOutcome<E: PairingEngine> {
+    /// Receipt, not yet indexed
+    receipt: TransactionReceipt<E>,
+
+    /// Logs generated
+    logs: Vec<Log<E>>,
+
+    /// Accounts written
+    accounts: HashMap<AccountId, Account<E>>,
+
+    /// Contract code stored
+    code: HashMap<E::Fr, Vec<u8>>,
+
+    /// Accounts read or written
+    accessed: HashSet<AccountId>,
+}
+
+/// Block executor running transactions on disjoint accounts concurrently
+///
+/// Transactions are scheduled from the accounts they declare: each runs
+/// after every earlier transaction sharing an account with it, so the result
+/// equals sequential execution. Calls may reach any account and run alone.
+/// Each transaction records the accounts it actually touched; if any falls
+/// outside its declared set, or the block fails, the block is re-executed
+/// sequentially.
+pub struct ParallelExecutor<'a, E: PairingEngine> {
+    /// Transition rules
+    transition: &'a StateTransition<E>,
+
+    /// Worker threads per layer
+    threads: usize,
+}
+
+impl<'a, E: PairingEngine> ParallelExecutor<'a, E> {
+    /// Create executor using up to `threads` threads
+    pub fn new(transition: &'a StateTransition<E>, threads: usize) -> Self {
+        Self {
+            transition,
+            threads: threads.max(1),
+        }
+    }
+
+    /// Accounts a transaction touches, `None` if only known after execution
+    pub fn access_set(&self, transaction: &Transaction<E>) -> Result<Option<HashSet<AccountId>>, StateError> {
+        let mut accounts = HashSet::new();
+        accounts.insert(transaction.from.clone());
+
+        match transaction.tx_type {
+            TransactionType::Transfer
+            | TransactionType::CreateAccount
+            | TransactionType::UpdateAccount => {
+                accounts.extend(transaction.to.iter().cloned());
+            }
+            TransactionType::Deploy => {
+                accounts.insert(self.transition.generate_contract_id(transaction)?);
+            }
+            TransactionType::Call => return Ok(None),
+        }
+
+        Ok(Some(accounts))
+    }
+
+    /// Group transaction indices into layers of non-conflicting transactions
+    pub fn schedule(&self, transactions: &[Transaction<E>]) -> Result<Vec<Vec<usize>>, StateError> {
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        let mut last_layer: HashMap<AccountId, usize> = HashMap::new();
+        let mut floor = 0;
+
+        for (index, tx) in transactions.iter().enumerate() {
+            let layer = match self.access_set(tx)? {
+                Some(accounts) => {
+                    let layer = accounts.iter()
+                        .filter_map(|id| last_layer.get(id))
+                        .map(|layer| layer + 1)
+                        .max()
+                        .unwrap_or(0)
+                        .max(floor);
+                    for id in accounts {
+                        last_layer.insert(id, layer);
+                    }
+                    layer
+                }
+                None => {
+                    // Runs alone, after everything before it and before everything after it
+                    floor = layers.len() + 1;
+                    layers.len()
+                }
+            };
+
+            if layer == layers.len() {
+                layers.push(Vec::new());
+            }
+            layers[layer].push(index);
+        }
+
+        Ok(layers)
+    }
+
+    /// Apply block of transactions, with the same result as `StateTransition::apply_block`
+    pub fn apply_block(
+        &self,
+        state: &State<E>,
+        transactions: &[Transaction<E>],
+        block_number: u64,
+        difficulty: &Difficulty,
+    ) -> Result<TransitionResult<E>, StateError> {
+        let verified = self.transition.check_block(state, transactions, difficulty)?;
+        let access_sets = transactions.iter()
+            .map(|tx| self.access_set(tx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut overlay = StateOverlay::new(state);
+        let mut outcomes: Vec<Option<TxOutcome<E>>> = transactions.iter().map(|_| None).collect();
+
+        for layer in self.schedule(transactions)? {
+            let results = self.run_layer(
+                state,
+                overlay.modified_accounts(),
+                overlay.new_code(),
+                transactions,
+                &access_sets,
+                &verified,
+                &layer,
+                block_number,
+                difficulty,
+            );
+
+            // Commit in transaction order
+            for (index, result) in layer.into_iter().zip(results) {
+                let outcome = match result {
+                    Ok(outcome) => outcome,
+                    Err(_) => return self.transition.apply_block(state, transactions, block_number, difficulty),
+                };
+
+                let declared = access_sets[index].as_ref();
+                if declared.map_or(false, |declared| !outcome.accessed.is_subset(declared)) {
+                    // Touched an undeclared account, the schedule cannot be trusted
+                    return self.transition.apply_block(state, transactions, block_number, difficulty);
+                }
+
+                overlay.set_accounts(outcome.accounts.clone());
+                for (code_hash, code) in &outcome.code {
+                    overlay.set_code(*code_hash, code.clone());
+                }
+                outcomes[index] = Some(outcome);
+            }
+        }
+
+        // Assemble results in transaction order
+        let mut total_computation = 0u64;
+        let mut all_logs = Vec::new();
+        let mut receipts = Vec::with_capacity(transactions.len());
+        for (index, outcome) in outcomes.into_iter().enumerate() {
+            let outcome = outcome
+                .ok_or_else(|| StateError::TransitionError("Transaction was not scheduled".to_string()))?;
+            let mut receipt = outcome.receipt;
+            receipt.tx_index = index as u32;
+
+            total_computation += receipt.computation_used;
+            all_logs.extend(outcome.logs);
+            receipts.push(receipt);
+        }
+
+        let new_root = overlay.calculate_root()?;
+        let receipt_root = TransactionReceipt::root(&receipts)?;
+        let logs_bloom = LogsBloom::from_logs(&all_logs)?;
+        let (modified_accounts, new_code) = overlay.commit();
+
+        Ok(TransitionResult {
+            new_root,
+            modified_accounts,
+            computation_used: total_computation,
+            logs: all_logs,
+            receipts,
+            receipt_root,
+            logs_bloom,
+            new_code,
+        })
+    }
+
+    /// Execute one layer, returning outcomes in layer order
+    #[allow(clippy::too_many_arguments)]
+    fn run_layer(
+        &self,
+        state: &State<E>,
+        written: &HashMap<AccountId, Account<E>>,
+        new_code: &HashMap<E::Fr, Vec<u8>>,
+        transactions: &[Transaction<E>],
+        access_sets: &[Option<HashSet<AccountId>>],
+        verified: &[bool],
+        layer: &[usize],
+        block_number: u64,
+        difficulty: &Difficulty,
+    ) -> Vec<Result<TxOutcome<E>, StateError>> {
+        let execute = |index: usize| -> Result<TxOutcome<E>, StateError> {
+            let tx = &transactions[index];
+            let mut child = StateOverlay::with_read_tracking(state);
+
+            // Code deployed earlier in the block
+            for (code_hash, code) in new_code {
+                child.set_code(*code_hash, code.clone());
+            }
+
+            // Start from the block's current version of the declared accounts
+            if let Some(declared) = &access_sets[index] {
+                for id in declared {
+                    if let Some(account) = written.get(id) {
+                        child.set_account(account.clone());
+                    }
+                }
+            } else {
+                child.set_accounts(written.clone());
+            }
+
+            let (receipt, logs) = match self.transition.apply_checked(&mut child, tx, block_number, difficulty, verified[index]) {
+                Ok(result) => {
+                    let receipt = result.receipts.into_iter().next()
+                        .ok_or_else(|| StateError::TransitionError("Missing receipt".to_string()))?;
+                    (receipt, result.logs)
+                }
+                Err(e) if e.is_execution_failure() => {
+                    let receipt = self.transition.apply_failed_transaction(&mut child, tx, &e, index as u32, block_number)?;
+                    (receipt, Vec::new())
+                }
+                Err(e) => return Err(e),
+            };
+
+            let accessed = child.accessed_accounts().unwrap_or_default();
+            let (accounts, code) = child.commit();
+            Ok(TxOutcome { receipt, logs, accounts, code, accessed })
+        };
+
+        if layer.len() == 1 || self.threads == 1 {
+            return layer.iter().map(|&index| execute(index)).collect();
+        }
+
+        let execute = &execute;
+        let chunk_size = (layer.len() + self.threads - 1) / self.threads;
+        thread::scope(|scope| {
+            let handles: Vec<_> = layer.chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || {
+                    chunk.iter().map(|&index| execute(index)).collect::<Vec<_>>()
+                }))
+                .collect();
+
+            handles.into_iter()
+                .flat_map(|handle| handle.join().expect("Execution thread panicked"))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::signature::SignatureScheme;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::One;
+    use crate::state::vm::opcodes;
+
+    fn setup_state(accounts: u8) -> State<Bls12_381> {
+        let mut state = State::new();
+        for id in 0..accounts {
+            let mut account = Account::new(
+                AccountId(vec![id]),
+                Bls12_381::G1Projective::prime_subgroup_generator(),
+            );
+            account.balance = 1000;
+            state.accounts.insert(account.id.clone(), account);
+        }
+        state
+    }
+
+    fn signed(mut tx: Transaction<Bls12_381>) -> Transaction<Bls12_381> {
+        tx.sign(&SignatureScheme::new(128).unwrap(), &Fr::one()).unwrap();
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        tx
+    }
+
+    fn transfer(from: u8, to: u8, value: u64, nonce: u64) -> Transaction<Bls12_381> {
+        signed(Transaction::new(
+            TransactionType::Transfer,
+            AccountId(vec![from]),
+            Some(AccountId(vec![to])),
+            value,
+            nonce,
+            vec![],
+        ))
+    }
+
+    #[test]
+    fn test_schedule_layers() {
+        let transition = StateTransition::<Bls12_381>::new().unwrap();
+        let executor = ParallelExecutor::new(&transition, 4);
+
+        let transactions = vec![
+            transfer(0, 1, 10, 0),
+            transfer(2, 3, 10, 0),
+            transfer(1, 4, 10, 0), // Depends on the first
+            transfer(5, 6, 10, 0),
+        ];
+        assert_eq!(
+            executor.schedule(&transactions).unwrap(),
+            vec![vec![0, 1, 3], vec![2]]
+        );
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let transition = StateTransition::<Bls12_381>::new().unwrap();
+        let executor = ParallelExecutor::new(&transition, 4);
+        let state = setup_state(8);
+
+        let mut nonces = [0u64; 8];
+        let mut transactions = Vec::new();
+        for (from, to, value) in [(0, 1, 100), (2, 3, 200), (1, 2, 1050), (4, 5, 10), (3, 0, 5), (6, 7, 2000), (5, 6, 1)] {
+            transactions.push(transfer(from, to, value, nonces[from as usize]));
+            nonces[from as usize] += 1;
+        }
+
+        // Deploy a contract storing 1 in slot 0, then call it in the same block
+        let mut code = vec![opcodes::PUSH];
+        code.extend_from_slice(&1u64.to_be_bytes());
+        code.push(opcodes::PUSH);
+        code.extend_from_slice(&0u64.to_be_bytes());
+        code.extend_from_slice(&[opcodes::SSTORE, opcodes::STOP]);
+        let deploy = signed(Transaction::new(TransactionType::Deploy, AccountId(vec![7]), None, 0, nonces[7], code));
+        let contract_id = transition.generate_contract_id(&deploy).unwrap();
+        transactions.push(deploy);
+        transactions.push(signed(Transaction::new(
+            TransactionType::Call,
+            AccountId(vec![4]),
+            Some(contract_id.clone()),
+            0,
+            nonces[4],
+            vec![],
+        )));
+
+        let difficulty = Difficulty::default();
+        let sequential = transition.apply_block(&state, &transactions, 1, &difficulty).unwrap();
+        let parallel = executor.apply_block(&state, &transactions, 1, &difficulty).unwrap();
+
+        assert_eq!(parallel.new_root, sequential.new_root);
+        assert_eq!(parallel.receipt_root, sequential.receipt_root);
+        assert_eq!(parallel.computation_used, sequential.computation_used);
+        assert_eq!(parallel.modified_accounts.len(), sequential.modified_accounts.len());
+        for (id, account) in &sequential.modified_accounts {
+            assert_eq!(parallel.modified_accounts[id].balance, account.balance);
+            assert_eq!(parallel.modified_accounts[id].nonce, account.nonce);
+        }
+
+        // The overdrawn transfers failed in both
+        assert!(!parallel.receipts[2].is_success());
+        assert!(!parallel.receipts[5].is_success());
+
+        // The call found the code deployed before it
+        assert!(parallel.receipts[8].is_success());
+        assert_eq!(
+            parallel.modified_accounts[&contract_id].get_storage(&Fr::from(0u64)),
+            Some(Fr::one())
+        );
+    }
+}
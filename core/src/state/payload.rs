@@ -0,0 +1,583 @@
+use super::{AccountId, StateError};
+use super::auth::{AuthPolicy, WeightedSigner, MAX_POLICY_SIGNERS};
+use crate::crypto::signature::{Signature, SignatureScheme};
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::collections::BTreeMap;
+
+/// Current account payload format version
+pub const ACCOUNT_PAYLOAD_VERSION: u8 = 1;
+
+/// Payload kind for `CreateAccount`
+const KIND_CREATE: u8 = 0x01;
+
+/// Payload kind for `UpdateAccount`
+const KIND_UPDATE: u8 = 0x02;
+
+/// Domain separator for proofs of possession
+const POP_DOMAIN: &[u8] = b"aporia/account-pop/v1";
+
+/// Maximum number of metadata entries per account
+pub const MAX_METADATA_ENTRIES: usize = 16;
+
+/// Maximum metadata key length in bytes
+pub const MAX_METADATA_KEY_LEN: usize = 64;
+
+/// Maximum metadata value length in bytes
+pub const MAX_METADATA_VALUE_LEN: usize = 256;
+
+/// Public key with a signature proving knowledge of its secret key
+#[derive(Clone, Debug)]
+pub struct KeyRegistration<E: PairingEngine> {
+    /// Public key
+    pub public_key: E::G1Projective,
+
+    /// Signature over the account ID and public key by the new key
+    pub proof_of_possession: Signature<E>,
+}
+
+impl<E: PairingEngine> KeyRegistration<E> {
+    /// Register the key of `secret_key` for account
+    pub fn new(account_id: &AccountId, secret_key: &E::Fr) -> Result<Self, StateError> {
+        let public_key = E::G1Projective::prime_subgroup_generator().mul(secret_key.into_repr());
+        let scheme = SignatureScheme::new(128)
+            .map_err(|e| StateError::PayloadError(e.to_string()))?;
+        let proof_of_possession = scheme.sign(&Self::pop_message(account_id, &public_key)?, secret_key)
+            .map_err(|e| StateError::PayloadError(e.to_string()))?;
+
+        Ok(Self {
+            public_key,
+            proof_of_possession,
+        })
+    }
+
+    /// Verify proof of possession for account
+    pub fn verify(&self, account_id: &AccountId) -> Result<(), StateError> {
+        let scheme = SignatureScheme::new(128)
+            .map_err(|e| StateError::PayloadError(e.to_string()))?;
+        let valid = scheme.verify(
+            &Self::pop_message(account_id, &self.public_key)?,
+            &self.proof_of_possession,
+            &self.public_key,
+        ).map_err(|e| StateError::PayloadError(e.to_string()))?;
+
+        if !valid || self.public_key.is_zero() {
+            return Err(StateError::PayloadError("Invalid proof of possession".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Message signed by the proof of possession
+    fn pop_message(account_id: &AccountId, public_key: &E::G1Projective) -> Result<Vec<u8>, StateError> {
+        let mut message = POP_DOMAIN.to_vec();
+        account_id.0.serialize(&mut message)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        public_key.into_affine().serialize(&mut message)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        Ok(message)
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), StateError> {
+        self.public_key.into_affine().serialize(&mut *bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.proof_of_possession.r.into_affine().serialize(&mut *bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.proof_of_possession.s.serialize(&mut *bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn decode(reader: &mut &[u8]) -> Result<Self, StateError> {
+        let public_key = E::G1Affine::deserialize(&mut *reader)
+            .map_err(|e| StateError::PayloadError(format!("Invalid public key: {}", e)))?;
+        let r = E::G1Affine::deserialize(&mut *reader)
+            .map_err(|e| StateError::PayloadError(format!("Invalid proof of possession: {}", e)))?;
+        let s = E::Fr::deserialize(&mut *reader)
+            .map_err(|e| StateError::PayloadError(format!("Invalid proof of possession: {}", e)))?;
+
+        Ok(Self {
+            public_key: public_key.into_projective(),
+            proof_of_possession: Signature { r: r.into_projective(), s },
+        })
+    }
+}
+
+/// `CreateAccount` transaction payload
+#[derive(Clone, Debug)]
+pub struct CreateAccountPayload<E: PairingEngine> {
+    /// Key of the new account
+    pub key: KeyRegistration<E>,
+
+    /// Initial metadata
+    pub metadata: BTreeMap<String, Vec<u8>>,
+}
+
+impl<E: PairingEngine> CreateAccountPayload<E> {
+    /// Create payload for account owned by `secret_key`
+    pub fn new(account_id: &AccountId, secret_key: &E::Fr) -> Result<Self, StateError> {
+        Ok(Self {
+            key: KeyRegistration::new(account_id, secret_key)?,
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    /// Add metadata entry
+    pub fn with_metadata(mut self, key: &str, value: Vec<u8>) -> Self {
+        self.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    /// Encode payload
+    pub fn encode(&self) -> Result<Vec<u8>, StateError> {
+        let mut bytes = vec![ACCOUNT_PAYLOAD_VERSION, KIND_CREATE];
+        self.key.encode(&mut bytes)?;
+        encode_entries(&mut bytes, self.metadata.iter().map(|(k, v)| (k, Some(v))))?;
+        Ok(bytes)
+    }
+
+    /// Decode payload, rejecting unknown versions and trailing bytes
+    pub fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+        let mut reader = decode_header(bytes, KIND_CREATE)?;
+        let key = KeyRegistration::decode(&mut reader)?;
+
+        let mut metadata = BTreeMap::new();
+        for (k, v) in decode_entries(&mut reader)? {
+            let v = v.ok_or_else(|| StateError::PayloadError("Cannot remove metadata on create".to_string()))?;
+            metadata.insert(k, v);
+        }
+
+        finish(reader)?;
+        Ok(Self { key, metadata })
+    }
+
+    /// Decode and verify payload for account
+    pub fn decode_for(account_id: &AccountId, bytes: &[u8]) -> Result<Self, StateError> {
+        let payload = Self::decode(bytes)?;
+        payload.key.verify(account_id)?;
+        Ok(payload)
+    }
+}
+
+/// Authorization policy change with a proof of possession for every policy key
+#[derive(Clone, Debug)]
+pub struct PolicyUpdate<E: PairingEngine> {
+    /// New policy
+    pub policy: AuthPolicy<E>,
+
+    /// Proofs of possession, in `policy.keys()` order
+    pub proofs_of_possession: Vec<Signature<E>>,
+}
+
+impl<E: PairingEngine> PolicyUpdate<E> {
+    /// Return to single key authorization
+    pub fn single() -> Self {
+        Self {
+            policy: AuthPolicy::Single,
+            proofs_of_possession: Vec::new(),
+        }
+    }
+
+    /// Weighted M-of-N policy from registered signer keys
+    pub fn weighted(signers: Vec<(KeyRegistration<E>, u32)>, threshold: u64) -> Result<Self, StateError> {
+        let (signers, proofs_of_possession) = signers.into_iter()
+            .map(|(key, weight)| (
+                WeightedSigner { public_key: key.public_key, weight },
+                key.proof_of_possession,
+            ))
+            .unzip();
+        let policy = AuthPolicy::Weighted { signers, threshold };
+        policy.check()?;
+        Ok(Self { policy, proofs_of_possession })
+    }
+
+    /// Aggregated key policy from registered member keys
+    pub fn aggregated(members: Vec<KeyRegistration<E>>) -> Result<Self, StateError> {
+        let (members, proofs_of_possession) = members.into_iter()
+            .map(|key| (key.public_key, key.proof_of_possession))
+            .unzip();
+        let policy = AuthPolicy::Aggregated { members };
+        policy.check()?;
+        Ok(Self { policy, proofs_of_possession })
+    }
+
+    /// Verify policy and proofs of possession for account
+    pub fn verify(&self, account_id: &AccountId) -> Result<(), StateError> {
+        self.policy.check()?;
+
+        let keys = self.policy.keys();
+        if keys.len() != self.proofs_of_possession.len() {
+            return Err(StateError::PayloadError("Missing proof of possession".to_string()));
+        }
+        for (public_key, proof_of_possession) in keys.into_iter().zip(&self.proofs_of_possession) {
+            KeyRegistration {
+                public_key,
+                proof_of_possession: proof_of_possession.clone(),
+            }.verify(account_id)?;
+        }
+
+        Ok(())
+    }
+
+    fn registrations(&self) -> impl Iterator<Item = KeyRegistration<E>> + '_ {
+        self.policy.keys().into_iter()
+            .zip(&self.proofs_of_possession)
+            .map(|(public_key, proof_of_possession)| KeyRegistration {
+                public_key,
+                proof_of_possession: proof_of_possession.clone(),
+            })
+    }
+
+    /// Encode as `tag, [threshold], count, (key, [weight])*`
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), StateError> {
+        let keys = self.policy.keys();
+        if keys.len() != self.proofs_of_possession.len() {
+            return Err(StateError::PayloadError("Missing proof of possession".to_string()));
+        }
+
+        match &self.policy {
+            AuthPolicy::Single => bytes.push(0),
+            AuthPolicy::Weighted { signers, threshold } => {
+                bytes.push(1);
+                bytes.extend_from_slice(&threshold.to_be_bytes());
+                bytes.push(signers.len() as u8);
+                for (signer, key) in signers.iter().zip(self.registrations()) {
+                    key.encode(bytes)?;
+                    bytes.extend_from_slice(&signer.weight.to_be_bytes());
+                }
+            }
+            AuthPolicy::Aggregated { members } => {
+                bytes.push(2);
+                bytes.push(members.len() as u8);
+                for key in self.registrations() {
+                    key.encode(bytes)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn decode(reader: &mut &[u8]) -> Result<Self, StateError> {
+        let tag = read_u8(reader)?;
+        if tag == 0 {
+            return Ok(Self::single());
+        }
+
+        let threshold = if tag == 1 {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(read_bytes(reader, 8)?);
+            Some(u64::from_be_bytes(buf))
+        } else {
+            None
+        };
+        let count = read_u8(reader)? as usize;
+        if count > MAX_POLICY_SIGNERS {
+            return Err(StateError::PayloadError("Too many policy keys".to_string()));
+        }
+
+        let mut keys = Vec::with_capacity(count);
+        for _ in 0..count {
+            let key = KeyRegistration::decode(reader)?;
+            let weight = match threshold {
+                Some(_) => {
+                    let mut buf = [0u8; 4];
+                    buf.copy_from_slice(read_bytes(reader, 4)?);
+                    u32::from_be_bytes(buf)
+                }
+                None => 0,
+            };
+            keys.push((key, weight));
+        }
+
+        match (tag, threshold) {
+            (1, Some(threshold)) => Self::weighted(keys, threshold),
+            (2, None) => Self::aggregated(keys.into_iter().map(|(key, _)| key).collect()),
+            _ => Err(StateError::PayloadError(format!("Unknown policy tag {}", tag))),
+        }
+    }
+}
+
+/// `UpdateAccount` transaction payload
+#[derive(Clone, Debug)]
+pub struct UpdateAccountPayload<E: PairingEngine> {
+    /// Replacement key, if rotating
+    pub new_key: Option<KeyRegistration<E>>,
+
+    /// Metadata changes, `None` removes the entry
+    pub metadata: BTreeMap<String, Option<Vec<u8>>>,
+
+    /// Replacement authorization policy
+    pub policy: Option<PolicyUpdate<E>>,
+}
+
+impl<E: PairingEngine> UpdateAccountPayload<E> {
+    /// Create empty update
+    pub fn new() -> Self {
+        Self {
+            new_key: None,
+            metadata: BTreeMap::new(),
+            policy: None,
+        }
+    }
+
+    /// Rotate account key to the key of `secret_key`
+    pub fn rotate_key(mut self, account_id: &AccountId, secret_key: &E::Fr) -> Result<Self, StateError> {
+        self.new_key = Some(KeyRegistration::new(account_id, secret_key)?);
+        Ok(self)
+    }
+
+    /// Set metadata entry
+    pub fn set_metadata(mut self, key: &str, value: Vec<u8>) -> Self {
+        self.metadata.insert(key.to_string(), Some(value));
+        self
+    }
+
+    /// Remove metadata entry
+    pub fn remove_metadata(mut self, key: &str) -> Self {
+        self.metadata.insert(key.to_string(), None);
+        self
+    }
+
+    /// Replace authorization policy
+    pub fn set_policy(mut self, policy: PolicyUpdate<E>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Encode payload
+    pub fn encode(&self) -> Result<Vec<u8>, StateError> {
+        let mut bytes = vec![ACCOUNT_PAYLOAD_VERSION, KIND_UPDATE];
+        match &self.new_key {
+            Some(key) => {
+                bytes.push(1);
+                key.encode(&mut bytes)?;
+            }
+            None => bytes.push(0),
+        }
+        encode_entries(&mut bytes, self.metadata.iter().map(|(k, v)| (k, v.as_ref())))?;
+        match &self.policy {
+            Some(policy) => {
+                bytes.push(1);
+                policy.encode(&mut bytes)?;
+            }
+            None => bytes.push(0),
+        }
+        Ok(bytes)
+    }
+
+    /// Decode payload, rejecting unknown versions and trailing bytes
+    pub fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+        let mut reader = decode_header(bytes, KIND_UPDATE)?;
+
+        let new_key = match read_u8(&mut reader)? {
+            0 => None,
+            1 => Some(KeyRegistration::decode(&mut reader)?),
+            flag => return Err(StateError::PayloadError(format!("Invalid key rotation flag {}", flag))),
+        };
+        let metadata = decode_entries(&mut reader)?.into_iter().collect();
+        let policy = match read_u8(&mut reader)? {
+            0 => None,
+            1 => Some(PolicyUpdate::decode(&mut reader)?),
+            flag => return Err(StateError::PayloadError(format!("Invalid policy flag {}", flag))),
+        };
+
+        finish(reader)?;
+        Ok(Self { new_key, metadata, policy })
+    }
+
+    /// Decode and verify payload for account
+    pub fn decode_for(account_id: &AccountId, bytes: &[u8]) -> Result<Self, StateError> {
+        let payload = Self::decode(bytes)?;
+        if let Some(key) = &payload.new_key {
+            key.verify(account_id)?;
+        }
+        if let Some(policy) = &payload.policy {
+            policy.verify(account_id)?;
+        }
+        Ok(payload)
+    }
+}
+
+impl<E: PairingEngine> Default for UpdateAccountPayload<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check metadata against size limits
+pub fn check_metadata(metadata: &BTreeMap<String, Vec<u8>>) -> Result<(), StateError> {
+    if metadata.len() > MAX_METADATA_ENTRIES {
+        return Err(StateError::PayloadError(format!(
+            "Account metadata has {} entries, limit is {}", metadata.len(), MAX_METADATA_ENTRIES
+        )));
+    }
+    Ok(())
+}
+
+/// Check payload version and kind, returning the remaining bytes
+fn decode_header(bytes: &[u8], kind: u8) -> Result<&[u8], StateError> {
+    match bytes {
+        [] | [_] => Err(StateError::PayloadError("Payload is empty".to_string())),
+        [version, ..] if *version != ACCOUNT_PAYLOAD_VERSION => Err(StateError::PayloadError(
+            format!("Unsupported payload version {}", version)
+        )),
+        [_, found, ..] if *found != kind => Err(StateError::PayloadError(
+            format!("Unexpected payload kind {}", found)
+        )),
+        [_, _, rest @ ..] => Ok(rest),
+    }
+}
+
+/// Reject trailing bytes
+fn finish(reader: &[u8]) -> Result<(), StateError> {
+    if !reader.is_empty() {
+        return Err(StateError::PayloadError(format!("{} trailing bytes in payload", reader.len())));
+    }
+    Ok(())
+}
+
+fn read_u8(reader: &mut &[u8]) -> Result<u8, StateError> {
+    let (byte, rest) = reader.split_first()
+        .ok_or_else(|| StateError::PayloadError("Payload is truncated".to_string()))?;
+    *reader = rest;
+    Ok(*byte)
+}
+
+fn read_bytes<'a>(reader: &mut &'a [u8], len: usize) -> Result<&'a [u8], StateError> {
+    if reader.len() < len {
+        return Err(StateError::PayloadError("Payload is truncated".to_string()));
+    }
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    Ok(bytes)
+}
+
+/// Encode metadata entries as `count, (key_len, key, flag, [value_len, value])*`
+fn encode_entries<'a>(
+    bytes: &mut Vec<u8>,
+    entries: impl ExactSizeIterator<Item = (&'a String, Option<&'a Vec<u8>>)>,
+) -> Result<(), StateError> {
+    if entries.len() > MAX_METADATA_ENTRIES {
+        return Err(StateError::PayloadError("Too many metadata entries".to_string()));
+    }
+    bytes.push(entries.len() as u8);
+
+    for (key, value) in entries {
+        if key.is_empty() || key.len() > MAX_METADATA_KEY_LEN {
+            return Err(StateError::PayloadError(format!("Invalid metadata key length {}", key.len())));
+        }
+        bytes.push(key.len() as u8);
+        bytes.extend_from_slice(key.as_bytes());
+
+        match value {
+            Some(value) => {
+                if value.len() > MAX_METADATA_VALUE_LEN {
+                    return Err(StateError::PayloadError(format!(
+                        "Metadata value for {} exceeds {} bytes", key, MAX_METADATA_VALUE_LEN
+                    )));
+                }
+                bytes.push(1);
+                bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                bytes.extend_from_slice(value);
+            }
+            None => bytes.push(0),
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode metadata entries; keys must be strictly ascending
+fn decode_entries(reader: &mut &[u8]) -> Result<Vec<(String, Option<Vec<u8>>)>, StateError> {
+    let count = read_u8(reader)? as usize;
+    if count > MAX_METADATA_ENTRIES {
+        return Err(StateError::PayloadError("Too many metadata entries".to_string()));
+    }
+
+    let mut entries: Vec<(String, Option<Vec<u8>>)> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let key_len = read_u8(reader)? as usize;
+        if key_len == 0 || key_len > MAX_METADATA_KEY_LEN {
+            return Err(StateError::PayloadError(format!("Invalid metadata key length {}", key_len)));
+        }
+        let key = String::from_utf8(read_bytes(reader, key_len)?.to_vec())
+            .map_err(|_| StateError::PayloadError("Metadata key is not UTF-8".to_string()))?;
+        if entries.last().map_or(false, |(previous, _)| *previous >= key) {
+            return Err(StateError::PayloadError("Metadata keys are not in canonical order".to_string()));
+        }
+
+        let value = match read_u8(reader)? {
+            0 => None,
+            1 => {
+                let len = u16::from_be_bytes(read_bytes(reader, 2)?.try_into().unwrap()) as usize;
+                if len > MAX_METADATA_VALUE_LEN {
+                    return Err(StateError::PayloadError(format!(
+                        "Metadata value for {} exceeds {} bytes", key, MAX_METADATA_VALUE_LEN
+                    )));
+                }
+                Some(read_bytes(reader, len)?.to_vec())
+            }
+            flag => return Err(StateError::PayloadError(format!("Invalid metadata flag {}", flag))),
+        };
+
+        entries.push((key, value));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_create_payload_roundtrip() {
+        let id = AccountId(vec![4]);
+        let secret = Fr::rand(&mut thread_rng());
+        let payload = CreateAccountPayload::<Bls12_381>::new(&id, &secret)
+            .unwrap()
+            .with_metadata("name", b"alice".to_vec());
+
+        let decoded = CreateAccountPayload::<Bls12_381>::decode_for(&id, &payload.encode().unwrap()).unwrap();
+        assert_eq!(decoded.key.public_key, payload.key.public_key);
+        assert_eq!(decoded.metadata, payload.metadata);
+
+        // Proof of possession is bound to the account
+        assert!(matches!(
+            CreateAccountPayload::<Bls12_381>::decode_for(&AccountId(vec![5]), &payload.encode().unwrap()),
+            Err(StateError::PayloadError(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_payloads() {
+        let id = AccountId(vec![4]);
+        let bytes = UpdateAccountPayload::<Bls12_381>::new()
+            .rotate_key(&id, &Fr::from(7u64))
+            .unwrap()
+            .remove_metadata("name")
+            .encode()
+            .unwrap();
+        assert!(UpdateAccountPayload::<Bls12_381>::decode_for(&id, &bytes).is_ok());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 2;
+
+        for bad in [&[][..], &bytes[..bytes.len() - 1], &trailing, &wrong_version] {
+            assert!(matches!(
+                UpdateAccountPayload::<Bls12_381>::decode(bad),
+                Err(StateError::PayloadError(_))
+            ));
+        }
+
+        // Update payload is not a create payload
+        assert!(CreateAccountPayload::<Bls12_381>::decode(&bytes).is_err());
+    }
+}
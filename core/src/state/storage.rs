@@ -1,6 +1,10 @@
 use super::{State, Account, AccountId, StateError};
+use super::logs::{Log, LogFilter, LogIndex, LogsBloom, BLOOM_SIZE};
+use super::code::{self, CodeStore};
 use ark_ec::PairingEngine;
-use std::collections::HashMap;
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -28,6 +32,64 @@ pub trait StateStorage<E: PairingEngine>: Send + Sync {
     
     /// Clear all storage
     fn clear(&mut self) -> Result<(), StateError>;
+
+    /// Save state as the version at its block height
+    fn save_state_version(&mut self, state: &State<E>) -> Result<(), StateError>;
+
+    /// Load state as of block height
+    fn load_state_at(&self, height: u64) -> Result<State<E>, StateError>;
+
+    /// Get account as of block height
+    fn get_account_at(&self, id: &AccountId, height: u64) -> Result<Option<Account<E>>, StateError>;
+
+    /// Mark block height as finalized
+    fn set_finalized_height(&mut self, height: u64) -> Result<(), StateError>;
+
+    /// Drop versions no longer retained by the pruning mode
+    fn prune(&mut self) -> Result<(), StateError>;
+
+    /// Index logs emitted at block height
+    fn index_logs(&mut self, height: u64, logs: Vec<Log<E>>) -> Result<LogsBloom, StateError>;
+
+    /// Find logs matching filter
+    fn get_logs(&self, filter: &LogFilter<E>) -> Result<Vec<Log<E>>, StateError>;
+
+    /// Store contract code, returning its code hash
+    fn save_code(&mut self, code: &[u8]) -> Result<E::Fr, StateError>;
+
+    /// Get contract code by code hash
+    fn get_code(&self, code_hash: &E::Fr) -> Result<Option<Vec<u8>>, StateError>;
+
+    /// Get code of the contract at account ID
+    fn get_contract_code(&self, id: &AccountId) -> Result<Option<Vec<u8>>, StateError>;
+}
+
+/// Pruning policy for historical state versions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PruningMode {
+    /// Keep every version
+    Archive,
+    /// Keep versions for the last N heights
+    KeepLast(u64),
+    /// Keep versions from the last finalized height onwards
+    KeepFinalized,
+}
+
+impl PruningMode {
+    /// First height retained given the latest and finalized heights
+    pub fn retain_from(&self, latest_height: u64, finalized_height: u64) -> u64 {
+        match self {
+            PruningMode::Archive => 0,
+            PruningMode::KeepLast(n) => (latest_height + 1).saturating_sub((*n).max(1)),
+            PruningMode::KeepFinalized => finalized_height.min(latest_height),
+        }
+    }
+}
+
+impl Default for PruningMode {
+    fn default() -> Self {
+        PruningMode::Archive
+    }
 }
 
 /// In-memory storage implementation
@@ -37,27 +99,72 @@ pub struct MemoryStorage<E: PairingEngine> {
     
     /// State root
     root: E::Fr,
+
+    /// Account versions by block height (`None` marks deletion)
+    history: HashMap<AccountId, BTreeMap<u64, Option<Account<E>>>>,
+
+    /// State roots by block height
+    roots: BTreeMap<u64, E::Fr>,
+
+    /// Pruning policy
+    pruning: PruningMode,
+
+    /// Last finalized height
+    finalized_height: u64,
+
+    /// Heights below this have been pruned
+    pruned_below: u64,
+
+    /// Log index
+    logs: LogIndex<E>,
+
+    /// Contract code
+    code: CodeStore<E>,
 }
 
 impl<E: PairingEngine> MemoryStorage<E> {
     /// Create new memory storage
     pub fn new() -> Self {
+        Self::with_pruning(PruningMode::Archive)
+    }
+
+    /// Create new memory storage with pruning policy
+    pub fn with_pruning(pruning: PruningMode) -> Self {
         Self {
             accounts: HashMap::new(),
             root: E::Fr::zero(),
+            history: HashMap::new(),
+            roots: BTreeMap::new(),
+            pruning,
+            finalized_height: 0,
+            pruned_below: 0,
+            logs: LogIndex::new(),
+            code: CodeStore::new(),
+        }
+    }
+
+    /// Check that a height is still available
+    fn check_height(&self, height: u64) -> Result<(), StateError> {
+        match self.roots.keys().next_back() {
+            Some(latest) if height >= self.pruned_below && height <= *latest => Ok(()),
+            _ => Err(StateError::StorageError(format!("State at height {} is not available", height))),
         }
     }
 }
 
 impl<E: PairingEngine> StateStorage<E> for MemoryStorage<E> {
     fn load_state(&self) -> Result<State<E>, StateError> {
-        Ok(State {
-            accounts: self.accounts.clone(),
-            root: self.root,
-        })
+        let mut state = State::new();
+        state.accounts = self.accounts.clone();
+        state.code = self.code.to_map();
+        state.root = self.root;
+        Ok(state)
     }
 
     fn save_state(&mut self, state: &State<E>) -> Result<(), StateError> {
+        for (hash, code) in &state.code {
+            self.code.insert_with_hash(*hash, code)?;
+        }
         self.accounts = state.accounts.clone();
         self.root = state.root;
         Ok(())
@@ -84,8 +191,144 @@ impl<E: PairingEngine> StateStorage<E> for MemoryStorage<E> {
     fn clear(&mut self) -> Result<(), StateError> {
         self.accounts.clear();
         self.root = E::Fr::zero();
+        self.history.clear();
+        self.roots.clear();
+        self.finalized_height = 0;
+        self.pruned_below = 0;
+        self.logs.clear();
+        self.code.clear();
         Ok(())
     }
+
+    fn save_state_version(&mut self, state: &State<E>) -> Result<(), StateError> {
+        let height = state.block_height;
+        if let Some(latest) = self.roots.keys().next_back() {
+            if height < *latest {
+                return Err(StateError::StorageError(format!(
+                    "Cannot save height {} below latest height {}", height, latest
+                )));
+            }
+        }
+
+        // Record changed and new accounts
+        for (id, account) in &state.accounts {
+            let changed = match self.accounts.get(id) {
+                Some(previous) => previous.serialize()? != account.serialize()?,
+                None => true,
+            };
+            if changed {
+                self.history.entry(id.clone()).or_default().insert(height, Some(account.clone()));
+            }
+        }
+
+        // Record deleted accounts
+        for id in self.accounts.keys() {
+            if !state.accounts.contains_key(id) {
+                self.history.entry(id.clone()).or_default().insert(height, None);
+            }
+        }
+
+        self.roots.insert(height, state.root);
+        self.save_state(state)?;
+        self.prune()
+    }
+
+    fn load_state_at(&self, height: u64) -> Result<State<E>, StateError> {
+        self.check_height(height)?;
+
+        let mut state = State::new();
+        state.code = self.code.to_map();
+        for (id, versions) in &self.history {
+            if let Some((_, Some(account))) = versions.range(..=height).next_back() {
+                state.accounts.insert(id.clone(), account.clone());
+            }
+        }
+
+        state.root = self.roots.range(..=height).next_back()
+            .map(|(_, root)| *root)
+            .unwrap_or_else(E::Fr::zero);
+        state.block_height = height;
+
+        Ok(state)
+    }
+
+    fn get_account_at(&self, id: &AccountId, height: u64) -> Result<Option<Account<E>>, StateError> {
+        self.check_height(height)?;
+
+        Ok(self.history.get(id)
+            .and_then(|versions| versions.range(..=height).next_back())
+            .and_then(|(_, account)| account.clone()))
+    }
+
+    fn set_finalized_height(&mut self, height: u64) -> Result<(), StateError> {
+        if height < self.finalized_height {
+            return Err(StateError::StorageError("Finalized height cannot decrease".to_string()));
+        }
+
+        self.finalized_height = height;
+        self.prune()
+    }
+
+    fn prune(&mut self) -> Result<(), StateError> {
+        let latest = match self.roots.keys().next_back() {
+            Some(latest) => *latest,
+            None => return Ok(()),
+        };
+
+        let cutoff = self.pruning.retain_from(latest, self.finalized_height);
+        if cutoff <= self.pruned_below {
+            return Ok(());
+        }
+
+        // Keep the newest version at or below the cutoff, it is still live there
+        for versions in self.history.values_mut() {
+            let mut retained = versions.split_off(&cutoff);
+            if !retained.contains_key(&cutoff) {
+                if let Some((_, account)) = versions.iter().next_back() {
+                    retained.insert(cutoff, account.clone());
+                }
+            }
+            *versions = retained;
+        }
+        self.history.retain(|_, versions| {
+            !(versions.len() == 1 && versions.values().all(|account| account.is_none()))
+        });
+
+        let mut retained_roots = self.roots.split_off(&cutoff);
+        if !retained_roots.contains_key(&cutoff) {
+            if let Some((_, root)) = self.roots.iter().next_back() {
+                retained_roots.insert(cutoff, *root);
+            }
+        }
+        self.roots = retained_roots;
+        self.pruned_below = cutoff;
+
+        Ok(())
+    }
+
+    fn index_logs(&mut self, height: u64, logs: Vec<Log<E>>) -> Result<LogsBloom, StateError> {
+        self.logs.insert(height, logs)
+    }
+
+    fn get_logs(&self, filter: &LogFilter<E>) -> Result<Vec<Log<E>>, StateError> {
+        self.logs.query(filter)
+    }
+
+    fn save_code(&mut self, code: &[u8]) -> Result<E::Fr, StateError> {
+        code::check_code_size(code)?;
+        self.code.insert(code)
+    }
+
+    fn get_code(&self, code_hash: &E::Fr) -> Result<Option<Vec<u8>>, StateError> {
+        Ok(self.code.get(code_hash).cloned())
+    }
+
+    fn get_contract_code(&self, id: &AccountId) -> Result<Option<Vec<u8>>, StateError> {
+        match self.accounts.get(id).and_then(|account| account.code_hash) {
+            Some(hash) => self.get_code(&hash),
+            None => Ok(None),
+        }
+    }
 }
 
 /// Persistent storage implementation using RocksDB
@@ -96,6 +339,9 @@ pub struct PersistentStorage<E: PairingEngine> {
     /// Database path
     path: PathBuf,
     
+    /// Pruning policy
+    pruning: PruningMode,
+    
     /// Phantom data for generic type
     _phantom: std::marker::PhantomData<E>,
 }
@@ -103,6 +349,11 @@ pub struct PersistentStorage<E: PairingEngine> {
 impl<E: PairingEngine> PersistentStorage<E> {
     /// Create new persistent storage
     pub fn new(path: PathBuf) -> Result<Self, StateError> {
+        Self::with_pruning(path, PruningMode::Archive)
+    }
+
+    /// Create new persistent storage with pruning policy
+    pub fn with_pruning(path: PathBuf, pruning: PruningMode) -> Result<Self, StateError> {
         let opts = rocksdb::Options::default();
         let db = rocksdb::DB::open(&opts, &path)
             .map_err(|e| StateError::StorageError(format!("Failed to open database: {}", e)))?;
@@ -110,6 +361,7 @@ impl<E: PairingEngine> PersistentStorage<E> {
         Ok(Self {
             db: Arc::new(RwLock::new(db)),
             path,
+            pruning,
             _phantom: std::marker::PhantomData,
         })
     }
@@ -126,6 +378,322 @@ impl<E: PairingEngine> PersistentStorage<E> {
     fn root_key() -> Vec<u8> {
         vec![0x00] // Key for state root
     }
+
+    /// Get serialized key for state root at height
+    fn version_root_key(height: u64) -> Vec<u8> {
+        let mut key = Vec::with_capacity(9);
+        key.push(0x02); // Prefix for versioned roots
+        key.extend_from_slice(&height.to_be_bytes());
+        key
+    }
+
+    /// Get serialized key prefix for account versions
+    fn account_version_prefix(id: &AccountId) -> Vec<u8> {
+        let mut key = Vec::with_capacity(5 + id.0.len());
+        key.push(0x03); // Prefix for versioned accounts
+        key.extend_from_slice(&(id.0.len() as u32).to_be_bytes());
+        key.extend_from_slice(&id.0);
+        key
+    }
+
+    /// Get serialized key for account version at height
+    fn account_version_key(id: &AccountId, height: u64) -> Vec<u8> {
+        let mut key = Self::account_version_prefix(id);
+        key.extend_from_slice(&height.to_be_bytes());
+        key
+    }
+
+    /// Get serialized key for versioning metadata
+    fn version_meta_key(name: u8) -> Vec<u8> {
+        vec![0x04, name] // 0 = finalized height, 1 = pruned below
+    }
+
+    /// Split versioned account key into account ID and height
+    fn parse_account_version_key(key: &[u8]) -> Option<(AccountId, u64)> {
+        if key.len() < 13 || key[0] != 0x03 {
+            return None;
+        }
+        let id_len = u32::from_be_bytes(key[1..5].try_into().ok()?) as usize;
+        if key.len() != 5 + id_len + 8 {
+            return None;
+        }
+        let height = u64::from_be_bytes(key[5 + id_len..].try_into().ok()?);
+        Some((AccountId(key[5..5 + id_len].to_vec()), height))
+    }
+
+    /// Read a u64 metadata value
+    fn read_meta(db: &rocksdb::DB, name: u8) -> Result<u64, StateError> {
+        let bytes = db.get(Self::version_meta_key(name))
+            .map_err(|e| StateError::StorageError(format!("Failed to read metadata: {}", e)))?;
+        Ok(bytes
+            .and_then(|b| b.as_slice().try_into().ok())
+            .map(u64::from_be_bytes)
+            .unwrap_or(0))
+    }
+
+    /// Latest versioned height, if any
+    fn latest_version_height(db: &rocksdb::DB) -> Result<Option<u64>, StateError> {
+        let iter = db.iterator(rocksdb::IteratorMode::From(&[0x03], rocksdb::Direction::Reverse));
+        for item in iter {
+            let (key, _) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read root: {}", e)))?;
+            if key[0] == 0x02 {
+                return Ok(Some(u64::from_be_bytes(key[1..9].try_into().unwrap())));
+            }
+            if key[0] < 0x02 {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Check that a height is still available
+    fn check_height(db: &rocksdb::DB, height: u64) -> Result<(), StateError> {
+        let pruned_below = Self::read_meta(db, 1)?;
+        match Self::latest_version_height(db)? {
+            Some(latest) if height >= pruned_below && height <= latest => Ok(()),
+            _ => Err(StateError::StorageError(format!("State at height {} is not available", height))),
+        }
+    }
+
+    /// Get serialized key for block logs bloom
+    fn bloom_key(height: u64) -> Vec<u8> {
+        let mut key = Vec::with_capacity(9);
+        key.push(0x05); // Prefix for logs blooms
+        key.extend_from_slice(&height.to_be_bytes());
+        key
+    }
+
+    /// Get serialized key prefix for block logs
+    fn block_logs_prefix(height: u64) -> Vec<u8> {
+        let mut key = Vec::with_capacity(13);
+        key.push(0x06); // Prefix for logs
+        key.extend_from_slice(&height.to_be_bytes());
+        key
+    }
+
+    /// Get serialized key for contract code
+    fn code_key(code_hash: &E::Fr) -> Result<Vec<u8>, StateError> {
+        let mut key = vec![0x07]; // Prefix for contract code
+        code_hash.serialize(&mut key)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        Ok(key)
+    }
+
+    /// Read contract code by hash
+    fn read_code(db: &rocksdb::DB, code_hash: &E::Fr) -> Result<Option<Vec<u8>>, StateError> {
+        db.get(Self::code_key(code_hash)?)
+            .map_err(|e| StateError::StorageError(format!("Failed to read code: {}", e)))
+    }
+
+    /// Read all contract code
+    fn read_all_code(db: &rocksdb::DB) -> Result<HashMap<E::Fr, Vec<u8>>, StateError> {
+        let mut code = HashMap::new();
+        for item in db.prefix_iterator([0x07]) {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read code: {}", e)))?;
+            if key[0] != 0x07 {
+                break;
+            }
+            let hash = E::Fr::deserialize(&key[1..])
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            code.insert(hash, value.to_vec());
+        }
+        Ok(code)
+    }
+
+    /// Get serialized key prefix for contract storage slots
+    fn storage_prefix(id: &AccountId) -> Vec<u8> {
+        let mut key = Vec::with_capacity(5 + id.0.len());
+        key.push(0x08); // Prefix for contract storage
+        key.extend_from_slice(&(id.0.len() as u32).to_be_bytes());
+        key.extend_from_slice(&id.0);
+        key
+    }
+
+    /// Get serialized key for contract storage slot
+    fn storage_slot_key(id: &AccountId, slot: &E::Fr) -> Result<Vec<u8>, StateError> {
+        let mut key = Self::storage_prefix(id);
+        slot.serialize(&mut key)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        Ok(key)
+    }
+
+    /// Get serialized key prefix for storage slot versions of account
+    fn slot_version_prefix(id: &AccountId) -> Vec<u8> {
+        let mut key = Vec::with_capacity(5 + id.0.len());
+        key.push(0x09); // Prefix for versioned storage slots
+        key.extend_from_slice(&(id.0.len() as u32).to_be_bytes());
+        key.extend_from_slice(&id.0);
+        key
+    }
+
+    /// Get serialized key for storage slot version at height
+    fn slot_version_key(id: &AccountId, slot: &E::Fr, height: u64) -> Result<Vec<u8>, StateError> {
+        let mut key = Self::slot_version_prefix(id);
+        slot.serialize(&mut key)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        key.extend_from_slice(&height.to_be_bytes());
+        Ok(key)
+    }
+
+    /// Serialize storage slot value (empty marks a cleared slot)
+    fn encode_slot(value: &E::Fr) -> Result<Vec<u8>, StateError> {
+        let mut bytes = Vec::new();
+        if !value.is_zero() {
+            value.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        }
+        Ok(bytes)
+    }
+
+    /// Read storage slots of account as of height from slot versions
+    fn read_storage_at(db: &rocksdb::DB, id: &AccountId, height: u64) -> Result<HashMap<E::Fr, E::Fr>, StateError> {
+        let prefix = Self::slot_version_prefix(id);
+        let mut storage = HashMap::new();
+        
+        // Versions of a slot are ordered by height, the last one at or below height wins
+        for item in db.prefix_iterator(&prefix) {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read storage: {}", e)))?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let (slot_bytes, version) = key[prefix.len()..].split_at(key.len() - prefix.len() - 8);
+            if u64::from_be_bytes(version.try_into().unwrap()) > height {
+                continue;
+            }
+            let slot = E::Fr::deserialize(slot_bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            if value.is_empty() {
+                storage.remove(&slot);
+            } else {
+                let value = E::Fr::deserialize(&value[..])
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                storage.insert(slot, value);
+            }
+        }
+        Ok(storage)
+    }
+
+    /// Read storage slots of account
+    fn read_storage(db: &rocksdb::DB, id: &AccountId) -> Result<HashMap<E::Fr, E::Fr>, StateError> {
+        let prefix = Self::storage_prefix(id);
+        let mut storage = HashMap::new();
+        for item in db.prefix_iterator(&prefix) {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read storage: {}", e)))?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let slot = E::Fr::deserialize(&key[prefix.len()..])
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            let value = E::Fr::deserialize(&value[..])
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            storage.insert(slot, value);
+        }
+        Ok(storage)
+    }
+
+    /// Delete all storage slots of account
+    fn delete_storage(db: &rocksdb::DB, batch: &mut rocksdb::WriteBatch, id: &AccountId) -> Result<(), StateError> {
+        let prefix = Self::storage_prefix(id);
+        for item in db.prefix_iterator(&prefix) {
+            let (key, _) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read storage: {}", e)))?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            batch.delete(&key);
+        }
+        Ok(())
+    }
+
+    /// Write account record and the storage slots that changed
+    ///
+    /// The record commits to storage through `state_root`, so an unchanged
+    /// record is skipped. Returns the changed slots, cleared ones as zero, or
+    /// `None` if the record was unchanged.
+    fn write_account(
+        db: &rocksdb::DB,
+        batch: &mut rocksdb::WriteBatch,
+        account: &Account<E>,
+    ) -> Result<Option<Vec<(E::Fr, E::Fr)>>, StateError> {
+        let record = account.serialize()?;
+        let previous = db.get(Self::account_key(&account.id))
+            .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))?;
+        if previous.as_deref() == Some(&record[..]) {
+            return Ok(None);
+        }
+        batch.put(Self::account_key(&account.id), record);
+        
+        let stored = Self::read_storage(db, &account.id)?;
+        let mut changed = Vec::new();
+        for (slot, value) in &account.storage {
+            if !value.is_zero() && stored.get(slot) != Some(value) {
+                batch.put(Self::storage_slot_key(&account.id, slot)?, Self::encode_slot(value)?);
+                changed.push((*slot, *value));
+            }
+        }
+        for slot in stored.keys() {
+            if account.get_storage(slot).map_or(true, |value| value.is_zero()) {
+                batch.delete(Self::storage_slot_key(&account.id, slot)?);
+                changed.push((*slot, E::Fr::zero()));
+            }
+        }
+        
+        Ok(Some(changed))
+    }
+
+    /// Read account record with its storage slots
+    fn read_account(db: &rocksdb::DB, record: &[u8]) -> Result<Account<E>, StateError> {
+        let mut account = Account::deserialize(record)?;
+        account.storage = Self::read_storage(db, &account.id)?;
+        Ok(account)
+    }
+
+    /// Decode a versioned account record with its storage as of height
+    /// (empty marks deletion)
+    fn decode_version(db: &rocksdb::DB, value: &[u8], height: u64) -> Result<Option<Account<E>>, StateError> {
+        if value.is_empty() {
+            return Ok(None);
+        }
+        let mut account = Account::deserialize(value)?;
+        account.storage = Self::read_storage_at(db, &account.id, height)?;
+        Ok(Some(account))
+    }
+
+    /// Write state root, changed accounts and code into batch, returning
+    /// the accounts written with their changed slots
+    fn write_state<'a>(
+        db: &rocksdb::DB,
+        batch: &mut rocksdb::WriteBatch,
+        state: &'a State<E>,
+    ) -> Result<Vec<(&'a Account<E>, Vec<(E::Fr, E::Fr)>)>, StateError> {
+        // Save root
+        let mut root_bytes = Vec::new();
+        state.root.serialize(&mut root_bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        batch.put(Self::root_key(), root_bytes);
+        
+        // Save accounts, storage slots are kept apart from the records
+        let mut written = Vec::new();
+        for account in state.accounts.values() {
+            if let Some(changed) = Self::write_account(db, batch, account)? {
+                written.push((account, changed));
+            }
+        }
+        
+        // Save contract code, identical code shares one entry
+        for (hash, code) in &state.code {
+            if code::code_hash::<E>(code)? != *hash {
+                return Err(StateError::StorageError("Code does not match its hash".to_string()));
+            }
+            batch.put(Self::code_key(hash)?, code);
+        }
+        
+        Ok(written)
+    }
 }
 
 impl<E: PairingEngine> StateStorage<E> for PersistentStorage<E> {
@@ -152,31 +720,22 @@ impl<E: PairingEngine> StateStorage<E> for PersistentStorage<E> {
                 .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))?;
             
             if key[0] == 0x01 {
-                let account = Account::deserialize(&value[..])
-                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                let account = Self::read_account(&db, &value)?;
                 accounts.insert(account.id.clone(), account);
             }
         }
         
-        Ok(State { accounts, root })
+        let mut state = State::new();
+        state.accounts = accounts;
+        state.code = Self::read_all_code(&db)?;
+        state.root = root;
+        Ok(state)
     }
 
     async fn save_state(&mut self, state: &State<E>) -> Result<(), StateError> {
-        let mut db = self.db.write().await;
-        let batch = rocksdb::WriteBatch::default();
-        
-        // Save root
-        let mut root_bytes = Vec::new();
-        state.root.serialize(&mut root_bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        batch.put(Self::root_key(), root_bytes);
-        
-        // Save accounts
-        for account in state.accounts.values() {
-            let account_bytes = account.serialize()
-                .map_err(|e| StateError::SerializationError(e.to_string()))?;
-            batch.put(Self::account_key(&account.id), account_bytes);
-        }
+        let db = self.db.write().await;
+        let mut batch = rocksdb::WriteBatch::default();
+        Self::write_state(&db, &mut batch, state)?;
         
         db.write(batch)
             .map_err(|e| StateError::StorageError(format!("Failed to write batch: {}", e)))?;
@@ -190,31 +749,30 @@ impl<E: PairingEngine> StateStorage<E> for PersistentStorage<E> {
         
         if let Some(bytes) = db.get(key)
             .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))? {
-            let account = Account::deserialize(&bytes[..])
-                .map_err(|e| StateError::SerializationError(e.to_string()))?;
-            Ok(Some(account))
+            Ok(Some(Self::read_account(&db, &bytes)?))
         } else {
             Ok(None)
         }
     }
 
     async fn save_account(&mut self, account: &Account<E>) -> Result<(), StateError> {
-        let mut db = self.db.write().await;
-        let key = Self::account_key(&account.id);
-        let value = account.serialize()
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        let db = self.db.write().await;
+        let mut batch = rocksdb::WriteBatch::default();
+        Self::write_account(&db, &mut batch, account)?;
         
-        db.put(key, value)
+        db.write(batch)
             .map_err(|e| StateError::StorageError(format!("Failed to write account: {}", e)))?;
         
         Ok(())
     }
 
     async fn delete_account(&mut self, id: &AccountId) -> Result<(), StateError> {
-        let mut db = self.db.write().await;
-        let key = Self::account_key(id);
+        let db = self.db.write().await;
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.delete(Self::account_key(id));
+        Self::delete_storage(&db, &mut batch, id)?;
         
-        db.delete(key)
+        db.write(batch)
             .map_err(|e| StateError::StorageError(format!("Failed to delete account: {}", e)))?;
         
         Ok(())
@@ -251,12 +809,328 @@ impl<E: PairingEngine> StateStorage<E> for PersistentStorage<E> {
         
         Ok(())
     }
+
+    async fn save_state_version(&mut self, state: &State<E>) -> Result<(), StateError> {
+        let height = state.block_height;
+        {
+            let db = self.db.write().await;
+            if let Some(latest) = Self::latest_version_height(&db)? {
+                if height < latest {
+                    return Err(StateError::StorageError(format!(
+                        "Cannot save height {} below latest height {}", height, latest
+                    )));
+                }
+            }
+
+            let mut batch = rocksdb::WriteBatch::default();
+            
+            // Record deleted accounts, clearing their slots from this height
+            let iter = db.prefix_iterator([0x01]);
+            for item in iter {
+                let (key, _) = item
+                    .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))?;
+                if key[0] != 0x01 {
+                    break;
+                }
+                let id = AccountId(key[1..].to_vec());
+                if !state.accounts.contains_key(&id) {
+                    batch.put(Self::account_version_key(&id, height), Vec::new());
+                    for slot in Self::read_storage(&db, &id)?.keys() {
+                        batch.put(Self::slot_version_key(&id, slot, height)?, Vec::new());
+                    }
+                    batch.delete(Self::account_key(&id));
+                    Self::delete_storage(&db, &mut batch, &id)?;
+                }
+            }
+            
+            // Record changed and new accounts with only the slots that changed
+            for (account, changed) in Self::write_state(&db, &mut batch, state)? {
+                batch.put(Self::account_version_key(&account.id, height), account.serialize()?);
+                for (slot, value) in changed {
+                    batch.put(Self::slot_version_key(&account.id, &slot, height)?, Self::encode_slot(&value)?);
+                }
+            }
+            
+            let mut root_bytes = Vec::new();
+            state.root.serialize(&mut root_bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            batch.put(Self::version_root_key(height), root_bytes);
+            
+            db.write(batch)
+                .map_err(|e| StateError::StorageError(format!("Failed to write batch: {}", e)))?;
+        }
+        
+        self.prune().await
+    }
+
+    async fn load_state_at(&self, height: u64) -> Result<State<E>, StateError> {
+        let db = self.db.read().await;
+        Self::check_height(&db, height)?;
+        
+        let mut state = State::new();
+        let mut latest: HashMap<AccountId, (u64, Vec<u8>)> = HashMap::new();
+        
+        let iter = db.prefix_iterator([0x03]);
+        for item in iter {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))?;
+            if let Some((id, version)) = Self::parse_account_version_key(&key) {
+                if version <= height {
+                    latest.insert(id, (version, value.to_vec()));
+                }
+            }
+        }
+        
+        for (id, (_, value)) in latest {
+            if let Some(account) = Self::decode_version(&db, &value, height)? {
+                state.accounts.insert(id, account);
+            }
+        }
+        
+        let iter = db.iterator(rocksdb::IteratorMode::From(
+            &Self::version_root_key(height),
+            rocksdb::Direction::Reverse,
+        ));
+        for item in iter {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read root: {}", e)))?;
+            if key[0] == 0x02 {
+                state.root = E::Fr::deserialize(&value[..])
+                    .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            }
+            break;
+        }
+        state.block_height = height;
+        state.code = Self::read_all_code(&db)?;
+        
+        Ok(state)
+    }
+
+    async fn get_account_at(&self, id: &AccountId, height: u64) -> Result<Option<Account<E>>, StateError> {
+        let db = self.db.read().await;
+        Self::check_height(&db, height)?;
+        
+        let prefix = Self::account_version_prefix(id);
+        let iter = db.iterator(rocksdb::IteratorMode::From(
+            &Self::account_version_key(id, height),
+            rocksdb::Direction::Reverse,
+        ));
+        for item in iter {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))?;
+            if !key.starts_with(&prefix) || key.len() != prefix.len() + 8 {
+                break;
+            }
+            return Self::decode_version(&db, &value, height);
+        }
+        
+        Ok(None)
+    }
+
+    async fn set_finalized_height(&mut self, height: u64) -> Result<(), StateError> {
+        {
+            let db = self.db.read().await;
+            if height < Self::read_meta(&db, 0)? {
+                return Err(StateError::StorageError("Finalized height cannot decrease".to_string()));
+            }
+            db.put(Self::version_meta_key(0), height.to_be_bytes())
+                .map_err(|e| StateError::StorageError(format!("Failed to write metadata: {}", e)))?;
+        }
+        
+        self.prune().await
+    }
+
+    async fn prune(&mut self) -> Result<(), StateError> {
+        let db = self.db.write().await;
+        let latest = match Self::latest_version_height(&db)? {
+            Some(latest) => latest,
+            None => return Ok(()),
+        };
+        
+        let cutoff = self.pruning.retain_from(latest, Self::read_meta(&db, 0)?);
+        if cutoff <= Self::read_meta(&db, 1)? {
+            return Ok(());
+        }
+        
+        let mut batch = rocksdb::WriteBatch::default();
+        
+        // Keep the newest version at or below the cutoff, it is still live there
+        let mut newest: HashMap<AccountId, (u64, Vec<u8>)> = HashMap::new();
+        let iter = db.prefix_iterator([0x03]);
+        for item in iter {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read account: {}", e)))?;
+            if let Some((id, version)) = Self::parse_account_version_key(&key) {
+                if version < cutoff {
+                    batch.delete(&key);
+                    newest.insert(id, (version, value.to_vec()));
+                } else if version == cutoff {
+                    newest.remove(&id);
+                }
+            }
+        }
+        for (id, (_, value)) in newest {
+            if !value.is_empty() {
+                batch.put(Self::account_version_key(&id, cutoff), value);
+            }
+        }
+        
+        // Likewise for each storage slot
+        let mut newest_slots: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        for item in db.prefix_iterator([0x09]) {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read storage: {}", e)))?;
+            if key[0] != 0x09 {
+                break;
+            }
+            let (slot_key, version) = key.split_at(key.len() - 8);
+            let version = u64::from_be_bytes(version.try_into().unwrap());
+            if version < cutoff {
+                batch.delete(&key);
+                newest_slots.insert(slot_key.to_vec(), value.to_vec());
+            } else if version == cutoff {
+                newest_slots.remove(slot_key);
+            }
+        }
+        for (mut slot_key, value) in newest_slots {
+            if !value.is_empty() {
+                slot_key.extend_from_slice(&cutoff.to_be_bytes());
+                batch.put(slot_key, value);
+            }
+        }
+        
+        let mut newest_root = None;
+        let iter = db.prefix_iterator([0x02]);
+        for item in iter {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read root: {}", e)))?;
+            let version = u64::from_be_bytes(key[1..9].try_into().unwrap());
+            if version >= cutoff {
+                if version == cutoff {
+                    newest_root = None;
+                }
+                break;
+            }
+            batch.delete(&key);
+            newest_root = Some(value.to_vec());
+        }
+        if let Some(root) = newest_root {
+            batch.put(Self::version_root_key(cutoff), root);
+        }
+        
+        batch.put(Self::version_meta_key(1), cutoff.to_be_bytes());
+        db.write(batch)
+            .map_err(|e| StateError::StorageError(format!("Failed to write batch: {}", e)))?;
+        
+        Ok(())
+    }
+
+    async fn index_logs(&mut self, height: u64, logs: Vec<Log<E>>) -> Result<LogsBloom, StateError> {
+        let db = self.db.write().await;
+        let mut batch = rocksdb::WriteBatch::default();
+        let bloom = LogsBloom::from_logs(&logs)?;
+        
+        // Replace any logs previously indexed at this height
+        let prefix = Self::block_logs_prefix(height);
+        for item in db.prefix_iterator(&prefix) {
+            let (key, _) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read log: {}", e)))?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            batch.delete(&key);
+        }
+        
+        batch.put(Self::bloom_key(height), bloom.0);
+        for (index, log) in logs.iter().enumerate() {
+            let mut key = prefix.clone();
+            key.extend_from_slice(&(index as u32).to_be_bytes());
+            batch.put(key, log.encode()?);
+        }
+        
+        db.write(batch)
+            .map_err(|e| StateError::StorageError(format!("Failed to write batch: {}", e)))?;
+        
+        Ok(bloom)
+    }
+
+    async fn get_logs(&self, filter: &LogFilter<E>) -> Result<Vec<Log<E>>, StateError> {
+        let db = self.db.read().await;
+        let mut result = Vec::new();
+        if filter.from_block > filter.to_block {
+            return Ok(result);
+        }
+        
+        let iter = db.iterator(rocksdb::IteratorMode::From(
+            &Self::bloom_key(filter.from_block),
+            rocksdb::Direction::Forward,
+        ));
+        for item in iter {
+            let (key, value) = item
+                .map_err(|e| StateError::StorageError(format!("Failed to read bloom: {}", e)))?;
+            if key[0] != 0x05 {
+                break;
+            }
+            let height = u64::from_be_bytes(key[1..9].try_into().unwrap());
+            if height > filter.to_block {
+                break;
+            }
+            
+            // Skip blocks ruled out by their bloom
+            let bloom = LogsBloom(value[..].try_into()
+                .map_err(|_| StateError::SerializationError(format!("Bloom must be {} bytes", BLOOM_SIZE)))?);
+            if !filter.may_match(&bloom)? {
+                continue;
+            }
+            
+            let prefix = Self::block_logs_prefix(height);
+            for item in db.prefix_iterator(&prefix) {
+                let (key, value) = item
+                    .map_err(|e| StateError::StorageError(format!("Failed to read log: {}", e)))?;
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                let log = Log::decode(&value)?;
+                if filter.matches(&log) {
+                    result.push(log);
+                }
+            }
+        }
+        
+        Ok(result)
+    }
+
+    async fn save_code(&mut self, code: &[u8]) -> Result<E::Fr, StateError> {
+        code::check_code_size(code)?;
+        let db = self.db.write().await;
+        let hash = code::code_hash::<E>(code)?;
+        
+        if Self::read_code(&db, &hash)?.is_none() {
+            db.put(Self::code_key(&hash)?, code)
+                .map_err(|e| StateError::StorageError(format!("Failed to write code: {}", e)))?;
+        }
+        
+        Ok(hash)
+    }
+
+    async fn get_code(&self, code_hash: &E::Fr) -> Result<Option<Vec<u8>>, StateError> {
+        let db = self.db.read().await;
+        Self::read_code(&db, code_hash)
+    }
+
+    async fn get_contract_code(&self, id: &AccountId) -> Result<Option<Vec<u8>>, StateError> {
+        let account = self.get_account(id).await?;
+        match account.and_then(|account| account.code_hash) {
+            Some(hash) => self.get_code(&hash).await,
+            None => Ok(None),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_bls12_381::Bls12_381;
+    use ark_bls12_381::{Bls12_381, Fr};
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -301,4 +1175,140 @@ mod tests {
         storage.clear().await.unwrap();
         assert!(storage.get_account(&id).await.unwrap().is_none());
     }
-}
\ No newline at end of file
+
+    fn versioned_state(height: u64, balance: u64) -> State<Bls12_381> {
+        let mut state = State::new();
+        let mut account = Account::new(
+            AccountId(vec![1, 2, 3]),
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        );
+        account.balance = balance;
+        state.set_account(account);
+        state.block_height = height;
+        state
+    }
+
+    #[tokio::test]
+    async fn test_persistent_versions() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = PersistentStorage::<Bls12_381>::new(temp_dir.path().to_path_buf()).unwrap();
+        let id = AccountId(vec![1, 2, 3]);
+
+        storage.save_state_version(&versioned_state(1, 10)).await.unwrap();
+        storage.save_state_version(&versioned_state(2, 20)).await.unwrap();
+        assert_eq!(storage.get_account_at(&id, 1).await.unwrap().unwrap().balance, 10);
+
+        // One version per save of the only account, none for other keys
+        let db = storage.db.read().await;
+        let versions = db.prefix_iterator([0x03])
+            .map(|item| item.unwrap().0)
+            .take_while(|key| key[0] == 0x03)
+            .count();
+        assert_eq!(versions, 2);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_slot_versions() {
+        let temp_dir = tempdir().unwrap();
+        let mut storage = PersistentStorage::<Bls12_381>::new(temp_dir.path().to_path_buf()).unwrap();
+        let id = AccountId(vec![9]);
+        let mut contract = Account::new_contract(
+            id.clone(),
+            Fr::from(1u64),
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        );
+
+        // Ten slots, then one changed and one cleared
+        let mut state = State::new();
+        for slot in 0..10u64 {
+            contract.set_storage(Fr::from(slot), Fr::from(slot + 100));
+        }
+        contract.commit_storage().unwrap();
+        state.set_account(contract.clone());
+        state.block_height = 1;
+        storage.save_state_version(&state).await.unwrap();
+
+        contract.set_storage(Fr::from(3u64), Fr::from(7u64));
+        contract.set_storage(Fr::from(4u64), Fr::zero());
+        contract.commit_storage().unwrap();
+        state.set_account(contract);
+        state.block_height = 2;
+        storage.save_state_version(&state).await.unwrap();
+
+        let old = storage.get_account_at(&id, 1).await.unwrap().unwrap();
+        assert_eq!(old.get_storage(&Fr::from(3u64)), Some(Fr::from(103u64)));
+        assert_eq!(old.storage_root().unwrap(), old.state_root);
+        let new = storage.load_state_at(2).await.unwrap().get_account(&id).unwrap();
+        assert_eq!(new.get_storage(&Fr::from(3u64)), Some(Fr::from(7u64)));
+        assert_eq!(new.get_storage(&Fr::from(4u64)), None);
+        assert_eq!(new.storage_root().unwrap(), new.state_root);
+
+        // Only the two changed slots are versioned again
+        let db = storage.db.read().await;
+        let versions = db.prefix_iterator([0x09])
+            .map(|item| item.unwrap().0)
+            .take_while(|key| key[0] == 0x09)
+            .count();
+        assert_eq!(versions, 12);
+    }
+
+    #[test]
+    fn test_code_storage() {
+        let mut storage = MemoryStorage::<Bls12_381>::new();
+        let code = vec![0x01, 0, 0, 0, 0, 0, 0, 0, 7, 0x80];
+
+        let hash = storage.save_code(&code).unwrap();
+        assert_eq!(storage.save_code(&code).unwrap(), hash);
+        assert_eq!(storage.get_code(&hash).unwrap(), Some(code.clone()));
+
+        let id = AccountId(vec![9]);
+        storage.save_account(&Account::new_contract(
+            id.clone(),
+            hash,
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        )).unwrap();
+        assert_eq!(storage.get_contract_code(&id).unwrap(), Some(code));
+        assert!(storage.get_contract_code(&AccountId(vec![1])).unwrap().is_none());
+
+        assert!(storage.save_code(&vec![0u8; code::MAX_CODE_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_historical_queries() {
+        let mut storage = MemoryStorage::<Bls12_381>::new();
+        let id = AccountId(vec![1, 2, 3]);
+
+        for height in 1..=5 {
+            storage.save_state_version(&versioned_state(height, height * 10)).unwrap();
+        }
+
+        assert_eq!(storage.get_account_at(&id, 2).unwrap().unwrap().balance, 20);
+        assert_eq!(storage.get_account_at(&id, 5).unwrap().unwrap().balance, 50);
+        assert_eq!(storage.get_account(&id).unwrap().unwrap().balance, 50);
+
+        let state = storage.load_state_at(3).unwrap();
+        assert_eq!(state.get_account(&id).unwrap().balance, 30);
+        assert!(state.get_account_proof(&id).is_ok());
+    }
+
+    #[test]
+    fn test_pruning_modes() {
+        let id = AccountId(vec![1, 2, 3]);
+
+        let mut storage = MemoryStorage::<Bls12_381>::with_pruning(PruningMode::KeepLast(2));
+        for height in 1..=5 {
+            storage.save_state_version(&versioned_state(height, height * 10)).unwrap();
+        }
+        assert!(storage.get_account_at(&id, 3).is_err());
+        assert_eq!(storage.get_account_at(&id, 4).unwrap().unwrap().balance, 40);
+
+        let mut storage = MemoryStorage::<Bls12_381>::with_pruning(PruningMode::KeepFinalized);
+        for height in 1..=5 {
+            storage.save_state_version(&versioned_state(height, height * 10)).unwrap();
+        }
+        assert_eq!(storage.get_account_at(&id, 1).unwrap().unwrap().balance, 10);
+        storage.set_finalized_height(4).unwrap();
+        assert!(storage.get_account_at(&id, 3).is_err());
+        assert_eq!(storage.get_account_at(&id, 4).unwrap().unwrap().balance, 40);
+    }
+}
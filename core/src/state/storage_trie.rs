@@ -0,0 +1,180 @@
+use super::{AccountId, StateError, StateProof};
+use super::merkle_tree::{MerkleProof, MerkleTree};
+use ark_ec::PairingEngine;
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use std::collections::HashMap;
+
+/// Depth of contract storage trees
+pub const STORAGE_TREE_DEPTH: usize = 256;
+
+/// Depth of the global state tree
+const STATE_TREE_DEPTH: usize = 256;
+
+/// Sparse Merkle tree over the storage slots of one contract
+pub struct StorageTrie<E: PairingEngine> {
+    /// Slot tree
+    tree: MerkleTree<E>,
+}
+
+impl<E: PairingEngine> StorageTrie<E> {
+    /// Create empty storage tree
+    pub fn new() -> Self {
+        Self {
+            tree: MerkleTree::new(STORAGE_TREE_DEPTH),
+        }
+    }
+
+    /// Build storage tree from slots, zero values are treated as absent
+    pub fn from_slots(slots: &HashMap<E::Fr, E::Fr>) -> Result<Self, StateError> {
+        let mut trie = Self::new();
+        for (slot, value) in slots {
+            if !value.is_zero() {
+                trie.insert(slot, value)?;
+            }
+        }
+        Ok(trie)
+    }
+
+    /// Insert slot value
+    pub fn insert(&mut self, slot: &E::Fr, value: &E::Fr) -> Result<E::Fr, StateError> {
+        self.tree.update(&field_bytes::<E>(slot)?, &field_bytes::<E>(value)?)
+    }
+
+    /// Get storage root
+    pub fn root(&self) -> E::Fr {
+        self.tree.root()
+    }
+
+    /// Get proof for slot
+    pub fn prove(&self, slot: &E::Fr) -> Result<MerkleProof<E>, StateError> {
+        self.tree.get_proof(&field_bytes::<E>(slot)?)
+    }
+
+    /// Verify slot proof against storage root; `None` proves the slot is empty
+    pub fn verify(
+        root: &E::Fr,
+        slot: &E::Fr,
+        value: Option<&E::Fr>,
+        proof: &MerkleProof<E>,
+    ) -> Result<bool, StateError> {
+        let value = value.filter(|value| !value.is_zero()).map(field_bytes::<E>).transpose()?;
+        Self::new().tree.verify_proof_against(
+            root,
+            &field_bytes::<E>(slot)?,
+            value.as_deref(),
+            proof,
+        )
+    }
+}
+
+impl<E: PairingEngine> Default for StorageTrie<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Proof of one contract storage slot against the global state root
+#[derive(Clone, Debug)]
+pub struct StorageProof<E: PairingEngine> {
+    /// Proof of the contract account
+    pub account_proof: StateProof<E>,
+
+    /// Storage slot
+    pub slot: E::Fr,
+
+    /// Slot value, `None` if empty
+    pub value: Option<E::Fr>,
+
+    /// Proof of the slot against the account's storage root
+    pub slot_proof: MerkleProof<E>,
+}
+
+impl<E: PairingEngine> StorageProof<E> {
+    /// Get contract account ID
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_proof.account_id
+    }
+
+    /// Verify slot value against global state root
+    pub fn verify(&self, state_root: &E::Fr) -> Result<bool, StateError> {
+        let account = match &self.account_proof.account {
+            Some(account) => account,
+            None => return Ok(false),
+        };
+
+        // Account record against the global root
+        let account_valid = MerkleTree::<E>::new(STATE_TREE_DEPTH).verify_proof_against(
+            state_root,
+            &account.id.0,
+            Some(&account.serialize()?),
+            &self.account_proof.merkle_proof,
+        )?;
+        if !account_valid || account.id != self.account_proof.account_id {
+            return Ok(false);
+        }
+
+        // Slot against the account's storage root
+        StorageTrie::verify(&account.state_root, &self.slot, self.value.as_ref(), &self.slot_proof)
+    }
+}
+
+/// Canonical bytes of a field element
+fn field_bytes<E: PairingEngine>(value: &E::Fr) -> Result<Vec<u8>, StateError> {
+    let mut bytes = Vec::new();
+    value.serialize(&mut bytes)
+        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Account, State};
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    #[test]
+    fn test_slot_proof() {
+        let mut slots = HashMap::new();
+        for i in 1..=4u64 {
+            slots.insert(Fr::from(i), Fr::from(i * 10));
+        }
+        let trie = StorageTrie::<Bls12_381>::from_slots(&slots).unwrap();
+        let root = trie.root();
+
+        let proof = trie.prove(&Fr::from(2u64)).unwrap();
+        assert!(StorageTrie::verify(&root, &Fr::from(2u64), Some(&Fr::from(20u64)), &proof).unwrap());
+        assert!(!StorageTrie::verify(&root, &Fr::from(2u64), Some(&Fr::from(21u64)), &proof).unwrap());
+
+        // Empty slot
+        let proof = trie.prove(&Fr::from(9u64)).unwrap();
+        assert!(StorageTrie::verify(&root, &Fr::from(9u64), None, &proof).unwrap());
+    }
+
+    #[test]
+    fn test_storage_proof_against_state_root() {
+        let mut state = State::<Bls12_381>::new();
+        let mut contract = Account::new_contract(
+            AccountId(vec![7]),
+            Fr::from(1u64),
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        );
+        contract.set_storage(Fr::from(3u64), Fr::from(33u64));
+        contract.commit_storage().unwrap();
+        state.set_account(contract);
+        state.set_account(Account::new(
+            AccountId(vec![8]),
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        ));
+        state.root = state.calculate_root(&HashMap::new()).unwrap();
+
+        let proof = state.get_storage_proof(&AccountId(vec![7]), &Fr::from(3u64)).unwrap();
+        assert_eq!(proof.value, Some(Fr::from(33u64)));
+        assert!(proof.verify(&state.root).unwrap());
+
+        // Forged value fails
+        let mut forged = proof.clone();
+        forged.value = Some(Fr::from(34u64));
+        assert!(!forged.verify(&state.root).unwrap());
+    }
+}
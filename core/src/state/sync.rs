@@ -0,0 +1,426 @@
+use super::{State, Account, AccountId, StateError, StateStorage};
+use super::merkle_tree::{MerkleTree, RangeProof};
+use ark_ec::PairingEngine;
+use ark_ff::Field;
+use std::collections::HashMap;
+
+/// Depth of the global state tree
+const STATE_TREE_DEPTH: usize = 256;
+
+/// Request for a chunk of state
+#[derive(Clone, Debug)]
+pub struct ChunkRequest<E: PairingEngine> {
+    /// State root the chunk must be proven against
+    pub root: E::Fr,
+
+    /// First key path of the chunk (inclusive)
+    pub start: Vec<bool>,
+
+    /// Maximum number of accounts in the chunk
+    pub max_accounts: usize,
+}
+
+/// Chunk of state covering a contiguous key range
+#[derive(Clone, Debug)]
+pub struct StateChunk<E: PairingEngine> {
+    /// State root the chunk is proven against
+    pub root: E::Fr,
+
+    /// First key path covered (inclusive)
+    pub start: Vec<bool>,
+
+    /// Last key path covered (inclusive)
+    pub end: Vec<bool>,
+
+    /// Accounts in the range, ordered by key path
+    pub accounts: Vec<Account<E>>,
+
+    /// Range proof for the accounts
+    pub proof: RangeProof<E>,
+}
+
+/// Transport used by a syncing node to fetch chunks
+pub trait SyncTransport<E: PairingEngine> {
+    /// Request a chunk from a peer
+    fn request_chunk(&mut self, request: &ChunkRequest<E>) -> Result<StateChunk<E>, StateError>;
+}
+
+/// Resumable sync progress
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncProgress {
+    /// Next key path to request, `None` once the key space is covered
+    pub next_start: Option<Vec<bool>>,
+
+    /// Number of chunks applied
+    pub chunks_applied: u64,
+
+    /// Number of accounts synced
+    pub accounts_synced: u64,
+}
+
+impl SyncProgress {
+    /// Create progress for a fresh sync
+    pub fn new() -> Self {
+        Self {
+            next_start: Some(vec![false; STATE_TREE_DEPTH]),
+            chunks_applied: 0,
+            accounts_synced: 0,
+        }
+    }
+
+    /// Check if the whole key space has been synced
+    pub fn is_complete(&self) -> bool {
+        self.next_start.is_none()
+    }
+}
+
+/// Serves state chunks to syncing nodes
+pub struct StateSyncServer<E: PairingEngine> {
+    /// State tree
+    tree: MerkleTree<E>,
+
+    /// Accounts ordered by key path
+    accounts: Vec<(Vec<bool>, Account<E>)>,
+}
+
+impl<E: PairingEngine> StateSyncServer<E> {
+    /// Create new sync server for state
+    pub fn new(state: &State<E>) -> Result<Self, StateError> {
+        let mut tree = MerkleTree::new(STATE_TREE_DEPTH);
+        let mut accounts = Vec::with_capacity(state.accounts.len());
+
+        for (id, account) in &state.accounts {
+            let account_bytes = account.serialize()?;
+            tree.update(&id.0, &account_bytes)?;
+            accounts.push((tree.key_path(&id.0), account.clone()));
+        }
+
+        accounts.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Self { tree, accounts })
+    }
+
+    /// Get served state root
+    pub fn root(&self) -> E::Fr {
+        self.tree.root()
+    }
+
+    /// Serve chunk for request
+    pub fn serve_chunk(&self, request: &ChunkRequest<E>) -> Result<StateChunk<E>, StateError> {
+        if request.root != self.root() {
+            return Err(StateError::ValidationError("Unknown state root".to_string()));
+        }
+
+        if request.start.len() != STATE_TREE_DEPTH || request.max_accounts == 0 {
+            return Err(StateError::ValidationError("Invalid chunk request".to_string()));
+        }
+
+        let first = self.accounts.partition_point(|(path, _)| *path < request.start);
+        let last = (first + request.max_accounts).min(self.accounts.len());
+
+        // The final chunk extends to the end of the key space
+        let end = if last == self.accounts.len() {
+            vec![true; STATE_TREE_DEPTH]
+        } else {
+            self.accounts[last - 1].0.clone()
+        };
+
+        let proof = self.tree.get_range_proof(&request.start, &end)?;
+        let accounts = self.accounts[first..last]
+            .iter()
+            .map(|(_, account)| account.clone())
+            .collect();
+
+        Ok(StateChunk {
+            root: request.root,
+            start: request.start.clone(),
+            end,
+            accounts,
+            proof,
+        })
+    }
+}
+
+/// Assembles verified chunks into storage
+pub struct StateSyncClient<E: PairingEngine> {
+    /// Trusted state root
+    trusted_root: E::Fr,
+
+    /// Sync progress
+    progress: SyncProgress,
+
+    /// Target storage
+    storage: Box<dyn StateStorage<E>>,
+
+    /// Tree used for proof verification
+    verifier: MerkleTree<E>,
+
+    /// Accounts per chunk request
+    chunk_size: usize,
+}
+
+impl<E: PairingEngine> StateSyncClient<E> {
+    /// Create new sync client writing into fresh storage
+    pub fn new(
+        trusted_root: E::Fr,
+        mut storage: Box<dyn StateStorage<E>>,
+        chunk_size: usize,
+    ) -> Result<Self, StateError> {
+        storage.clear()?;
+        Ok(Self::resume(trusted_root, SyncProgress::new(), storage, chunk_size))
+    }
+
+    /// Resume sync from saved progress
+    pub fn resume(
+        trusted_root: E::Fr,
+        progress: SyncProgress,
+        storage: Box<dyn StateStorage<E>>,
+        chunk_size: usize,
+    ) -> Self {
+        Self {
+            trusted_root,
+            progress,
+            storage,
+            verifier: MerkleTree::new(STATE_TREE_DEPTH),
+            chunk_size,
+        }
+    }
+
+    /// Get current progress
+    pub fn progress(&self) -> &SyncProgress {
+        &self.progress
+    }
+
+    /// Next chunk request, if any
+    pub fn next_request(&self) -> Option<ChunkRequest<E>> {
+        self.progress.next_start.as_ref().map(|start| ChunkRequest {
+            root: self.trusted_root,
+            start: start.clone(),
+            max_accounts: self.chunk_size,
+        })
+    }
+
+    /// Verify chunk against the trusted root
+    pub fn verify_chunk(&self, chunk: &StateChunk<E>) -> Result<(), StateError> {
+        if chunk.root != self.trusted_root {
+            return Err(StateError::ValidationError("Chunk root mismatch".to_string()));
+        }
+
+        if self.progress.next_start.as_ref() != Some(&chunk.start) {
+            return Err(StateError::ValidationError("Unexpected chunk start".to_string()));
+        }
+
+        // Each chunk must make progress without exceeding the requested size
+        if chunk.end.len() != STATE_TREE_DEPTH || chunk.end < chunk.start {
+            return Err(StateError::ValidationError("Chunk does not advance".to_string()));
+        }
+
+        if chunk.accounts.len() > self.chunk_size {
+            return Err(StateError::ValidationError("Chunk exceeds requested size".to_string()));
+        }
+
+        let mut leaves = Vec::with_capacity(chunk.accounts.len());
+        for account in &chunk.accounts {
+            if account.storage_root()? != account.state_root {
+                return Err(StateError::MerkleError("Account storage does not match state root".to_string()));
+            }
+            leaves.push((self.verifier.key_path(&account.id.0), account.serialize()?));
+        }
+
+        let valid = self.verifier.verify_range_proof(
+            &self.trusted_root,
+            &chunk.start,
+            &chunk.end,
+            &leaves,
+            &chunk.proof,
+        )?;
+
+        if !valid {
+            return Err(StateError::MerkleError("Invalid chunk range proof".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Verify and store chunk
+    pub fn apply_chunk(&mut self, chunk: &StateChunk<E>) -> Result<(), StateError> {
+        self.verify_chunk(chunk)?;
+
+        for account in &chunk.accounts {
+            self.storage.save_account(account)?;
+        }
+
+        self.progress.next_start = next_path(&chunk.end);
+        self.progress.chunks_applied += 1;
+        self.progress.accounts_synced += chunk.accounts.len() as u64;
+
+        Ok(())
+    }
+
+    /// Fetch and apply chunks until sync completes
+    pub fn sync<T: SyncTransport<E>>(&mut self, transport: &mut T) -> Result<(), StateError> {
+        while let Some(request) = self.next_request() {
+            let chunk = transport.request_chunk(&request)?;
+            self.apply_chunk(&chunk)?;
+        }
+
+        Ok(())
+    }
+
+    /// Finish sync and return the populated storage
+    pub fn finalize(mut self) -> Result<Box<dyn StateStorage<E>>, StateError> {
+        if !self.progress.is_complete() {
+            return Err(StateError::StorageError("State sync is not complete".to_string()));
+        }
+
+        let mut state = self.storage.load_state()?;
+        let root = state.calculate_root(&HashMap::new())?;
+        if root != self.trusted_root {
+            return Err(StateError::MerkleError("Synced state root mismatch".to_string()));
+        }
+
+        state.root = root;
+        self.storage.save_state(&state)?;
+
+        Ok(self.storage)
+    }
+}
+
+/// In-process transport serving chunks from a local server
+pub struct LocalTransport<'a, E: PairingEngine> {
+    /// Serving side
+    server: &'a StateSyncServer<E>,
+}
+
+impl<'a, E: PairingEngine> LocalTransport<'a, E> {
+    /// Create new local transport
+    pub fn new(server: &'a StateSyncServer<E>) -> Self {
+        Self { server }
+    }
+}
+
+impl<'a, E: PairingEngine> SyncTransport<E> for LocalTransport<'a, E> {
+    fn request_chunk(&mut self, request: &ChunkRequest<E>) -> Result<StateChunk<E>, StateError> {
+        self.server.serve_chunk(request)
+    }
+}
+
+/// Successor of a key path, `None` past the last path
+fn next_path(path: &[bool]) -> Option<Vec<bool>> {
+    let mut next = path.to_vec();
+    for bit in next.iter_mut().rev() {
+        if *bit {
+            *bit = false;
+        } else {
+            *bit = true;
+            return Some(next);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use crate::state::storage::MemoryStorage;
+
+    fn setup_test_state(count: u8) -> State<Bls12_381> {
+        let mut state = State::new();
+        for i in 0..count {
+            let mut account = Account::new(
+                AccountId(vec![i]),
+                Bls12_381::G1Projective::prime_subgroup_generator(),
+            );
+            account.balance = i as u64 * 100;
+            state.set_account(account);
+        }
+        state
+    }
+
+    #[test]
+    fn test_full_sync() {
+        let state = setup_test_state(10);
+        let server = StateSyncServer::new(&state).unwrap();
+        let mut transport = LocalTransport::new(&server);
+
+        let storage = Box::new(MemoryStorage::<Bls12_381>::new());
+        let mut client = StateSyncClient::new(server.root(), storage, 3).unwrap();
+        client.sync(&mut transport).unwrap();
+
+        assert_eq!(client.progress().accounts_synced, 10);
+        assert_eq!(client.progress().chunks_applied, 4);
+
+        let storage = client.finalize().unwrap();
+        let account = storage.get_account(&AccountId(vec![7])).unwrap().unwrap();
+        assert_eq!(account.balance, 700);
+        assert_eq!(storage.get_storage_root().unwrap(), server.root());
+    }
+
+    #[test]
+    fn test_tampered_chunk_rejected() {
+        let state = setup_test_state(5);
+        let server = StateSyncServer::new(&state).unwrap();
+
+        let storage = Box::new(MemoryStorage::<Bls12_381>::new());
+        let mut client = StateSyncClient::new(server.root(), storage, 5).unwrap();
+
+        let mut chunk = server.serve_chunk(&client.next_request().unwrap()).unwrap();
+        chunk.accounts[0].balance += 1;
+        assert!(client.apply_chunk(&chunk).is_err());
+
+        let mut chunk = server.serve_chunk(&client.next_request().unwrap()).unwrap();
+        chunk.accounts.pop();
+        assert!(client.apply_chunk(&chunk).is_err());
+        assert_eq!(client.progress().chunks_applied, 0);
+    }
+
+    #[test]
+    fn test_chunk_bounds_enforced() {
+        let state = setup_test_state(6);
+        let server = StateSyncServer::new(&state).unwrap();
+
+        let storage = Box::new(MemoryStorage::<Bls12_381>::new());
+        let mut client = StateSyncClient::new(server.root(), storage, 2).unwrap();
+
+        // More accounts than requested, although correctly proven
+        let mut request = client.next_request().unwrap();
+        request.max_accounts = 4;
+        let chunk = server.serve_chunk(&request).unwrap();
+        assert!(client.verify_chunk(&chunk).is_err());
+
+        let chunk = server.serve_chunk(&client.next_request().unwrap()).unwrap();
+        client.apply_chunk(&chunk).unwrap();
+
+        // A chunk ending before its start would move sync backwards
+        let mut stalled = server.serve_chunk(&client.next_request().unwrap()).unwrap();
+        stalled.end = chunk.end.clone();
+        stalled.accounts.clear();
+        assert!(client.apply_chunk(&stalled).is_err());
+        assert_eq!(client.progress().chunks_applied, 1);
+
+        client.sync(&mut LocalTransport::new(&server)).unwrap();
+        assert!(client.finalize().is_ok());
+    }
+
+    #[test]
+    fn test_resume_sync() {
+        let state = setup_test_state(8);
+        let server = StateSyncServer::new(&state).unwrap();
+
+        let storage = Box::new(MemoryStorage::<Bls12_381>::new());
+        let mut client = StateSyncClient::new(server.root(), storage, 2).unwrap();
+        let chunk = server.serve_chunk(&client.next_request().unwrap()).unwrap();
+        client.apply_chunk(&chunk).unwrap();
+
+        // Restart from saved progress with the same storage
+        let progress = client.progress().clone();
+        let storage = client.storage;
+        let mut client = StateSyncClient::resume(server.root(), progress, storage, 2);
+        client.sync(&mut LocalTransport::new(&server)).unwrap();
+
+        assert!(client.progress().is_complete());
+        assert_eq!(client.progress().accounts_synced, 8);
+        assert!(client.finalize().is_ok());
+    }
+}
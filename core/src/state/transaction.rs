@@ -1,10 +1,19 @@
 use super::{AccountId, StateError};
+use super::computation::{ComputationProof, Difficulty};
+use super::merkle_tree::MerkleTree;
+pub use super::logs::Log;
+use crate::crypto::hash_to_curve::{self, DomainTag};
 use crate::crypto::signature::{Signature, SignatureScheme};
 use ark_ec::PairingEngine;
-use ark_ff::Field;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::CanonicalSerialize;
 use std::collections::HashMap;
 
+/// Version of the transaction wire format
+pub const TX_WIRE_VERSION: u8 = 1;
+
+/// Chain ID used when none is set explicitly
+pub const DEFAULT_CHAIN_ID: u64 = 1;
+
 /// Transaction types
 #[derive(Clone, Debug, PartialEq)]
 pub enum TransactionType {
@@ -20,12 +29,50 @@ pub enum TransactionType {
     UpdateAccount,
 }
 
+impl TransactionType {
+    /// Wire byte of transaction type
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            TransactionType::Transfer => 0,
+            TransactionType::Deploy => 1,
+            TransactionType::Call => 2,
+            TransactionType::CreateAccount => 3,
+            TransactionType::UpdateAccount => 4,
+        }
+    }
+
+    /// Parse transaction type from wire byte
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(TransactionType::Transfer),
+            1 => Some(TransactionType::Deploy),
+            2 => Some(TransactionType::Call),
+            3 => Some(TransactionType::CreateAccount),
+            4 => Some(TransactionType::UpdateAccount),
+            _ => None,
+        }
+    }
+}
+
+/// Signature by one signer of a weighted account policy
+#[derive(Clone, Debug)]
+pub struct SignerSignature<E: PairingEngine> {
+    /// Signer index in the account policy
+    pub signer: u32,
+
+    /// Signature over the signing encoding
+    pub signature: Signature<E>,
+}
+
 /// Transaction data
 #[derive(Clone, Debug)]
 pub struct Transaction<E: PairingEngine> {
     /// Transaction type
     pub tx_type: TransactionType,
     
+    /// Chain the transaction is valid on
+    pub chain_id: u64,
+    
     /// Transaction nonce
     pub nonce: u64,
     
@@ -50,6 +97,9 @@ pub struct Transaction<E: PairingEngine> {
     /// Transaction signature
     pub signature: Option<Signature<E>>,
     
+    /// Signer signatures for weighted account policies, ordered by signer
+    pub cosignatures: Vec<SignerSignature<E>>,
+    
     /// Proof of computation
     pub computation_proof: Option<Vec<u8>>,
 }
@@ -66,6 +116,7 @@ impl<E: PairingEngine> Transaction<E> {
     ) -> Self {
         Self {
             tx_type,
+            chain_id: DEFAULT_CHAIN_ID,
             nonce,
             from,
             to,
@@ -74,10 +125,17 @@ impl<E: PairingEngine> Transaction<E> {
             gas_limit: 0, // Zero-fee structure
             data,
             signature: None,
+            cosignatures: Vec::new(),
             computation_proof: None,
         }
     }
 
+    /// Set chain ID
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
     /// Sign transaction
     pub fn sign(&mut self, signature_scheme: &SignatureScheme<E>, private_key: &E::Fr) -> Result<(), StateError> {
         let message = self.encode_for_signing()?;
@@ -88,6 +146,25 @@ impl<E: PairingEngine> Transaction<E> {
         Ok(())
     }
 
+    /// Add signature by signer of a weighted account policy
+    pub fn cosign(
+        &mut self,
+        signature_scheme: &SignatureScheme<E>,
+        signer: u32,
+        private_key: &E::Fr,
+    ) -> Result<(), StateError> {
+        let message = self.encode_for_signing()?;
+        let signature = signature_scheme.sign(&message, private_key)
+            .map_err(|e| StateError::ValidationError(e.to_string()))?;
+
+        let cosignature = SignerSignature { signer, signature };
+        match self.cosignatures.binary_search_by_key(&signer, |c| c.signer) {
+            Ok(index) => self.cosignatures[index] = cosignature,
+            Err(index) => self.cosignatures.insert(index, cosignature),
+        }
+        Ok(())
+    }
+
     /// Verify transaction signature
     pub fn verify_signature(&self, public_key: &E::G1Projective) -> Result<bool, StateError> {
         let signature = self.signature.as_ref()
@@ -106,64 +183,266 @@ impl<E: PairingEngine> Transaction<E> {
         self.computation_proof = Some(proof);
     }
 
-    /// Verify proof of computation
-    pub fn verify_computation(&self) -> Result<bool, StateError> {
+    /// Solve the computation puzzle and attach the proof
+    pub fn solve_computation(&mut self, difficulty: &Difficulty) -> Result<(), StateError> {
+        let proof = ComputationProof::generate(self, difficulty)?;
+        self.computation_proof = Some(proof.encode());
+        Ok(())
+    }
+
+    /// Verify proof of computation against the difficulty
+    pub fn verify_computation(&self, difficulty: &Difficulty) -> Result<bool, StateError> {
         let proof = self.computation_proof.as_ref()
             .ok_or_else(|| StateError::ValidationError("Missing computation proof".to_string()))?;
-        
-        // Implement proof verification logic here
-        // This is a placeholder for the actual verification
-        Ok(!proof.is_empty())
+
+        match ComputationProof::decode(proof) {
+            Ok(proof) => proof.verify(self, difficulty),
+            Err(_) => Ok(false),
+        }
     }
 
-    /// Calculate transaction hash
+    /// Calculate transaction hash over the signed fields
     pub fn hash(&self) -> Result<E::Fr, StateError> {
-        let encoded = self.encode_for_signing()?;
-        let mut hasher = sha3::Sha3_256::new();
-        hasher.update(&encoded);
-        let hash = hasher.finalize();
-        
-        E::Fr::from_random_bytes(&hash)
-            .ok_or_else(|| StateError::ValidationError("Failed to generate hash".to_string()))
+        hash_to_curve::hash_to_scalar(&self.encode_for_signing()?, DomainTag::TRANSACTION_HASH)
+            .map_err(|e| StateError::SerializationError(e.to_string()))
     }
 
     /// Encode transaction for signing
-    fn encode_for_signing(&self) -> Result<Vec<u8>, StateError> {
+    ///
+    /// This is the canonical encoding without the signature and computation proof.
+    pub fn encode_for_signing(&self) -> Result<Vec<u8>, StateError> {
         let mut bytes = Vec::new();
-        
-        // Encode transaction type
-        (self.tx_type.clone() as u8).serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        
-        // Encode basic fields
-        self.nonce.serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        self.from.0.serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        
-        // Encode optional receiver
-        if let Some(to) = &self.to {
-            true.serialize(&mut bytes)
-                .map_err(|e| StateError::SerializationError(e.to_string()))?;
-            to.0.serialize(&mut bytes)
-                .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        } else {
-            false.serialize(&mut bytes)
-                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+
+        bytes.push(TX_WIRE_VERSION);
+        bytes.extend_from_slice(&self.chain_id.to_be_bytes());
+        bytes.push(self.tx_type.to_byte());
+        bytes.extend_from_slice(&self.nonce.to_be_bytes());
+
+        // Encode accounts
+        if self.from.0.is_empty() {
+            return Err(StateError::SerializationError("Sender is empty".to_string()));
         }
-        
+        write_bytes(&mut bytes, &self.from.0)?;
+        match &self.to {
+            Some(to) if to.0.is_empty() => {
+                return Err(StateError::SerializationError("Receiver is empty".to_string()));
+            }
+            Some(to) => {
+                bytes.push(1);
+                write_bytes(&mut bytes, &to.0)?;
+            }
+            None => bytes.push(0),
+        }
+
         // Encode remaining fields
-        self.value.serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        self.gas_price.serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        self.gas_limit.serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        self.data.serialize(&mut bytes)
-            .map_err(|e| StateError::SerializationError(e.to_string()))?;
-        
+        bytes.extend_from_slice(&self.value.to_be_bytes());
+        bytes.extend_from_slice(&self.gas_price.to_be_bytes());
+        bytes.extend_from_slice(&self.gas_limit.to_be_bytes());
+        write_bytes(&mut bytes, &self.data)?;
+
+        Ok(bytes)
+    }
+
+    /// Encode transaction in the canonical wire format
+    ///
+    /// Layout, integers big-endian, byte strings prefixed with a `u32` length:
+    /// `version | chain_id | type | nonce | from | to? | value | gas_price | gas_limit | data | signature? | proof?`.
+    /// Optional fields are prefixed with a presence byte, a signature is the
+    /// compressed `r` point followed by `s`. Signer signatures use presence
+    /// byte 2 followed by a `u32` count and `(u32 signer, signature)` pairs in
+    /// strictly increasing signer order.
+    pub fn encode(&self) -> Result<Vec<u8>, StateError> {
+        let mut bytes = self.encode_for_signing()?;
+
+        match (&self.signature, self.cosignatures.is_empty()) {
+            (Some(_), false) => {
+                return Err(StateError::SerializationError(
+                    "Transaction has both a signature and signer signatures".to_string()
+                ));
+            }
+            (Some(signature), true) => {
+                bytes.push(1);
+                write_signature(&mut bytes, signature)?;
+            }
+            (None, false) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&(self.cosignatures.len() as u32).to_be_bytes());
+                for (i, cosignature) in self.cosignatures.iter().enumerate() {
+                    if i > 0 && self.cosignatures[i - 1].signer >= cosignature.signer {
+                        return Err(StateError::SerializationError(
+                            "Signer signatures are not ordered".to_string()
+                        ));
+                    }
+                    bytes.extend_from_slice(&cosignature.signer.to_be_bytes());
+                    write_signature(&mut bytes, &cosignature.signature)?;
+                }
+            }
+            (None, true) => bytes.push(0),
+        }
+
+        match &self.computation_proof {
+            Some(proof) => {
+                bytes.push(1);
+                write_bytes(&mut bytes, proof)?;
+            }
+            None => bytes.push(0),
+        }
+
         Ok(bytes)
     }
+
+    /// Decode transaction from the canonical wire format
+    ///
+    /// Only the canonical encoding is accepted: `decode(bytes)?.encode()? == bytes`.
+    pub fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+        let mut reader = bytes;
+
+        let version = read_u8(&mut reader)?;
+        if version != TX_WIRE_VERSION {
+            return Err(StateError::SerializationError(format!(
+                "Unsupported transaction version {}", version
+            )));
+        }
+        let chain_id = read_u64(&mut reader)?;
+        let type_byte = read_u8(&mut reader)?;
+        let tx_type = TransactionType::from_byte(type_byte)
+            .ok_or_else(|| StateError::SerializationError(format!(
+                "Unknown transaction type {}", type_byte
+            )))?;
+        let nonce = read_u64(&mut reader)?;
+
+        // Decode accounts
+        let from = read_vec(&mut reader)?;
+        if from.is_empty() {
+            return Err(StateError::SerializationError("Sender is empty".to_string()));
+        }
+        let to = if read_flag(&mut reader)? {
+            let to = read_vec(&mut reader)?;
+            if to.is_empty() {
+                return Err(StateError::SerializationError("Receiver is empty".to_string()));
+            }
+            Some(AccountId(to))
+        } else {
+            None
+        };
+
+        // Decode remaining fields
+        let value = read_u64(&mut reader)?;
+        let gas_price = read_u64(&mut reader)?;
+        let gas_limit = read_u64(&mut reader)?;
+        let data = read_vec(&mut reader)?;
+
+        let mut signature = None;
+        let mut cosignatures = Vec::new();
+        match read_u8(&mut reader)? {
+            0 => {}
+            1 => signature = Some(read_signature::<E>(&mut reader)?),
+            2 => {
+                let count = read_u32(&mut reader)?;
+                if count == 0 {
+                    return Err(StateError::SerializationError("No signer signatures".to_string()));
+                }
+                for _ in 0..count {
+                    let signer = read_u32(&mut reader)?;
+                    if cosignatures.last().map_or(false, |last: &SignerSignature<E>| last.signer >= signer) {
+                        return Err(StateError::SerializationError(
+                            "Signer signatures are not ordered".to_string()
+                        ));
+                    }
+                    let signature = read_signature::<E>(&mut reader)?;
+                    cosignatures.push(SignerSignature { signer, signature });
+                }
+            }
+            flag => {
+                return Err(StateError::SerializationError(format!("Invalid signature flag {}", flag)));
+            }
+        }
+        let computation_proof = if read_flag(&mut reader)? {
+            Some(read_vec(&mut reader)?)
+        } else {
+            None
+        };
+
+        if !reader.is_empty() {
+            return Err(StateError::SerializationError(format!(
+                "{} trailing bytes after transaction", reader.len()
+            )));
+        }
+
+        Ok(Self {
+            tx_type,
+            chain_id,
+            nonce,
+            from: AccountId(from),
+            to,
+            value,
+            gas_price,
+            gas_limit,
+            data,
+            signature,
+            cosignatures,
+            computation_proof,
+        })
+    }
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, value: &[u8]) -> Result<(), StateError> {
+    let len = u32::try_from(value.len())
+        .map_err(|_| StateError::SerializationError("Field too long".to_string()))?;
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(value);
+    Ok(())
+}
+
+fn write_signature<E: PairingEngine>(bytes: &mut Vec<u8>, signature: &Signature<E>) -> Result<(), StateError> {
+    let encoded = signature.to_bytes()
+        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+    bytes.extend_from_slice(&encoded);
+    Ok(())
+}
+
+fn read_bytes<'a>(reader: &mut &'a [u8], len: usize) -> Result<&'a [u8], StateError> {
+    if reader.len() < len {
+        return Err(StateError::SerializationError("Transaction is truncated".to_string()));
+    }
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    Ok(bytes)
+}
+
+fn read_u8(reader: &mut &[u8]) -> Result<u8, StateError> {
+    Ok(read_bytes(reader, 1)?[0])
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, StateError> {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(read_bytes(reader, 4)?);
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn read_u64(reader: &mut &[u8]) -> Result<u64, StateError> {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(read_bytes(reader, 8)?);
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn read_flag(reader: &mut &[u8]) -> Result<bool, StateError> {
+    match read_u8(reader)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        flag => Err(StateError::SerializationError(format!("Invalid presence flag {}", flag))),
+    }
+}
+
+fn read_vec(reader: &mut &[u8]) -> Result<Vec<u8>, StateError> {
+    let len = read_u32(reader)? as usize;
+    Ok(read_bytes(reader, len)?.to_vec())
+}
+
+fn read_signature<E: PairingEngine>(reader: &mut &[u8]) -> Result<Signature<E>, StateError> {
+    let bytes = read_bytes(reader, Signature::<E>::encoded_size())?;
+    Signature::from_bytes(bytes)
+        .map_err(|e| StateError::SerializationError(format!("Invalid signature: {}", e)))
 }
 
 /// Transaction receipt
@@ -191,23 +470,78 @@ pub struct TransactionReceipt<E: PairingEngine> {
     pub state_changes: HashMap<AccountId, E::Fr>,
 }
 
-/// Log entry
-#[derive(Clone, Debug)]
-pub struct Log<E: PairingEngine> {
-    /// Contract address
-    pub address: AccountId,
-    
-    /// Topics
-    pub topics: Vec<E::Fr>,
-    
-    /// Log data
-    pub data: Vec<u8>,
+/// Receipt status of a successfully executed transaction
+pub const RECEIPT_STATUS_SUCCESS: u8 = 1;
+
+/// Receipt status of a transaction that failed during execution
+pub const RECEIPT_STATUS_FAILURE: u8 = 0;
+
+impl<E: PairingEngine> TransactionReceipt<E> {
+    /// Check if the transaction executed successfully
+    pub fn is_success(&self) -> bool {
+        self.status == RECEIPT_STATUS_SUCCESS
+    }
+
+    /// Encode receipt for commitment
+    pub fn encode(&self) -> Result<Vec<u8>, StateError> {
+        let mut bytes = Vec::new();
+        
+        self.hash.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.block_number.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.tx_index.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.computation_used.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        self.status.serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        
+        // Encode logs
+        (self.logs.len() as u64).serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        for log in &self.logs {
+            log.address.0.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            log.topics.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            log.data.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        }
+        
+        // Encode state changes in account order
+        let mut changes: Vec<_> = self.state_changes.iter().collect();
+        changes.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+        (changes.len() as u64).serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        for (id, account_hash) in changes {
+            id.0.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            account_hash.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        }
+        
+        Ok(bytes)
+    }
+
+    /// Calculate Merkle root over receipts keyed by transaction index
+    pub fn root(receipts: &[TransactionReceipt<E>]) -> Result<E::Fr, StateError> {
+        let mut merkle_tree = MerkleTree::<E>::new(256);
+        
+        for receipt in receipts {
+            merkle_tree.update(&receipt.tx_index.to_be_bytes(), &receipt.encode()?)?;
+        }
+        
+        Ok(merkle_tree.root())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use ark_bls12_381::Bls12_381;
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::PrimeField;
     use rand::thread_rng;
 
     #[test]
@@ -282,8 +616,140 @@ mod tests {
             vec![],
         );
         
-        let proof = vec![1, 2, 3, 4];
-        tx.add_computation_proof(proof);
-        assert!(tx.verify_computation().unwrap());
+        let difficulty = Difficulty::default();
+        assert!(tx.verify_computation(&difficulty).is_err());
+
+        tx.add_computation_proof(vec![1, 2, 3, 4]);
+        assert!(!tx.verify_computation(&difficulty).unwrap());
+
+        tx.solve_computation(&difficulty).unwrap();
+        assert!(tx.verify_computation(&difficulty).unwrap());
+
+        // The puzzle is bound to the transaction hash, so the stale solution
+        // is checked against a new challenge once the transaction changes
+        let proof = ComputationProof::decode(tx.computation_proof.as_ref().unwrap()).unwrap();
+        let hash = tx.hash().unwrap();
+        tx.value += 1;
+        assert_ne!(tx.hash().unwrap(), hash);
+
+        // A difficulty above the work the stale solution achieves rejects it
+        let stale_bits = proof.work_bits(&tx).unwrap();
+        assert!(!tx.verify_computation(&Difficulty::new(stale_bits + 1)).unwrap());
+
+        tx.solve_computation(&difficulty).unwrap();
+        assert!(tx.verify_computation(&difficulty).unwrap());
+    }
+
+    /// Vectors shared with the network layer
+    const WIRE_VECTORS: &str = include_str!("../../../tools/tests/vectors/transaction.json");
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_wire_roundtrip() {
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        let mut rng = thread_rng();
+        let private_key = Bls12_381::Fr::rand(&mut rng);
+
+        let mut tx = Transaction::<Bls12_381>::new(
+            TransactionType::Call,
+            AccountId(vec![1, 2, 3]),
+            Some(AccountId(vec![4, 5, 6])),
+            100,
+            7,
+            vec![9, 9],
+        ).with_chain_id(42);
+        tx.sign(&signature_scheme, &private_key).unwrap();
+        tx.add_computation_proof(vec![1, 2, 3, 4]);
+
+        let encoded = tx.encode().unwrap();
+        let decoded = Transaction::<Bls12_381>::decode(&encoded).unwrap();
+        assert_eq!(decoded.chain_id, 42);
+        assert_eq!(decoded.signature.as_ref().unwrap().s, tx.signature.as_ref().unwrap().s);
+        assert_eq!(decoded.hash().unwrap(), tx.hash().unwrap());
+        assert_eq!(decoded.encode().unwrap(), encoded);
+
+        // Chain ID is covered by the signature
+        let g = Bls12_381::G1Projective::prime_subgroup_generator();
+        let public_key = g.mul(private_key.into_repr());
+        let mut replayed = decoded.clone();
+        replayed.chain_id = 43;
+        assert!(!replayed.verify_signature(&public_key).unwrap());
+    }
+
+    #[test]
+    fn test_cosigned_wire_roundtrip() {
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        let mut tx = Transaction::<Bls12_381>::new(
+            TransactionType::Transfer,
+            AccountId(vec![1]),
+            Some(AccountId(vec![2])),
+            10,
+            0,
+            vec![],
+        );
+        tx.cosign(&signature_scheme, 3, &Bls12_381::Fr::from(3u64)).unwrap();
+        tx.cosign(&signature_scheme, 1, &Bls12_381::Fr::from(1u64)).unwrap();
+
+        let decoded = Transaction::<Bls12_381>::decode(&tx.encode().unwrap()).unwrap();
+        assert_eq!(decoded.cosignatures.iter().map(|c| c.signer).collect::<Vec<_>>(), vec![1, 3]);
+        assert!(decoded.signature.is_none());
+
+        // A transaction carries either kind of signature
+        tx.sign(&signature_scheme, &Bls12_381::Fr::from(1u64)).unwrap();
+        assert!(tx.encode().is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_wire_vectors() {
+        let vectors: serde_json::Value = serde_json::from_str(WIRE_VECTORS).unwrap();
+
+        for vector in vectors["valid"].as_array().unwrap() {
+            let name = vector["name"].as_str().unwrap();
+            let encoded = from_hex(vector["encoded"].as_str().unwrap());
+            let tx = Transaction::<Bls12_381>::decode(&encoded)
+                .unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+            assert_eq!(tx.chain_id, vector["chain_id"].as_u64().unwrap(), "{}", name);
+            assert_eq!(tx.tx_type.to_byte() as u64, vector["type"].as_u64().unwrap(), "{}", name);
+            assert_eq!(tx.nonce, vector["nonce"].as_u64().unwrap(), "{}", name);
+            assert_eq!(tx.from.0, from_hex(vector["from"].as_str().unwrap()), "{}", name);
+            assert_eq!(tx.to.map(|to| to.0), vector["to"].as_str().map(from_hex), "{}", name);
+            assert_eq!(tx.value, vector["value"].as_u64().unwrap(), "{}", name);
+            assert_eq!(tx.gas_limit, vector["gas_limit"].as_u64().unwrap(), "{}", name);
+            assert_eq!(tx.data, from_hex(vector["data"].as_str().unwrap()), "{}", name);
+            assert_eq!(tx.signature.is_some(), !vector["signature"].is_null(), "{}", name);
+            let signers: Vec<u64> = vector["cosignatures"].as_array().unwrap()
+                .iter()
+                .map(|c| c["signer"].as_u64().unwrap())
+                .collect();
+            assert_eq!(
+                tx.cosignatures.iter().map(|c| c.signer as u64).collect::<Vec<_>>(),
+                signers,
+                "{}", name
+            );
+            assert_eq!(
+                tx.computation_proof.clone(),
+                vector["computation_proof"].as_str().map(from_hex),
+                "{}", name
+            );
+
+            let hash = from_hex(vector["signing_hash"].as_str().unwrap());
+            assert_eq!(tx.hash().unwrap(), Bls12_381::Fr::from_le_bytes_mod_order(&hash), "{}", name);
+            assert_eq!(tx.encode().unwrap(), encoded, "{}", name);
+        }
+
+        for vector in vectors["invalid"].as_array().unwrap() {
+            let encoded = from_hex(vector["encoded"].as_str().unwrap());
+            assert!(
+                Transaction::<Bls12_381>::decode(&encoded).is_err(),
+                "{} decoded", vector["name"]
+            );
+        }
+    }
+}
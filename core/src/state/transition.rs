@@ -1,8 +1,21 @@
 use super::{State, Account, AccountId, Transaction, TransactionType, StateError};
-use crate::crypto::signature::SignatureScheme;
+use super::overlay::StateOverlay;
+use super::admission::AdmissionPolicy;
+use super::auth::{AuthPolicy, AuthorizationReport};
+use super::code;
+use super::computation::{self, Difficulty, DifficultyAdjuster};
+use super::payload::{self, CreateAccountPayload, UpdateAccountPayload};
+use super::transaction::{
+    TransactionReceipt, DEFAULT_CHAIN_ID, RECEIPT_STATUS_FAILURE, RECEIPT_STATUS_SUCCESS,
+};
+use super::logs::{Log, LogsBloom};
+use super::vm::{CallContext, ContractVm, ExecutionOutcome, VmConfig};
+use crate::crypto::signature::{BatchItem, SignatureScheme};
+use crate::crypto::hash_to_curve::DomainTag;
+use crate::crypto::utils::CryptoUtils;
 use ark_ec::PairingEngine;
 use ark_ff::Field;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// State transition result
 #[derive(Debug)]
@@ -18,22 +31,18 @@ pub struct TransitionResult<E: PairingEngine> {
     
     /// Logs generated
     pub logs: Vec<Log<E>>,
-}
-
-/// Transaction log
-#[derive(Debug, Clone)]
-pub struct Log<E: PairingEngine> {
-    /// Event topic
-    pub topic: E::Fr,
     
-    /// Event data
-    pub data: Vec<u8>,
+    /// Receipts of executed transactions
+    pub receipts: Vec<TransactionReceipt<E>>,
+    
+    /// Receipt root
+    pub receipt_root: E::Fr,
     
-    /// Block number
-    pub block_number: u64,
+    /// Bloom over all generated logs
+    pub logs_bloom: LogsBloom,
     
-    /// Transaction hash
-    pub transaction_hash: E::Fr,
+    /// Contract code stored, by code hash
+    pub new_code: HashMap<E::Fr, Vec<u8>>,
 }
 
 /// State transition handler
@@ -41,8 +50,17 @@ pub struct StateTransition<E: PairingEngine> {
     /// Signature scheme
     signature_scheme: SignatureScheme<E>,
     
-    /// Minimum computation requirement
-    min_computation: u64,
+    /// Rules deriving proof of computation difficulty from recent block load
+    difficulty_adjuster: DifficultyAdjuster,
+    
+    /// Computational credit rules
+    admission: AdmissionPolicy,
+    
+    /// Contract VM
+    vm: ContractVm<E>,
+    
+    /// Chain ID transactions must carry
+    chain_id: u64,
 }
 
 impl<E: PairingEngine> StateTransition<E> {
@@ -51,31 +69,124 @@ impl<E: PairingEngine> StateTransition<E> {
         Ok(Self {
             signature_scheme: SignatureScheme::new(128)
                 .map_err(|e| StateError::ValidationError(e.to_string()))?,
-            min_computation: 1000, // Minimum required computation
+            difficulty_adjuster: DifficultyAdjuster::default(),
+            admission: AdmissionPolicy::default(),
+            vm: ContractVm::new(VmConfig::default()),
+            chain_id: DEFAULT_CHAIN_ID,
         })
     }
 
+    /// Set chain ID transactions must carry
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Pin proof of computation difficulty regardless of block load
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        self.with_difficulty_adjuster(DifficultyAdjuster::fixed(difficulty))
+    }
+
+    /// Set rules adjusting proof of computation difficulty to block load
+    pub fn with_difficulty_adjuster(mut self, adjuster: DifficultyAdjuster) -> Self {
+        self.difficulty_adjuster = adjuster;
+        self
+    }
+
+    /// Difficulty transactions of a block must meet
+    ///
+    /// `recent_loads` is the computation used by the preceding blocks as
+    /// recorded on chain, oldest first.
+    pub fn difficulty(&self, recent_loads: &[u64]) -> Difficulty {
+        self.difficulty_adjuster.difficulty(recent_loads)
+    }
+
+    /// Set computational credit rules
+    pub fn with_admission_policy(mut self, admission: AdmissionPolicy) -> Self {
+        self.admission = admission;
+        self
+    }
+
+    /// Computational credit rules transactions are admitted by
+    pub fn admission_policy(&self) -> &AdmissionPolicy {
+        &self.admission
+    }
+
     /// Apply state transition
     pub fn apply_transaction(
         &self,
         state: &State<E>,
         transaction: &Transaction<E>,
         block_number: u64,
+        difficulty: &Difficulty,
+    ) -> Result<TransitionResult<E>, StateError> {
+        let mut overlay = StateOverlay::new(state);
+        self.apply_transaction_to(&mut overlay, transaction, block_number, difficulty)
+    }
+
+    /// Apply transaction on top of an overlay, reverting its writes on failure
+    pub fn apply_transaction_to(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        transaction: &Transaction<E>,
+        block_number: u64,
+        difficulty: &Difficulty,
+    ) -> Result<TransitionResult<E>, StateError> {
+        let mut result = self.apply_checked(overlay, transaction, block_number, difficulty, false)?;
+        result.new_root = overlay.calculate_root()?;
+        Ok(result)
+    }
+
+    /// Apply transaction, skipping signature checks already done for the block
+    ///
+    /// The state root is left unset, block execution calculates it once.
+    pub(super) fn apply_checked(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        transaction: &Transaction<E>,
+        block_number: u64,
+        difficulty: &Difficulty,
+        signatures_verified: bool,
+    ) -> Result<TransitionResult<E>, StateError> {
+        let checkpoint = overlay.checkpoint();
+
+        match self.execute_transaction(overlay, transaction, block_number, difficulty, signatures_verified) {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                overlay.revert_to(checkpoint)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Validate, execute and write transaction into overlay
+    fn execute_transaction(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        transaction: &Transaction<E>,
+        block_number: u64,
+        difficulty: &Difficulty,
+        signatures_verified: bool,
     ) -> Result<TransitionResult<E>, StateError> {
         // Validate transaction
-        self.validate_transaction(state, transaction)?;
+        self.validate_transaction(overlay, transaction, signatures_verified, block_number, difficulty)?;
         
         // Process transaction based on type
-        let result = match transaction.tx_type {
-            TransactionType::Transfer => self.process_transfer(state, transaction)?,
-            TransactionType::Deploy => self.process_deploy(state, transaction)?,
-            TransactionType::Call => self.process_call(state, transaction)?,
-            TransactionType::CreateAccount => self.process_create_account(state, transaction)?,
-            TransactionType::UpdateAccount => self.process_update_account(state, transaction)?,
+        let mut result = match transaction.tx_type {
+            TransactionType::Transfer => self.process_transfer(overlay, transaction)?,
+            TransactionType::Deploy => self.process_deploy(overlay, transaction, block_number)?,
+            TransactionType::Call => self.process_call(overlay, transaction, block_number)?,
+            TransactionType::CreateAccount => self.process_create_account(overlay, transaction, block_number)?,
+            TransactionType::UpdateAccount => self.process_update_account(overlay, transaction)?,
         };
 
-        // Calculate new state root
-        let new_root = state.calculate_root(&result.modified_accounts)?;
+        // Charge the sender's computational credit
+        if let Some(sender) = result.modified_accounts.get_mut(&transaction.from) {
+            self.admission.charge(sender, result.computation_used, block_number);
+        }
+
+        // Write modified accounts
+        overlay.set_accounts(result.modified_accounts.clone());
 
         // Create logs
         let transaction_hash = transaction.hash()?;
@@ -85,20 +196,112 @@ impl<E: PairingEngine> StateTransition<E> {
             log.transaction_hash = transaction_hash;
         });
 
+        // Create receipt
+        let receipt = self.build_receipt(
+            transaction,
+            0,
+            block_number,
+            result.computation_used,
+            RECEIPT_STATUS_SUCCESS,
+            &logs,
+            &result.modified_accounts,
+        )?;
+        let receipts = vec![receipt];
+        let receipt_root = TransactionReceipt::root(&receipts)?;
+        let logs_bloom = LogsBloom::from_logs(&logs)?;
+
         Ok(TransitionResult {
-            new_root,
+            new_root: E::Fr::zero(), // Calculated by the caller
             modified_accounts: result.modified_accounts,
             computation_used: result.computation_used,
             logs,
+            receipts,
+            receipt_root,
+            logs_bloom,
+            new_code: result.new_code,
+        })
+    }
+
+    /// Record an execution failure: the sender's nonce and computational
+    /// credit are consumed but no other effect of the transaction is kept
+    ///
+    /// Computation metered by the VM before a revert is charged on top of
+    /// the base cost.
+    pub(super) fn apply_failed_transaction(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        transaction: &Transaction<E>,
+        error: &StateError,
+        tx_index: u32,
+        block_number: u64,
+    ) -> Result<TransactionReceipt<E>, StateError> {
+        let computation_used = self.calculate_computation_used(transaction)?
+            .saturating_add(error.vm_computation_used());
+        let mut sender = overlay.get_account(&transaction.from)
+            .ok_or_else(|| StateError::ValidationError("Sender account not found".to_string()))?;
+        sender.increment_nonce();
+        self.admission.charge(&mut sender, computation_used, block_number);
+
+        let mut touched = HashMap::new();
+        touched.insert(sender.id.clone(), sender.clone());
+        overlay.set_account(sender);
+
+        self.build_receipt(
+            transaction,
+            tx_index,
+            block_number,
+            computation_used,
+            RECEIPT_STATUS_FAILURE,
+            &[],
+            &touched,
+        )
+    }
+
+    /// Build receipt for an executed transaction
+    fn build_receipt(
+        &self,
+        transaction: &Transaction<E>,
+        tx_index: u32,
+        block_number: u64,
+        computation_used: u64,
+        status: u8,
+        logs: &[Log<E>],
+        touched_accounts: &HashMap<AccountId, Account<E>>,
+    ) -> Result<TransactionReceipt<E>, StateError> {
+        let mut state_changes = HashMap::new();
+        for (id, account) in touched_accounts {
+            let account_hash = CryptoUtils::hash_to_field(&account.serialize()?, DomainTag::ACCOUNT_HASH)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            state_changes.insert(id.clone(), account_hash);
+        }
+
+        Ok(TransactionReceipt {
+            hash: transaction.hash()?,
+            block_number,
+            tx_index,
+            computation_used,
+            status,
+            logs: logs.to_vec(),
+            state_changes,
         })
     }
 
     /// Validate transaction
     fn validate_transaction(
         &self,
-        state: &State<E>,
+        state: &StateOverlay<E>,
         transaction: &Transaction<E>,
+        signatures_verified: bool,
+        block_number: u64,
+        difficulty: &Difficulty,
     ) -> Result<(), StateError> {
+        // Reject transactions signed for another chain
+        if transaction.chain_id != self.chain_id {
+            return Err(StateError::ValidationError(format!(
+                "Transaction is for chain {}, expected {}", transaction.chain_id, self.chain_id
+            )));
+        }
+
         // Verify sender exists
         let sender = state.get_account(&transaction.from)
             .ok_or_else(|| StateError::ValidationError("Sender account not found".to_string()))?;
@@ -108,28 +311,71 @@ impl<E: PairingEngine> StateTransition<E> {
             return Err(StateError::ValidationError("Invalid nonce".to_string()));
         }
 
-        // Verify signature
-        if !transaction.verify_signature(&sender.public_key)? {
-            return Err(StateError::ValidationError("Invalid signature".to_string()));
+        // Verify signatures and computation proof
+        if !signatures_verified {
+            self.validate_signatures(&sender, transaction)?;
         }
+        self.validate_computation(transaction, difficulty)?;
 
-        // Verify computation proof
-        if !transaction.verify_computation()? {
-            return Err(StateError::ValidationError("Invalid computation proof".to_string()));
-        }
+        // Verify computational credit
+        self.admission.check_credit(&sender, transaction, block_number)?;
 
         // Verify sufficient balance
         if transaction.value > sender.balance {
-            return Err(StateError::ValidationError("Insufficient balance".to_string()));
+            return Err(StateError::ExecutionError("Insufficient balance".to_string()));
+        }
+
+        // Verify deployed code size
+        if transaction.tx_type == TransactionType::Deploy {
+            code::check_code_size(&transaction.data)?;
         }
 
         Ok(())
     }
 
+    /// Validate signatures against the sender's policy
+    fn validate_signatures(
+        &self,
+        sender: &Account<E>,
+        transaction: &Transaction<E>,
+    ) -> Result<(), StateError> {
+        let report = sender.auth_policy.authorize(
+            &self.signature_scheme,
+            &sender.public_key,
+            &transaction.encode_for_signing()?,
+            transaction.signature.as_ref(),
+            &transaction.cosignatures,
+        )?;
+        Self::ensure_authorized(sender, &report)
+    }
+
+    /// Reject transaction unless the report satisfies the sender's policy
+    fn ensure_authorized(sender: &Account<E>, report: &AuthorizationReport) -> Result<(), StateError> {
+        if report.is_authorized() {
+            return Ok(());
+        }
+
+        Err(StateError::ValidationError(match &sender.auth_policy {
+            AuthPolicy::Weighted { .. } => format!(
+                "Insufficient signatures: weight {} of {}, missing signers {:?}, invalid signers {:?}",
+                report.signed_weight, report.threshold, report.missing, report.invalid
+            ),
+            _ => "Invalid signature".to_string(),
+        }))
+    }
+
+    /// Validate proof of computation
+    fn validate_computation(&self, transaction: &Transaction<E>, difficulty: &Difficulty) -> Result<(), StateError> {
+        if !transaction.verify_computation(difficulty)? {
+            return Err(StateError::ValidationError("Invalid computation proof".to_string()));
+        }
+        Ok(())
+    }
+
     /// Process transfer transaction
     fn process_transfer(
         &self,
-        state: &State<E>,
+        state: &StateOverlay<E>,
         transaction: &Transaction<E>,
     ) -> Result<TransitionResult<E>, StateError> {
         let mut modified_accounts = HashMap::new();
@@ -140,9 +386,9 @@ impl<E: PairingEngine> StateTransition<E> {
 
         // Get receiver account
         let receiver_id = transaction.to.as_ref()
-            .ok_or_else(|| StateError::ValidationError("Receiver not specified".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Receiver not specified".to_string()))?;
         let mut receiver = state.get_account(receiver_id)
-            .ok_or_else(|| StateError::ValidationError("Receiver account not found".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Receiver account not found".to_string()))?;
 
         // Update balances
         sender.update_balance(-(transaction.value as i64))?;
@@ -160,14 +406,19 @@ impl<E: PairingEngine> StateTransition<E> {
             modified_accounts,
             computation_used: self.calculate_computation_used(transaction)?,
             logs: Vec::new(),
+            receipts: Vec::new(),
+            receipt_root: E::Fr::zero(),
+            logs_bloom: LogsBloom::new(),
+            new_code: HashMap::new(),
         })
     }
 
     /// Process contract deployment
     fn process_deploy(
         &self,
-        state: &State<E>,
+        state: &mut StateOverlay<E>,
         transaction: &Transaction<E>,
+        block_number: u64,
     ) -> Result<TransitionResult<E>, StateError> {
         let mut modified_accounts = HashMap::new();
 
@@ -175,13 +426,21 @@ impl<E: PairingEngine> StateTransition<E> {
         let mut sender = state.get_account(&transaction.from)
             .ok_or_else(|| StateError::ValidationError("Sender account not found".to_string()))?;
 
+        // Validate and store contract code
+        ContractVm::<E>::validate_code(&transaction.data)?;
+        let code_hash = self.compute_code_hash(&transaction.data)?;
+        state.set_code(code_hash, transaction.data.clone());
+        let mut new_code = HashMap::new();
+        new_code.insert(code_hash, transaction.data.clone());
+
         // Create contract account
         let contract_id = self.generate_contract_id(transaction)?;
-        let contract_account = Account::new_contract(
+        let mut contract_account = Account::new_contract(
             contract_id.clone(),
-            self.compute_code_hash(&transaction.data)?,
+            code_hash,
             sender.public_key,
         );
+        contract_account.created_at = block_number;
 
         // Update sender balance and nonce
         sender.update_balance(-(transaction.value as i64))?;
@@ -196,17 +455,21 @@ impl<E: PairingEngine> StateTransition<E> {
             modified_accounts,
             computation_used: self.calculate_computation_used(transaction)?,
             logs: Vec::new(),
+            receipts: Vec::new(),
+            receipt_root: E::Fr::zero(),
+            logs_bloom: LogsBloom::new(),
+            new_code,
         })
     }
 
     /// Process contract call
     fn process_call(
         &self,
-        state: &State<E>,
+        state: &mut StateOverlay<E>,
         transaction: &Transaction<E>,
+        block_number: u64,
     ) -> Result<TransitionResult<E>, StateError> {
         let mut modified_accounts = HashMap::new();
-        let mut logs = Vec::new();
 
         // Get sender account
         let mut sender = state.get_account(&transaction.from)
@@ -214,62 +477,78 @@ impl<E: PairingEngine> StateTransition<E> {
 
         // Get contract account
         let contract_id = transaction.to.as_ref()
-            .ok_or_else(|| StateError::ValidationError("Contract not specified".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Contract not specified".to_string()))?;
         let mut contract = state.get_account(contract_id)
-            .ok_or_else(|| StateError::ValidationError("Contract not found".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Contract not found".to_string()))?;
 
         if !contract.is_contract() {
-            return Err(StateError::ValidationError("Target is not a contract".to_string()));
+            return Err(StateError::ExecutionError("Target is not a contract".to_string()));
         }
 
-        // Execute contract call
-        let (storage_updates, call_logs) = self.execute_contract_call(&contract, transaction)?;
-
-        // Update contract storage
-        for (key, value) in storage_updates {
-            contract.set_storage(key, value);
-        }
-
-        // Update sender balance and nonce
+        // Move call value and update sender nonce before execution
         sender.update_balance(-(transaction.value as i64))?;
         sender.increment_nonce();
+        contract.update_balance(transaction.value as i64)?;
+        state.set_account(sender);
+        state.set_account(contract);
 
-        // Store modified accounts
-        modified_accounts.insert(sender.id.clone(), sender);
-        modified_accounts.insert(contract.id.clone(), contract);
+        // Execute contract call
+        let outcome = self.execute_contract_call(state, contract_id, transaction, block_number)?;
+        if !outcome.success {
+            return Err(StateError::Reverted {
+                reason: outcome.revert_reason.unwrap_or_default(),
+                computation_used: outcome.computation_used,
+            });
+        }
 
-        logs.extend(call_logs);
+        // Collect accounts touched by the call, committing contract storage
+        for id in [&transaction.from, contract_id].into_iter().chain(outcome.touched_accounts.iter()) {
+            if let Some(mut account) = state.get_account(id) {
+                if account.is_contract() {
+                    account.commit_storage()?;
+                }
+                modified_accounts.insert(id.clone(), account);
+            }
+        }
 
         Ok(TransitionResult {
             new_root: E::Fr::zero(), // Will be calculated later
             modified_accounts,
-            computation_used: self.calculate_computation_used(transaction)?,
-            logs,
+            computation_used: self.calculate_computation_used(transaction)? + outcome.computation_used,
+            logs: outcome.logs,
+            receipts: Vec::new(),
+            receipt_root: E::Fr::zero(),
+            logs_bloom: LogsBloom::new(),
+            new_code: HashMap::new(),
         })
     }
 
     /// Process account creation
     fn process_create_account(
         &self,
-        state: &State<E>,
+        state: &StateOverlay<E>,
         transaction: &Transaction<E>,
+        block_number: u64,
     ) -> Result<TransitionResult<E>, StateError> {
         let mut modified_accounts = HashMap::new();
 
         // Verify account doesn't exist
         let account_id = transaction.to.as_ref()
-            .ok_or_else(|| StateError::ValidationError("Account ID not specified".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Account ID not specified".to_string()))?;
         if state.get_account(account_id).is_some() {
-            return Err(StateError::ValidationError("Account already exists".to_string()));
+            return Err(StateError::ExecutionError("Account already exists".to_string()));
         }
 
         // Get sender account
         let mut sender = state.get_account(&transaction.from)
             .ok_or_else(|| StateError::ValidationError("Sender account not found".to_string()))?;
 
-        // Create new account
-        let public_key = self.extract_public_key(&transaction.data)?;
-        let new_account = Account::new(account_id.clone(), public_key);
+        // Create new account from its payload
+        let payload = CreateAccountPayload::decode_for(account_id, &transaction.data)?;
+        payload::check_metadata(&payload.metadata)?;
+        let mut new_account = Account::new(account_id.clone(), payload.key.public_key);
+        new_account.metadata = payload.metadata;
+        new_account.created_at = block_number;
 
         // Update sender nonce
         sender.increment_nonce();
@@ -283,40 +562,44 @@ impl<E: PairingEngine> StateTransition<E> {
             modified_accounts,
             computation_used: self.calculate_computation_used(transaction)?,
             logs: Vec::new(),
+            receipts: Vec::new(),
+            receipt_root: E::Fr::zero(),
+            logs_bloom: LogsBloom::new(),
+            new_code: HashMap::new(),
         })
     }
 
     /// Process account update
     fn process_update_account(
         &self,
-        state: &State<E>,
+        state: &StateOverlay<E>,
         transaction: &Transaction<E>,
     ) -> Result<TransitionResult<E>, StateError> {
         let mut modified_accounts = HashMap::new();
 
         // Get target account
         let account_id = transaction.to.as_ref()
-            .ok_or_else(|| StateError::ValidationError("Account ID not specified".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Account ID not specified".to_string()))?;
         let mut account = state.get_account(account_id)
-            .ok_or_else(|| StateError::ValidationError("Account not found".to_string()))?;
+            .ok_or_else(|| StateError::ExecutionError("Account not found".to_string()))?;
 
         // Get sender account
-        let mut sender = state.get_account(&transaction.from)
+        let sender = state.get_account(&transaction.from)
             .ok_or_else(|| StateError::ValidationError("Sender account not found".to_string()))?;
 
         // Verify sender is the account owner
         if sender.id != account.id {
-            return Err(StateError::ValidationError("Not account owner".to_string()));
+            return Err(StateError::ExecutionError("Not account owner".to_string()));
         }
 
-        // Update account
-        self.apply_account_updates(&mut account, &transaction.data)?;
+        // Update account from its payload
+        let payload = UpdateAccountPayload::decode_for(account_id, &transaction.data)?;
+        self.apply_account_updates(&mut account, payload)?;
 
-        // Update sender nonce
-        sender.increment_nonce();
+        // Update nonce, sender and account are the same
+        account.increment_nonce();
 
-        // Store modified accounts
-        modified_accounts.insert(sender.id.clone(), sender);
+        // Store modified account
         modified_accounts.insert(account.id.clone(), account);
 
         Ok(TransitionResult {
@@ -324,30 +607,19 @@ impl<E: PairingEngine> StateTransition<E> {
             modified_accounts,
             computation_used: self.calculate_computation_used(transaction)?,
             logs: Vec::new(),
+            receipts: Vec::new(),
+            receipt_root: E::Fr::zero(),
+            logs_bloom: LogsBloom::new(),
+            new_code: HashMap::new(),
         })
     }
 
     // Helper functions
     fn calculate_computation_used(&self, transaction: &Transaction<E>) -> Result<u64, StateError> {
-        // Base computation cost
-        let mut computation = self.min_computation;
-
-        // Add cost based on data size
-        computation += transaction.data.len() as u64 * 10;
-
-        // Add cost based on transaction type
-        computation += match transaction.tx_type {
-            TransactionType::Transfer => 1000,
-            TransactionType::Deploy => 50000,
-            TransactionType::Call => 5000,
-            TransactionType::CreateAccount => 2000,
-            TransactionType::UpdateAccount => 3000,
-        };
-
-        Ok(computation)
+        Ok(computation::computation_cost(transaction))
     }
 
-    fn generate_contract_id(&self, transaction: &Transaction<E>) -> Result<AccountId, StateError> {
+    pub(super) fn generate_contract_id(&self, transaction: &Transaction<E>) -> Result<AccountId, StateError> {
         let mut hasher = sha3::Sha3_256::new();
         hasher.update(&transaction.from.0);
         hasher.update(&transaction.nonce.to_le_bytes());
@@ -355,37 +627,51 @@ impl<E: PairingEngine> StateTransition<E> {
     }
 
     fn compute_code_hash(&self, code: &[u8]) -> Result<E::Fr, StateError> {
-        let mut hasher = sha3::Sha3_256::new();
-        hasher.update(code);
-        let hash = hasher.finalize();
-
-        E::Fr::from_random_bytes(&hash)
-            .ok_or_else(|| StateError::ValidationError("Invalid code hash".to_string()))
+        code::code_hash::<E>(code)
     }
 
     fn execute_contract_call(
         &self,
-        contract: &Account<E>,
+        state: &mut StateOverlay<E>,
+        contract_id: &AccountId,
         transaction: &Transaction<E>,
-    ) -> Result<(HashMap<E::Fr, E::Fr>, Vec<Log<E>>), StateError> {
-        // This is a placeholder for actual contract execution
-        // In a real implementation, this would:
-        // 1. Load and verify contract code
-        // 2. Set up execution environment
-        // 3. Execute contract code
-        // 4. Return storage updates and logs
+        block_number: u64,
+    ) -> Result<ExecutionOutcome<E>, StateError> {
+        let context = CallContext {
+            caller: transaction.from.clone(),
+            address: contract_id.clone(),
+            value: transaction.value,
+            block_number,
+            calldata: transaction.data.clone(),
+        };
 
-        Ok((HashMap::new(), Vec::new()))
-    }
+        // Zero-fee transactions run up to the VM limit
+        let limit = if transaction.gas_limit > 0 { transaction.gas_limit } else { u64::MAX };
 
-    fn extract_public_key(&self, data: &[u8]) -> Result<E::G1Projective, StateError> {
-        // This is a placeholder for actual public key extraction
-        Ok(E::G1Projective::prime_subgroup_generator())
+        self.vm.call(state, &context, limit)
     }
 
-    fn apply_account_updates(&self, account: &mut Account<E>, data: &[u8]) -> Result<(), StateError> {
-        // This is a placeholder for actual account updates
-        Ok(())
+    fn apply_account_updates(
+        &self,
+        account: &mut Account<E>,
+        payload: UpdateAccountPayload<E>,
+    ) -> Result<(), StateError> {
+        if let Some(key) = payload.new_key {
+            account.public_key = key.public_key;
+        }
+
+        if let Some(policy) = payload.policy {
+            account.auth_policy = policy.policy;
+        }
+
+        for (key, value) in payload.metadata {
+            match value {
+                Some(value) => account.metadata.insert(key, value),
+                None => account.metadata.remove(&key),
+            };
+        }
+
+        payload::check_metadata(&account.metadata)
     }
 }
 
@@ -394,6 +680,7 @@ mod tests {
     use super::*;
     use ark_bls12_381::{Bls12_381, Fr};
     use crate::crypto::signature::SignatureScheme;
+    use crate::state::vm::opcodes;
     use rand::thread_rng;
 
     fn setup_test_state() -> State<Bls12_381> {
@@ -437,7 +724,7 @@ mod tests {
         
         let signature_scheme = SignatureScheme::new(128).unwrap();
         tx.sign(&signature_scheme, private_key).unwrap();
-        tx.add_computation_proof(vec![1, 2, 3]); // Mock proof
+        tx.solve_computation(&Difficulty::default()).unwrap();
         
         tx
     }
@@ -446,9 +733,8 @@ mod tests {
     fn test_transfer_transaction() {
         let state_transition = StateTransition::<Bls12_381>::new().unwrap();
         let state = setup_test_state();
-        let mut rng = thread_rng();
         
-        let private_key = Fr::rand(&mut rng);
+        let private_key = Fr::one();
         let tx = create_signed_transaction(
             TransactionType::Transfer,
             AccountId(vec![1]),
@@ -458,7 +744,7 @@ mod tests {
             &private_key,
         );
         
-        let result = state_transition.apply_transaction(&state, &tx, 1).unwrap();
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
         
         // Verify balances
         let sender_account = result.modified_accounts.get(&AccountId(vec![1])).unwrap();
@@ -472,10 +758,9 @@ mod tests {
     fn test_deploy_contract() {
         let state_transition = StateTransition::<Bls12_381>::new().unwrap();
         let state = setup_test_state();
-        let mut rng = thread_rng();
         
-        let private_key = Fr::rand(&mut rng);
-        let contract_code = vec![1, 2, 3, 4]; // Mock contract code
+        let private_key = Fr::one();
+        let contract_code = vec![opcodes::STOP];
         let mut tx = Transaction::new(
             TransactionType::Deploy,
             AccountId(vec![1]),
@@ -487,9 +772,9 @@ mod tests {
         
         let signature_scheme = SignatureScheme::new(128).unwrap();
         tx.sign(&signature_scheme, &private_key).unwrap();
-        tx.add_computation_proof(vec![1, 2, 3]);
+        tx.solve_computation(&Difficulty::default()).unwrap();
         
-        let result = state_transition.apply_transaction(&state, &tx, 1).unwrap();
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
         
         // Verify contract deployment
         assert_eq!(result.modified_accounts.len(), 2);
@@ -497,6 +782,51 @@ mod tests {
             .find(|account| account.is_contract())
             .unwrap();
         assert!(contract_account.code_hash.is_some());
+        assert!(result.new_code.contains_key(&contract_account.code_hash.unwrap()));
+    }
+
+    #[test]
+    fn test_deploy_oversized_code() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let state = setup_test_state();
+        
+        let mut tx = Transaction::new(
+            TransactionType::Deploy,
+            AccountId(vec![1]),
+            None,
+            0,
+            0,
+            vec![opcodes::STOP; code::MAX_CODE_SIZE + 1],
+        );
+        
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        tx.sign(&signature_scheme, &Fr::one()).unwrap();
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        
+        assert!(matches!(
+            state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()),
+            Err(StateError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrong_chain_id() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap().with_chain_id(2);
+        let state = setup_test_state();
+        
+        let tx = create_signed_transaction(
+            TransactionType::Transfer,
+            AccountId(vec![1]),
+            Some(AccountId(vec![2])),
+            100,
+            0,
+            &Fr::one(),
+        );
+        
+        assert!(matches!(
+            state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()),
+            Err(StateError::ValidationError(_))
+        ));
     }
 
     #[test]
@@ -505,17 +835,22 @@ mod tests {
         let mut state = setup_test_state();
         let mut rng = thread_rng();
         
-        // Deploy contract first
+        // Deploy contract first, storing the block number in slot 0
         let contract_id = AccountId(vec![3]);
-        let mut contract = Account::new_contract(
+        let code_hash = Fr::rand(&mut rng);
+        let mut code = vec![opcodes::BLOCKNUMBER, opcodes::PUSH];
+        code.extend_from_slice(&0u64.to_be_bytes());
+        code.extend_from_slice(&[opcodes::SSTORE, opcodes::STOP]);
+        state.set_code(code_hash, code);
+        let contract = Account::new_contract(
             contract_id.clone(),
-            Fr::rand(&mut rng),
+            code_hash,
             Bls12_381::G1Projective::prime_subgroup_generator(),
         );
         state.accounts.insert(contract_id.clone(), contract);
         
         // Create contract call transaction
-        let private_key = Fr::rand(&mut rng);
+        let private_key = Fr::one();
         let tx = create_signed_transaction(
             TransactionType::Call,
             AccountId(vec![1]),
@@ -525,11 +860,14 @@ mod tests {
             &private_key,
         );
         
-        let result = state_transition.apply_transaction(&state, &tx, 1).unwrap();
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
         
         // Verify contract call
         assert!(result.modified_accounts.contains_key(&AccountId(vec![1])));
-        assert!(result.modified_accounts.contains_key(&AccountId(vec![3])));
+        let contract = result.modified_accounts.get(&AccountId(vec![3])).unwrap();
+        assert_eq!(contract.get_storage(&Fr::from(0u64)), Some(Fr::from(1u64)));
+        assert_eq!(contract.state_root, contract.storage_root().unwrap());
+        assert!(!contract.state_root.is_zero());
     }
 
     #[test]
@@ -538,32 +876,142 @@ mod tests {
         let state = setup_test_state();
         let mut rng = thread_rng();
         
-        let private_key = Fr::rand(&mut rng);
+        let private_key = Fr::one();
         let new_account_id = AccountId(vec![4]);
-        let tx = create_signed_transaction(
+        let new_key = Fr::rand(&mut rng);
+        let payload = CreateAccountPayload::<Bls12_381>::new(&new_account_id, &new_key)
+            .unwrap()
+            .with_metadata("name", b"alice".to_vec());
+        let mut tx = Transaction::new(
             TransactionType::CreateAccount,
             AccountId(vec![1]),
             Some(new_account_id.clone()),
             0,
             0,
-            &private_key,
+            payload.encode().unwrap(),
         );
         
-        let result = state_transition.apply_transaction(&state, &tx, 1).unwrap();
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        tx.sign(&signature_scheme, &private_key).unwrap();
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
         
         // Verify account creation
         assert!(result.modified_accounts.contains_key(&new_account_id));
         let new_account = result.modified_accounts.get(&new_account_id).unwrap();
         assert_eq!(new_account.balance, 0);
+        assert_eq!(new_account.public_key, payload.key.public_key);
+        assert_eq!(new_account.metadata.get("name"), Some(&b"alice".to_vec()));
+    }
+
+    #[test]
+    fn test_update_account_rotates_key() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let state = setup_test_state();
+        let account_id = AccountId(vec![1]);
+        
+        let new_key = Fr::from(5u64);
+        let payload = UpdateAccountPayload::<Bls12_381>::new()
+            .rotate_key(&account_id, &new_key)
+            .unwrap()
+            .set_metadata("name", b"bob".to_vec());
+        let mut tx = Transaction::new(
+            TransactionType::UpdateAccount,
+            account_id.clone(),
+            Some(account_id.clone()),
+            0,
+            0,
+            payload.encode().unwrap(),
+        );
+        
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        tx.sign(&signature_scheme, &Fr::one()).unwrap();
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
+        let account = result.modified_accounts.get(&account_id).unwrap();
+        assert_eq!(account.public_key, payload.new_key.unwrap().public_key);
+        assert_eq!(account.nonce, 1);
+        
+        // Malformed payload is rejected
+        tx.data = vec![payload::ACCOUNT_PAYLOAD_VERSION, 0xff];
+        tx.sign(&signature_scheme, &Fr::one()).unwrap();
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        assert!(matches!(
+            state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()),
+            Err(StateError::PayloadError(_))
+        ));
+    }
+
+    #[test]
+    fn test_weighted_policy_account() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let mut state = setup_test_state();
+        let account_id = AccountId(vec![1]);
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        
+        // Switch the account to 2-of-3
+        let signers = (2..=4u64)
+            .map(|secret| (payload::KeyRegistration::new(&account_id, &Fr::from(secret)).unwrap(), 1))
+            .collect();
+        let payload = UpdateAccountPayload::<Bls12_381>::new()
+            .set_policy(payload::PolicyUpdate::weighted(signers, 2).unwrap());
+        let mut tx = Transaction::new(
+            TransactionType::UpdateAccount,
+            account_id.clone(),
+            Some(account_id.clone()),
+            0,
+            0,
+            payload.encode().unwrap(),
+        );
+        tx.sign(&signature_scheme, &Fr::one()).unwrap();
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
+        for account in result.modified_accounts.into_values() {
+            state.set_account(account);
+        }
+        
+        // The old single key no longer authorizes
+        let tx = create_signed_transaction(
+            TransactionType::Transfer,
+            account_id.clone(),
+            Some(AccountId(vec![2])),
+            100,
+            1,
+            &Fr::one(),
+        );
+        assert!(state_transition.apply_transaction(&state, &tx, 2, &Difficulty::default()).is_err());
+        
+        // One of three signers reports the missing ones
+        let mut tx = Transaction::new(
+            TransactionType::Transfer,
+            account_id.clone(),
+            Some(AccountId(vec![2])),
+            100,
+            1,
+            vec![],
+        );
+        tx.solve_computation(&Difficulty::default()).unwrap();
+        tx.cosign(&signature_scheme, 2, &Fr::from(4u64)).unwrap();
+        match state_transition.apply_transaction(&state, &tx, 2, &Difficulty::default()) {
+            Err(StateError::ValidationError(message)) => assert!(message.contains("missing signers [0, 1]")),
+            other => panic!("unexpected result {:?}", other.map(|r| r.new_root)),
+        }
+        
+        // Two signers authorize
+        tx.cosign(&signature_scheme, 0, &Fr::from(2u64)).unwrap();
+        let result = state_transition.apply_transaction(&state, &tx, 2, &Difficulty::default()).unwrap();
+        assert_eq!(result.modified_accounts.get(&AccountId(vec![2])).unwrap().balance, 100);
     }
 
     #[test]
     fn test_invalid_nonce() {
         let state_transition = StateTransition::<Bls12_381>::new().unwrap();
         let state = setup_test_state();
-        let mut rng = thread_rng();
         
-        let private_key = Fr::rand(&mut rng);
+        let private_key = Fr::one();
         let tx = create_signed_transaction(
             TransactionType::Transfer,
             AccountId(vec![1]),
@@ -573,7 +1021,7 @@ mod tests {
             &private_key,
         );
         
-        let result = state_transition.apply_transaction(&state, &tx, 1);
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default());
         assert!(result.is_err());
     }
 
@@ -581,9 +1029,8 @@ mod tests {
     fn test_insufficient_balance() {
         let state_transition = StateTransition::<Bls12_381>::new().unwrap();
         let state = setup_test_state();
-        let mut rng = thread_rng();
         
-        let private_key = Fr::rand(&mut rng);
+        let private_key = Fr::one();
         let tx = create_signed_transaction(
             TransactionType::Transfer,
             AccountId(vec![1]),
@@ -593,17 +1040,271 @@ mod tests {
             &private_key,
         );
         
-        let result = state_transition.apply_transaction(&state, &tx, 1);
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default());
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_computational_credit_limit() {
+        // Sender holds 1000, so capacity is 4000 + 1000: two transfers of 2000
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap()
+            .with_admission_policy(AdmissionPolicy {
+                base_capacity: 4_000,
+                regeneration: 2_000,
+                ..AdmissionPolicy::default()
+            });
+        let state = setup_test_state();
+
+        let transactions: Vec<_> = (0..3)
+            .map(|nonce| create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                10,
+                nonce,
+                &Fr::one(),
+            ))
+            .collect();
+
+        let err = state_transition.apply_block(&state, &transactions, 1, &Difficulty::default()).unwrap_err();
+        assert!(err.to_string().contains("Insufficient computational credit"));
+
+        // Spread over two blocks the credit has regenerated
+        let result = state_transition.apply_block(&state, &transactions[..2], 1, &Difficulty::default()).unwrap();
+        let mut next = setup_test_state();
+        next.accounts.extend(result.modified_accounts);
+        assert_eq!(next.accounts[&AccountId(vec![1])].credit.used, 4_000);
+        state_transition.apply_block(&next, &transactions[2..], 2, &Difficulty::default()).unwrap();
+    }
+
+    #[test]
+    fn test_difficulty_follows_block_load() {
+        // Each transfer costs 2000, so two of them load a block at 4x the target
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap()
+            .with_difficulty_adjuster(DifficultyAdjuster::new(1_000, 1, 8, 12).unwrap());
+        let state = setup_test_state();
+        let transactions: Vec<_> = (0..2)
+            .map(|nonce| create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                10,
+                nonce,
+                &Fr::one(),
+            ))
+            .collect();
+        
+        let idle = state_transition.difficulty(&[]);
+        assert_eq!(idle, Difficulty::new(8));
+        let result = state_transition.apply_block(&state, &transactions, 1, &idle).unwrap();
+        
+        // Derived from the recorded load alone, applying the block changed nothing
+        let busy = state_transition.difficulty(&[result.computation_used]);
+        assert_eq!(busy, Difficulty::new(10));
+        assert_eq!(state_transition.difficulty(&[]), idle);
+        
+        // Proofs solved for the idle network no longer suffice
+        let mut next = setup_test_state();
+        next.accounts.extend(result.modified_accounts);
+        let mut tx = (0..)
+            .map(|value| create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                value,
+                2,
+                &Fr::one(),
+            ))
+            .find(|tx| !tx.verify_computation(&Difficulty::new(10)).unwrap())
+            .unwrap();
+        assert!(state_transition.apply_block(&next, &[tx.clone()], 2, &busy).is_err());
+        
+        tx.solve_computation(&busy).unwrap();
+        assert!(state_transition.apply_block(&next, &[tx], 2, &busy).is_ok());
+    }
+
+    #[test]
+    fn test_block_applies_transactions_sequentially() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let state = setup_test_state();
+        
+        // Sender public key is the generator, so the secret key is one
+        let private_key = Fr::one();
+        let transactions: Vec<_> = (0..2)
+            .map(|nonce| create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                300,
+                nonce,
+                &private_key,
+            ))
+            .collect();
+        
+        let result = state_transition.apply_block(&state, &transactions, 1, &Difficulty::default()).unwrap();
+        
+        let sender_account = result.modified_accounts.get(&AccountId(vec![1])).unwrap();
+        let receiver_account = result.modified_accounts.get(&AccountId(vec![2])).unwrap();
+        assert_eq!(sender_account.balance, 400);
+        assert_eq!(sender_account.nonce, 2);
+        assert_eq!(receiver_account.balance, 600);
+    }
+
+    #[test]
+    fn test_block_invalid_transaction_discards_overlay() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let state = setup_test_state();
+        
+        let private_key = Fr::one();
+        let transactions = vec![
+            create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                100,
+                0,
+                &private_key,
+            ),
+            create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                100,
+                0, // Replayed nonce
+                &private_key,
+            ),
+        ];
+        
+        assert!(state_transition.apply_block(&state, &transactions, 1, &Difficulty::default()).is_err());
+        assert_eq!(state.get_account(&AccountId(vec![1])).unwrap().balance, 1000);
+    }
+
+    #[test]
+    fn test_execution_failure_produces_failed_receipt() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let state = setup_test_state();
+        
+        let private_key = Fr::one();
+        let transactions: Vec<_> = (0..2)
+            .map(|nonce| create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                600, // Second transfer exceeds the remaining balance
+                nonce,
+                &private_key,
+            ))
+            .collect();
+        
+        let result = state_transition.apply_block(&state, &transactions, 1, &Difficulty::default()).unwrap();
+        
+        assert_eq!(result.receipts.len(), 2);
+        assert!(result.receipts[0].is_success());
+        assert!(!result.receipts[1].is_success());
+        assert_eq!(result.receipts[1].tx_index, 1);
+        assert!(result.receipts[1].computation_used > 0);
+        assert!(result.receipts[0].state_changes.contains_key(&AccountId(vec![2])));
+        
+        // Failed transfer consumes the nonce but moves no funds
+        let sender_account = result.modified_accounts.get(&AccountId(vec![1])).unwrap();
+        assert_eq!(sender_account.balance, 400);
+        assert_eq!(sender_account.nonce, 2);
+        assert_eq!(result.receipt_root, TransactionReceipt::root(&result.receipts).unwrap());
+    }
+
+    #[test]
+    fn test_reverted_call_charges_vm_computation() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let mut state = setup_test_state();
+        
+        // Contract writes a slot and then reverts
+        let contract_id = AccountId(vec![3]);
+        let code_hash = Fr::from(7u64);
+        let mut code = vec![opcodes::PUSH];
+        code.extend_from_slice(&1u64.to_be_bytes());
+        code.push(opcodes::PUSH);
+        code.extend_from_slice(&0u64.to_be_bytes());
+        code.extend_from_slice(&[opcodes::SSTORE, opcodes::REVERT]);
+        state.set_code(code_hash, code);
+        state.accounts.insert(contract_id.clone(), Account::new_contract(
+            contract_id.clone(),
+            code_hash,
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        ));
+        
+        let tx = create_signed_transaction(
+            TransactionType::Call,
+            AccountId(vec![1]),
+            Some(contract_id.clone()),
+            0,
+            0,
+            &Fr::one(),
+        );
+        let outcome = state_transition
+            .execute_contract_call(&mut StateOverlay::new(&state), &contract_id, &tx, 1)
+            .unwrap();
+        assert!(!outcome.success);
+        assert!(outcome.computation_used > 0);
+        
+        let result = state_transition.apply_block(&state, &[tx.clone()], 1, &Difficulty::default()).unwrap();
+        let receipt = &result.receipts[0];
+        assert!(!receipt.is_success());
+        assert_eq!(receipt.computation_used, computation::computation_cost(&tx) + outcome.computation_used);
+        assert_eq!(result.computation_used, receipt.computation_used);
+        assert_eq!(result.modified_accounts[&AccountId(vec![1])].credit.used, receipt.computation_used);
+    }
+
+    #[test]
+    fn test_validate_block_uses_current_nonce() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let mut state = setup_test_state();
+        state.accounts.get_mut(&AccountId(vec![1])).unwrap().nonce = 5;
+        
+        let private_key = Fr::one();
+        let tx = create_signed_transaction(
+            TransactionType::Transfer,
+            AccountId(vec![1]),
+            Some(AccountId(vec![2])),
+            100,
+            5,
+            &private_key,
+        );
+        
+        assert!(state_transition.validate_block(&state, &[tx], &Difficulty::default()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_block_locates_bad_signature() {
+        let state_transition = StateTransition::<Bls12_381>::new().unwrap();
+        let state = setup_test_state();
+        
+        let mut transactions: Vec<_> = (0..6)
+            .map(|nonce| create_signed_transaction(
+                TransactionType::Transfer,
+                AccountId(vec![1]),
+                Some(AccountId(vec![2])),
+                10,
+                nonce,
+                &Fr::one(),
+            ))
+            .collect();
+        assert!(state_transition.validate_block(&state, &transactions, &Difficulty::default()).is_ok());
+        
+        // Signed by the wrong key
+        let signature_scheme = SignatureScheme::new(128).unwrap();
+        transactions[4].sign(&signature_scheme, &Fr::from(2u64)).unwrap();
+        match state_transition.validate_block(&state, &transactions, &Difficulty::default()) {
+            Err(StateError::ValidationError(message)) => assert!(message.contains("transaction 4")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
     #[test]
     fn test_computation_used() {
         let state_transition = StateTransition::<Bls12_381>::new().unwrap();
         let state = setup_test_state();
-        let mut rng = thread_rng();
         
-        let private_key = Fr::rand(&mut rng);
+        let private_key = Fr::one();
         let tx = create_signed_transaction(
             TransactionType::Transfer,
             AccountId(vec![1]),
@@ -613,8 +1314,8 @@ mod tests {
             &private_key,
         );
         
-        let result = state_transition.apply_transaction(&state, &tx, 1).unwrap();
-        assert!(result.computation_used >= state_transition.min_computation);
+        let result = state_transition.apply_transaction(&state, &tx, 1, &Difficulty::default()).unwrap();
+        assert!(result.computation_used >= computation::BASE_COMPUTATION);
     }
 }
 
@@ -625,56 +1326,151 @@ impl<E: PairingEngine> StateTransition<E> {
         &self,
         state: &State<E>,
         transactions: &[Transaction<E>],
+        difficulty: &Difficulty,
     ) -> Result<(), StateError> {
+        self.check_block(state, transactions, difficulty).map(|_| ())
+    }
+
+    /// Validate block, returning which transactions had their signatures verified
+    ///
+    /// Signatures are checked in one batch against the senders at the start of
+    /// the block. Transactions following an `UpdateAccount` by the same sender
+    /// may be signed under new keys and are left to be checked on execution.
+    pub(super) fn check_block(
+        &self,
+        state: &State<E>,
+        transactions: &[Transaction<E>],
+        difficulty: &Difficulty,
+    ) -> Result<Vec<bool>, StateError> {
         let mut nonce_map = HashMap::new();
+        let mut updated = HashSet::new();
+        let mut verified = Vec::with_capacity(transactions.len());
+        let mut counts = Vec::with_capacity(transactions.len());
+        let mut senders = Vec::with_capacity(transactions.len());
+        let mut messages = Vec::with_capacity(transactions.len());
+        let mut checks = Vec::new();
         
-        for tx in transactions {
-            // Check basic transaction validity
-            self.validate_transaction(state, tx)?;
+        for (index, tx) in transactions.iter().enumerate() {
+            // Check computation proof
+            let sender = state.get_account(&tx.from)
+                .ok_or_else(|| StateError::ValidationError("Sender account not found".to_string()))?;
+            self.validate_computation(tx, difficulty)?;
             
-            // Check nonce sequence
-            let nonce = nonce_map.entry(tx.from.clone()).or_insert(0);
+            // Check nonce sequence starting from the sender's current nonce
+            let nonce = nonce_map.entry(tx.from.clone()).or_insert(sender.nonce);
             if tx.nonce != *nonce {
                 return Err(StateError::ValidationError("Invalid nonce sequence".to_string()));
             }
             *nonce += 1;
+            
+            // Collect signatures to batch
+            let batched = !updated.contains(&tx.from);
+            let first_check = checks.len();
+            if batched {
+                for (key, signature) in sender.auth_policy.signature_checks(
+                    &sender.public_key,
+                    tx.signature.as_ref(),
+                    &tx.cosignatures,
+                )? {
+                    checks.push((index, key, signature));
+                }
+            }
+            if tx.tx_type == TransactionType::UpdateAccount {
+                updated.insert(tx.from.clone());
+            }
+            
+            verified.push(batched);
+            counts.push(checks.len() - first_check);
+            messages.push(if batched { tx.encode_for_signing()? } else { Vec::new() });
+            senders.push(sender);
         }
         
-        Ok(())
+        // Verify all signatures at once, locating the first invalid one
+        let items: Vec<_> = checks.iter()
+            .map(|(index, key, signature)| BatchItem {
+                message: &messages[*index],
+                signature: *signature,
+                public_key: key,
+            })
+            .collect();
+        let invalid = self.signature_scheme.verify_batch(&items)
+            .map_err(|e| StateError::ValidationError(e.to_string()))?;
+        if let Some(item) = invalid {
+            return Err(StateError::ValidationError(format!(
+                "Invalid signature in transaction {}", checks[item].0
+            )));
+        }
+        
+        // Every batched signature is valid, check each policy is satisfied
+        for (index, tx) in transactions.iter().enumerate() {
+            if verified[index] {
+                let report = senders[index].auth_policy.report(&tx.cosignatures, &vec![true; counts[index]]);
+                Self::ensure_authorized(&senders[index], &report)?;
+            }
+        }
+        
+        Ok(verified)
     }
 
     /// Apply block of transactions
+    ///
+    /// Block-invalid transactions (bad signature, nonce or computation proof)
+    /// reject the whole block. Execution failures are kept in the block with a
+    /// failed receipt and only consume the sender's nonce and credit.
+    /// Computation proofs must meet `difficulty`, derived with
+    /// [`Self::difficulty`] from the loads of the preceding blocks.
     pub fn apply_block(
         &self,
         state: &State<E>,
         transactions: &[Transaction<E>],
         block_number: u64,
+        difficulty: &Difficulty,
     ) -> Result<TransitionResult<E>, StateError> {
-        let mut modified_accounts = HashMap::new();
+        let mut overlay = StateOverlay::new(state);
         let mut total_computation = 0u64;
         let mut all_logs = Vec::new();
+        let mut receipts = Vec::with_capacity(transactions.len());
         
         // Validate entire block first
-        self.validate_block(state, transactions)?;
+        let verified = self.check_block(state, transactions, difficulty)?;
         
-        // Apply each transaction
-        for tx in transactions {
-            let result = self.apply_transaction(state, tx, block_number)?;
+        // Apply each transaction on top of the previous ones; a block-invalid
+        // transaction drops the overlay and leaves the state untouched
+        for (index, tx) in transactions.iter().enumerate() {
+            let tx_index = index as u32;
+            let receipt = match self.apply_checked(&mut overlay, tx, block_number, difficulty, verified[index]) {
+                Ok(result) => {
+                    all_logs.extend(result.logs);
+                    let mut receipt = result.receipts.into_iter().next()
+                        .ok_or_else(|| StateError::TransitionError("Missing receipt".to_string()))?;
+                    receipt.tx_index = tx_index;
+                    receipt
+                }
+                Err(e) if e.is_execution_failure() => {
+                    self.apply_failed_transaction(&mut overlay, tx, &e, tx_index, block_number)?
+                }
+                Err(e) => return Err(e),
+            };
             
-            // Merge results
-            modified_accounts.extend(result.modified_accounts);
-            total_computation += result.computation_used;
-            all_logs.extend(result.logs);
+            total_computation += receipt.computation_used;
+            receipts.push(receipt);
         }
         
-        // Calculate final state root
-        let new_root = state.calculate_root(&modified_accounts)?;
+        // Calculate final state and receipt roots
+        let new_root = overlay.calculate_root()?;
+        let receipt_root = TransactionReceipt::root(&receipts)?;
+        let logs_bloom = LogsBloom::from_logs(&all_logs)?;
+        let (modified_accounts, new_code) = overlay.commit();
         
         Ok(TransitionResult {
             new_root,
             modified_accounts,
             computation_used: total_computation,
             logs: all_logs,
+            receipts,
+            receipt_root,
+            logs_bloom,
+            new_code,
         })
     }
 
@@ -684,9 +1480,10 @@ impl<E: PairingEngine> StateTransition<E> {
         old_state: &State<E>,
         new_state: &State<E>,
         transactions: &[Transaction<E>],
+        difficulty: &Difficulty,
     ) -> Result<bool, StateError> {
         // Apply transactions to old state
-        let result = self.apply_block(old_state, transactions, 0)?;
+        let result = self.apply_block(old_state, transactions, 0, difficulty)?;
         
         // Verify new state matches expected result
         if new_state.root != result.new_root {
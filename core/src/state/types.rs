@@ -1,5 +1,6 @@
 use super::{Account, AccountId, StateError};
-use crate::crypto::merkle_tree::MerkleTree;
+use super::merkle_tree::{MerkleProof, MerkleTree};
+use super::storage_trie::{StorageProof, StorageTrie};
 use ark_ec::PairingEngine;
 use ark_ff::Field;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
@@ -11,6 +12,9 @@ pub struct State<E: PairingEngine> {
     /// Account states
     pub accounts: HashMap<AccountId, Account<E>>,
     
+    /// Contract code by code hash
+    pub code: HashMap<E::Fr, Vec<u8>>,
+    
     /// State root
     pub root: E::Fr,
     
@@ -29,6 +33,7 @@ impl<E: PairingEngine> State<E> {
     pub fn new() -> Self {
         Self {
             accounts: HashMap::new(),
+            code: HashMap::new(),
             root: E::Fr::zero(),
             version: 0,
             block_height: 0,
@@ -51,6 +56,16 @@ impl<E: PairingEngine> State<E> {
         self.accounts.remove(id);
     }
 
+    /// Get contract code by code hash
+    pub fn get_code(&self, code_hash: &E::Fr) -> Option<&Vec<u8>> {
+        self.code.get(code_hash)
+    }
+
+    /// Store contract code under its hash
+    pub fn set_code(&mut self, code_hash: E::Fr, code: Vec<u8>) {
+        self.code.insert(code_hash, code);
+    }
+
     /// Calculate state root
     pub fn calculate_root(
         &self,
@@ -124,18 +139,43 @@ impl<E: PairingEngine> State<E> {
 
     /// Verify state proof
     pub fn verify_proof(&self, proof: &StateProof<E>) -> Result<bool, StateError> {
-        let mut merkle_tree = MerkleTree::new(256);
-        
-        if let Some(account) = &proof.account {
-            let account_bytes = account.serialize()?;
-            merkle_tree.verify_proof(
-                &proof.account_id.0,
-                &account_bytes,
-                &proof.merkle_proof,
-            )?;
+        let merkle_tree = MerkleTree::new(256);
+        
+        let account_bytes = match &proof.account {
+            Some(account) => Some(account.serialize()?),
+            None => None,
+        };
+        let valid = merkle_tree.verify_proof_against(
+            &self.root,
+            &proof.account_id.0,
+            account_bytes.as_deref(),
+            &proof.merkle_proof,
+        )?;
+        
+        Ok(valid && proof.root == self.root)
+    }
+
+    /// Get proof of a contract storage slot against the state root
+    pub fn get_storage_proof(
+        &self,
+        id: &AccountId,
+        slot: &E::Fr,
+    ) -> Result<StorageProof<E>, StateError> {
+        let account_proof = self.get_account_proof(id)?;
+        let account = account_proof.account.as_ref()
+            .ok_or_else(|| StateError::AccountError("Account not found".to_string()))?;
+        
+        let trie = StorageTrie::from_slots(&account.storage)?;
+        if trie.root() != account.state_root {
+            return Err(StateError::MerkleError("Storage does not match state root".to_string()));
         }
         
-        Ok(proof.root == self.root)
+        Ok(StorageProof {
+            slot: *slot,
+            value: account.get_storage(slot).filter(|value| !value.is_zero()),
+            slot_proof: trie.prove(slot)?,
+            account_proof,
+        })
     }
 
     /// Serialize state
@@ -159,7 +199,18 @@ impl<E: PairingEngine> State<E> {
         for (id, account) in &self.accounts {
             id.0.serialize(&mut bytes)
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
-            account.serialize(&mut bytes)
+            account.encode_with_storage()?.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        }
+        
+        // Serialize contract code
+        (self.code.len() as u64).serialize(&mut bytes)
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        
+        for (code_hash, code) in &self.code {
+            code_hash.serialize(&mut bytes)
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            code.serialize(&mut bytes)
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
         }
         
@@ -196,17 +247,37 @@ impl<E: PairingEngine> State<E> {
         for _ in 0..account_count {
             let id_bytes: Vec<u8> = CanonicalDeserialize::deserialize(&bytes[offset..])
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
-            offset += id_bytes.len();
+            offset += id_bytes.serialized_size();
             
-            let account: Account<E> = Account::deserialize(&bytes[offset..])
+            let account_bytes: Vec<u8> = CanonicalDeserialize::deserialize(&bytes[offset..])
                 .map_err(|e| StateError::SerializationError(e.to_string()))?;
-            offset += account.serialized_size();
+            offset += account_bytes.serialized_size();
+            let account = Account::decode_with_storage(&account_bytes)?;
             
             accounts.insert(AccountId(id_bytes), account);
         }
         
+        // Deserialize contract code
+        let code_count: u64 = CanonicalDeserialize::deserialize(&bytes[offset..])
+            .map_err(|e| StateError::SerializationError(e.to_string()))?;
+        offset += std::mem::size_of::<u64>();
+        
+        let mut code = HashMap::new();
+        for _ in 0..code_count {
+            let code_hash: E::Fr = CanonicalDeserialize::deserialize(&bytes[offset..])
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            offset += code_hash.serialized_size();
+            
+            let code_bytes: Vec<u8> = CanonicalDeserialize::deserialize(&bytes[offset..])
+                .map_err(|e| StateError::SerializationError(e.to_string()))?;
+            offset += code_bytes.serialized_size();
+            
+            code.insert(code_hash, code_bytes);
+        }
+        
         Ok(Self {
             accounts,
+            code,
             root,
             version,
             block_height,
@@ -276,7 +347,7 @@ pub struct StateProof<E: PairingEngine> {
     pub account: Option<Account<E>>,
     
     /// Merkle proof
-    pub merkle_proof: Vec<E::Fr>,
+    pub merkle_proof: MerkleProof<E>,
     
     /// State root
     pub root: E::Fr,
@@ -342,9 +413,14 @@ mod tests {
             Bls12_381::G1Projective::prime_subgroup_generator(),
         );
         state.set_account(account);
+        state.root = state.calculate_root(&HashMap::new()).unwrap();
         
         let proof = state.get_account_proof(&id).unwrap();
         assert!(state.verify_proof(&proof).unwrap());
+        
+        // Proof must match the committed root
+        state.root = Bls12_381::Fr::zero();
+        assert!(!state.verify_proof(&proof).unwrap());
     }
 
     #[test]
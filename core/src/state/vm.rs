@@ -0,0 +1,747 @@
+use super::{AccountId, StateError};
+use super::logs::Log;
+use super::overlay::StateOverlay;
+use ark_ec::PairingEngine;
+use ark_ff::{One, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+
+/// Contract bytecode instructions
+pub mod opcodes {
+    /// Halt successfully
+    pub const STOP: u8 = 0x00;
+    /// Push 8-byte big-endian immediate
+    pub const PUSH: u8 = 0x01;
+    /// Drop top word
+    pub const POP: u8 = 0x02;
+    /// Copy the word at 1-byte immediate depth to the top
+    pub const DUP: u8 = 0x03;
+    /// Swap the top word with the word at 1-byte immediate depth + 1
+    pub const SWAP: u8 = 0x04;
+
+    /// Field addition
+    pub const ADD: u8 = 0x10;
+    /// Field subtraction
+    pub const SUB: u8 = 0x11;
+    /// Field multiplication
+    pub const MUL: u8 = 0x12;
+    /// Push 1 if the top two words are equal
+    pub const EQ: u8 = 0x13;
+    /// Push 1 if `a < b` for `[.., a, b]`
+    pub const LT: u8 = 0x14;
+    /// Push 1 if the top word is zero
+    pub const ISZERO: u8 = 0x15;
+
+    /// Jump to destination
+    pub const JUMP: u8 = 0x20;
+    /// Jump to destination if condition is non-zero, for `[.., cond, dest]`
+    pub const JUMPI: u8 = 0x21;
+
+    /// Load storage slot
+    pub const SLOAD: u8 = 0x30;
+    /// Store value into slot, for `[.., value, key]`
+    pub const SSTORE: u8 = 0x31;
+
+    /// Push caller address
+    pub const CALLER: u8 = 0x40;
+    /// Push own address
+    pub const ADDRESS: u8 = 0x41;
+    /// Push value sent with the call
+    pub const CALLVALUE: u8 = 0x42;
+    /// Push current block number
+    pub const BLOCKNUMBER: u8 = 0x43;
+    /// Push 8-byte calldata word at index
+    pub const CALLDATALOAD: u8 = 0x44;
+    /// Push calldata length
+    pub const CALLDATASIZE: u8 = 0x45;
+    /// Push address literal with 1-byte length immediate
+    pub const ADDR: u8 = 0x46;
+
+    /// Push balance of address
+    pub const BALANCE: u8 = 0x50;
+    /// Transfer from own balance, for `[.., amount, address]`
+    pub const TRANSFER: u8 = 0x51;
+
+    /// Emit log with 1-byte immediate topic count, for `[.., data, topics..]`
+    pub const LOG: u8 = 0x60;
+
+    /// Call contract, for `[.., args.., argc, value, address]`
+    pub const CALL: u8 = 0x70;
+
+    /// Halt returning top word
+    pub const RETURN: u8 = 0x80;
+    /// Halt reverting all changes of the call
+    pub const REVERT: u8 = 0x81;
+}
+
+use opcodes::*;
+
+/// Contract VM limits
+#[derive(Clone, Debug)]
+pub struct VmConfig {
+    /// Maximum computation per transaction
+    pub max_computation: u64,
+
+    /// Maximum nested call depth
+    pub max_call_depth: usize,
+
+    /// Maximum stack size
+    pub max_stack: usize,
+}
+
+impl Default for VmConfig {
+    fn default() -> Self {
+        Self {
+            max_computation: 1_000_000,
+            max_call_depth: 64,
+            max_stack: 1024,
+        }
+    }
+}
+
+/// Context of a contract call
+#[derive(Clone, Debug)]
+pub struct CallContext {
+    /// Calling account
+    pub caller: AccountId,
+
+    /// Called contract
+    pub address: AccountId,
+
+    /// Value transferred with the call
+    pub value: u64,
+
+    /// Current block number
+    pub block_number: u64,
+
+    /// Call input
+    pub calldata: Vec<u8>,
+}
+
+/// Result of a top-level contract call
+#[derive(Clone, Debug)]
+pub struct ExecutionOutcome<E: PairingEngine> {
+    /// Whether the call completed without reverting
+    pub success: bool,
+
+    /// Computation consumed
+    pub computation_used: u64,
+
+    /// Returned word
+    pub return_value: Option<E::Fr>,
+
+    /// Revert reason
+    pub revert_reason: Option<String>,
+
+    /// Logs emitted by successful frames
+    pub logs: Vec<Log<E>>,
+
+    /// Accounts touched by the call
+    pub touched_accounts: Vec<AccountId>,
+}
+
+/// Reason a frame stopped abnormally
+enum Halt {
+    /// Frame reverted, its changes are undone
+    Revert(String),
+    /// Host failure, aborts the transaction
+    Fatal(StateError),
+}
+
+impl From<StateError> for Halt {
+    fn from(e: StateError) -> Self {
+        Halt::Fatal(e)
+    }
+}
+
+/// Computation meter shared by all frames of a call
+struct Meter {
+    used: u64,
+    limit: u64,
+}
+
+impl Meter {
+    fn charge(&mut self, amount: u64) -> Result<(), Halt> {
+        if self.used + amount > self.limit {
+            self.used = self.limit;
+            return Err(Halt::Revert("Out of computation".to_string()));
+        }
+        self.used += amount;
+        Ok(())
+    }
+}
+
+/// Deterministic, metered contract VM
+pub struct ContractVm<E: PairingEngine> {
+    /// VM limits
+    config: VmConfig,
+
+    /// Curve type
+    _engine: PhantomData<E>,
+}
+
+impl<E: PairingEngine> ContractVm<E> {
+    /// Create new VM
+    pub fn new(config: VmConfig) -> Self {
+        Self {
+            config,
+            _engine: PhantomData,
+        }
+    }
+
+    /// Check that code only contains known instructions with complete immediates
+    pub fn validate_code(code: &[u8]) -> Result<(), StateError> {
+        Self::instruction_starts(code).map(|_| ())
+    }
+
+    /// Execute a call against the overlay, reverting its writes if it fails
+    pub fn call(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        context: &CallContext,
+        computation_limit: u64,
+    ) -> Result<ExecutionOutcome<E>, StateError> {
+        let mut meter = Meter {
+            used: 0,
+            limit: computation_limit.min(self.config.max_computation),
+        };
+        let mut logs = Vec::new();
+        let mut touched = BTreeSet::new();
+        touched.insert(context.address.clone());
+
+        let result = self.run_frame(overlay, context, 0, &mut meter, &mut logs, &mut touched)?;
+
+        Ok(ExecutionOutcome {
+            success: result.is_ok(),
+            computation_used: meter.used,
+            return_value: result.as_ref().ok().copied().flatten(),
+            revert_reason: result.err(),
+            logs,
+            touched_accounts: touched.into_iter().collect(),
+        })
+    }
+
+    /// Run a call frame, keeping its effects only if it succeeds
+    fn run_frame(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        context: &CallContext,
+        depth: usize,
+        meter: &mut Meter,
+        logs: &mut Vec<Log<E>>,
+        touched: &mut BTreeSet<AccountId>,
+    ) -> Result<Result<Option<E::Fr>, String>, StateError> {
+        let checkpoint = overlay.checkpoint();
+        let mut frame_logs = Vec::new();
+        let mut frame_touched = BTreeSet::new();
+
+        match self.interpret(overlay, context, depth, meter, &mut frame_logs, &mut frame_touched) {
+            Ok(value) => {
+                logs.extend(frame_logs);
+                touched.extend(frame_touched);
+                Ok(Ok(value))
+            }
+            Err(Halt::Revert(reason)) => {
+                overlay.revert_to(checkpoint)?;
+                Ok(Err(reason))
+            }
+            Err(Halt::Fatal(e)) => Err(e),
+        }
+    }
+
+    /// Interpret contract code
+    fn interpret(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        context: &CallContext,
+        depth: usize,
+        meter: &mut Meter,
+        logs: &mut Vec<Log<E>>,
+        touched: &mut BTreeSet<AccountId>,
+    ) -> Result<Option<E::Fr>, Halt> {
+        let contract = overlay.get_account(&context.address)
+            .ok_or_else(|| Halt::Revert("Contract not found".to_string()))?;
+        let code_hash = contract.code_hash
+            .ok_or_else(|| Halt::Revert("Target is not a contract".to_string()))?;
+        let code = overlay.get_code(&code_hash)
+            .cloned()
+            .ok_or_else(|| Halt::Revert("Missing contract code".to_string()))?;
+        let starts = Self::instruction_starts(&code).map_err(|e| Halt::Revert(e.to_string()))?;
+
+        // Address table: 0 is the caller, 1 the contract itself
+        let mut addresses = vec![context.caller.clone(), context.address.clone()];
+        let mut stack: Vec<E::Fr> = Vec::new();
+        let mut pc = 0usize;
+
+        while pc < code.len() {
+            let op = code[pc];
+            pc += 1;
+            meter.charge(Self::op_cost(op, &code, pc))?;
+
+            match op {
+                STOP => return Ok(None),
+                PUSH => {
+                    let value = u64::from_be_bytes(code[pc..pc + 8].try_into().unwrap());
+                    pc += 8;
+                    self.push(&mut stack, E::Fr::from(value))?;
+                }
+                POP => {
+                    Self::pop(&mut stack)?;
+                }
+                DUP => {
+                    let n = code[pc] as usize;
+                    pc += 1;
+                    let value = *stack.iter().rev().nth(n)
+                        .ok_or_else(|| Halt::Revert("Stack underflow".to_string()))?;
+                    self.push(&mut stack, value)?;
+                }
+                SWAP => {
+                    let n = code[pc] as usize + 1;
+                    pc += 1;
+                    if n >= stack.len() {
+                        return Err(Halt::Revert("Stack underflow".to_string()));
+                    }
+                    let top = stack.len() - 1;
+                    stack.swap(top, top - n);
+                }
+                ADD | SUB | MUL | EQ | LT => {
+                    let b = Self::pop(&mut stack)?;
+                    let a = Self::pop(&mut stack)?;
+                    let result = match op {
+                        ADD => a + b,
+                        SUB => a - b,
+                        MUL => a * b,
+                        EQ => Self::bool_word(a == b),
+                        _ => Self::bool_word(a.into_repr() < b.into_repr()),
+                    };
+                    self.push(&mut stack, result)?;
+                }
+                ISZERO => {
+                    let a = Self::pop(&mut stack)?;
+                    self.push(&mut stack, Self::bool_word(a.is_zero()))?;
+                }
+                JUMP | JUMPI => {
+                    let dest = Self::to_u64(&Self::pop(&mut stack)?)? as usize;
+                    let jump = op == JUMP || !Self::pop(&mut stack)?.is_zero();
+                    if jump {
+                        if dest >= code.len() || !starts[dest] {
+                            return Err(Halt::Revert("Invalid jump destination".to_string()));
+                        }
+                        pc = dest;
+                    }
+                }
+                SLOAD => {
+                    let key = Self::pop(&mut stack)?;
+                    let value = overlay.get_account(&context.address)
+                        .and_then(|account| account.get_storage(&key))
+                        .unwrap_or_else(E::Fr::zero);
+                    self.push(&mut stack, value)?;
+                }
+                SSTORE => {
+                    let key = Self::pop(&mut stack)?;
+                    let value = Self::pop(&mut stack)?;
+                    let mut account = overlay.get_account(&context.address)
+                        .ok_or_else(|| Halt::Revert("Contract not found".to_string()))?;
+                    account.set_storage(key, value);
+                    overlay.set_account(account);
+                    touched.insert(context.address.clone());
+                }
+                CALLER => self.push(&mut stack, E::Fr::from(0u64))?,
+                ADDRESS => self.push(&mut stack, E::Fr::from(1u64))?,
+                CALLVALUE => self.push(&mut stack, E::Fr::from(context.value))?,
+                BLOCKNUMBER => self.push(&mut stack, E::Fr::from(context.block_number))?,
+                CALLDATALOAD => {
+                    let index = Self::to_u64(&Self::pop(&mut stack)?)? as usize;
+                    let mut word = [0u8; 8];
+                    for (i, byte) in word.iter_mut().enumerate() {
+                        *byte = index.checked_mul(8)
+                            .and_then(|offset| context.calldata.get(offset + i))
+                            .copied()
+                            .unwrap_or(0);
+                    }
+                    self.push(&mut stack, E::Fr::from(u64::from_be_bytes(word)))?;
+                }
+                CALLDATASIZE => {
+                    self.push(&mut stack, E::Fr::from(context.calldata.len() as u64))?;
+                }
+                ADDR => {
+                    let len = code[pc] as usize;
+                    addresses.push(AccountId(code[pc + 1..pc + 1 + len].to_vec()));
+                    pc += 1 + len;
+                    self.push(&mut stack, E::Fr::from((addresses.len() - 1) as u64))?;
+                }
+                BALANCE => {
+                    let address = Self::address(&addresses, &Self::pop(&mut stack)?)?;
+                    let balance = overlay.get_account(address).map_or(0, |account| account.balance);
+                    self.push(&mut stack, E::Fr::from(balance))?;
+                }
+                TRANSFER => {
+                    let to = Self::address(&addresses, &Self::pop(&mut stack)?)?.clone();
+                    let amount = Self::to_u64(&Self::pop(&mut stack)?)?;
+                    Self::transfer(overlay, &context.address, &to, amount)?;
+                    touched.insert(context.address.clone());
+                    touched.insert(to);
+                }
+                LOG => {
+                    let topic_count = code[pc] as usize;
+                    pc += 1;
+                    let mut topics = Vec::with_capacity(topic_count);
+                    for _ in 0..topic_count {
+                        topics.push(Self::pop(&mut stack)?);
+                    }
+                    let mut data = Vec::new();
+                    Self::pop(&mut stack)?.serialize(&mut data)
+                        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+                    logs.push(Log::new(context.address.clone(), topics, data));
+                }
+                CALL => {
+                    let to = Self::address(&addresses, &Self::pop(&mut stack)?)?.clone();
+                    let value = Self::to_u64(&Self::pop(&mut stack)?)?;
+                    let argc = Self::to_u64(&Self::pop(&mut stack)?)? as usize;
+                    if argc > stack.len() {
+                        return Err(Halt::Revert("Stack underflow".to_string()));
+                    }
+                    let mut calldata = Vec::with_capacity(argc * 8);
+                    for arg in stack.split_off(stack.len() - argc) {
+                        calldata.extend_from_slice(&Self::to_u64(&arg)?.to_be_bytes());
+                    }
+
+                    let (success, value) = self.nested_call(
+                        overlay, context, to, value, calldata, depth, meter, logs, touched,
+                    )?;
+                    self.push(&mut stack, value.unwrap_or_else(E::Fr::zero))?;
+                    self.push(&mut stack, Self::bool_word(success))?;
+                }
+                RETURN => return Ok(Some(Self::pop(&mut stack)?)),
+                REVERT => return Err(Halt::Revert("Contract reverted".to_string())),
+                _ => return Err(Halt::Revert(format!("Invalid opcode 0x{:02x}", op))),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Transfer value and run a nested call frame
+    #[allow(clippy::too_many_arguments)]
+    fn nested_call(
+        &self,
+        overlay: &mut StateOverlay<E>,
+        context: &CallContext,
+        to: AccountId,
+        value: u64,
+        calldata: Vec<u8>,
+        depth: usize,
+        meter: &mut Meter,
+        logs: &mut Vec<Log<E>>,
+        touched: &mut BTreeSet<AccountId>,
+    ) -> Result<(bool, Option<E::Fr>), Halt> {
+        if depth + 1 >= self.config.max_call_depth {
+            return Ok((false, None));
+        }
+
+        let checkpoint = overlay.checkpoint();
+        match Self::transfer(overlay, &context.address, &to, value) {
+            Ok(()) => {}
+            Err(Halt::Revert(_)) => return Ok((false, None)),
+            Err(fatal) => return Err(fatal),
+        }
+
+        let child = CallContext {
+            caller: context.address.clone(),
+            address: to.clone(),
+            value,
+            block_number: context.block_number,
+            calldata,
+        };
+
+        match self.run_frame(overlay, &child, depth + 1, meter, logs, touched)? {
+            Ok(value) => {
+                touched.insert(context.address.clone());
+                touched.insert(to);
+                Ok((true, value))
+            }
+            Err(_) => {
+                overlay.revert_to(checkpoint)?;
+                Ok((false, None))
+            }
+        }
+    }
+
+    /// Move balance between accounts
+    fn transfer(
+        overlay: &mut StateOverlay<E>,
+        from: &AccountId,
+        to: &AccountId,
+        amount: u64,
+    ) -> Result<(), Halt> {
+        if amount == 0 || from == to {
+            return Ok(());
+        }
+
+        let mut sender = overlay.get_account(from)
+            .ok_or_else(|| Halt::Revert("Sender account not found".to_string()))?;
+        let mut receiver = overlay.get_account(to)
+            .ok_or_else(|| Halt::Revert("Receiver account not found".to_string()))?;
+
+        sender.balance = sender.balance.checked_sub(amount)
+            .ok_or_else(|| Halt::Revert("Insufficient balance".to_string()))?;
+        receiver.balance = receiver.balance.checked_add(amount)
+            .ok_or_else(|| Halt::Revert("Balance overflow".to_string()))?;
+
+        overlay.set_account(sender);
+        overlay.set_account(receiver);
+        Ok(())
+    }
+
+    /// Mark which code offsets begin an instruction
+    fn instruction_starts(code: &[u8]) -> Result<Vec<bool>, StateError> {
+        let mut starts = vec![false; code.len()];
+        let mut pc = 0usize;
+
+        while pc < code.len() {
+            starts[pc] = true;
+            let immediate = match code[pc] {
+                PUSH => 8,
+                DUP | SWAP | LOG => 1,
+                ADDR => 1 + *code.get(pc + 1).unwrap_or(&0) as usize,
+                STOP | POP | ADD | SUB | MUL | EQ | LT | ISZERO | JUMP | JUMPI | SLOAD
+                | SSTORE | CALLER | ADDRESS | CALLVALUE | BLOCKNUMBER | CALLDATALOAD
+                | CALLDATASIZE | BALANCE | TRANSFER | CALL | RETURN | REVERT => 0,
+                op => {
+                    return Err(StateError::ExecutionError(format!(
+                        "Invalid opcode 0x{:02x} at {}", op, pc
+                    )))
+                }
+            };
+
+            if pc + 1 + immediate > code.len() {
+                return Err(StateError::ExecutionError(format!(
+                    "Truncated immediate at {}", pc
+                )));
+            }
+            pc += 1 + immediate;
+        }
+
+        Ok(starts)
+    }
+
+    /// Computation cost of instruction
+    fn op_cost(op: u8, code: &[u8], pc: usize) -> u64 {
+        match op {
+            STOP | RETURN | REVERT => 0,
+            SLOAD => 50,
+            SSTORE => 200,
+            BALANCE => 20,
+            TRANSFER => 100,
+            LOG => 100 + 50 * *code.get(pc).unwrap_or(&0) as u64,
+            CALL => 700,
+            JUMP | JUMPI => 8,
+            ADDR => 3 + *code.get(pc).unwrap_or(&0) as u64 / 8,
+            _ => 3,
+        }
+    }
+
+    /// Push word, enforcing stack limit
+    fn push(&self, stack: &mut Vec<E::Fr>, value: E::Fr) -> Result<(), Halt> {
+        if stack.len() >= self.config.max_stack {
+            return Err(Halt::Revert("Stack overflow".to_string()));
+        }
+        stack.push(value);
+        Ok(())
+    }
+
+    /// Pop word
+    fn pop(stack: &mut Vec<E::Fr>) -> Result<E::Fr, Halt> {
+        stack.pop().ok_or_else(|| Halt::Revert("Stack underflow".to_string()))
+    }
+
+    /// Convert word to u64, reverting if it does not fit
+    fn to_u64(word: &E::Fr) -> Result<u64, Halt> {
+        let repr = word.into_repr();
+        let limbs = repr.as_ref();
+        if limbs[1..].iter().any(|limb| *limb != 0) {
+            return Err(Halt::Revert("Word out of range".to_string()));
+        }
+        Ok(limbs[0])
+    }
+
+    /// Resolve address table index
+    fn address<'b>(addresses: &'b [AccountId], word: &E::Fr) -> Result<&'b AccountId, Halt> {
+        let index = Self::to_u64(word)? as usize;
+        addresses.get(index).ok_or_else(|| Halt::Revert("Invalid address".to_string()))
+    }
+
+    /// Boolean as word
+    fn bool_word(value: bool) -> E::Fr {
+        if value {
+            E::Fr::one()
+        } else {
+            E::Fr::zero()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Account, State};
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    fn push(code: &mut Vec<u8>, value: u64) {
+        code.push(PUSH);
+        code.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn setup_contract(state: &mut State<Bls12_381>, id: u8, code: Vec<u8>) -> AccountId {
+        let contract_id = AccountId(vec![id]);
+        let code_hash = Fr::from(id as u64);
+        state.set_code(code_hash, code);
+        state.set_account(Account::new_contract(
+            contract_id.clone(),
+            code_hash,
+            Bls12_381::G1Projective::prime_subgroup_generator(),
+        ));
+        contract_id
+    }
+
+    fn context(address: AccountId) -> CallContext {
+        CallContext {
+            caller: AccountId(vec![0]),
+            address,
+            value: 0,
+            block_number: 7,
+            calldata: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_counter_contract() {
+        // slot[0] += 1; log(data = block number, topic = 42)
+        let mut code = Vec::new();
+        push(&mut code, 0);
+        code.push(SLOAD);
+        push(&mut code, 1);
+        code.push(ADD);
+        push(&mut code, 0);
+        code.push(SSTORE);
+        code.push(BLOCKNUMBER);
+        push(&mut code, 42);
+        code.extend_from_slice(&[LOG, 1, STOP]);
+
+        let mut state = State::<Bls12_381>::new();
+        let contract_id = setup_contract(&mut state, 1, code);
+        let vm = ContractVm::<Bls12_381>::new(VmConfig::default());
+
+        let mut overlay = StateOverlay::new(&state);
+        let outcome = vm.call(&mut overlay, &context(contract_id.clone()), 10_000).unwrap();
+
+        assert!(outcome.success);
+        assert!(outcome.computation_used > 0);
+        assert_eq!(outcome.logs.len(), 1);
+        assert_eq!(outcome.logs[0].topics, vec![Fr::from(42u64)]);
+        let contract = overlay.get_account(&contract_id).unwrap();
+        assert_eq!(contract.get_storage(&Fr::from(0u64)), Some(Fr::from(1u64)));
+    }
+
+    #[test]
+    fn test_revert_discards_changes() {
+        let mut code = Vec::new();
+        push(&mut code, 5);
+        push(&mut code, 0);
+        code.extend_from_slice(&[SSTORE, REVERT]);
+
+        let mut state = State::<Bls12_381>::new();
+        let contract_id = setup_contract(&mut state, 1, code);
+        let vm = ContractVm::<Bls12_381>::new(VmConfig::default());
+
+        let mut overlay = StateOverlay::new(&state);
+        let outcome = vm.call(&mut overlay, &context(contract_id.clone()), 10_000).unwrap();
+
+        assert!(!outcome.success);
+        assert!(overlay.get_account(&contract_id).unwrap().get_storage(&Fr::from(0u64)).is_none());
+    }
+
+    #[test]
+    fn test_out_of_computation() {
+        // Infinite loop
+        let mut code = Vec::new();
+        push(&mut code, 0);
+        code.push(JUMP);
+
+        let mut state = State::<Bls12_381>::new();
+        let contract_id = setup_contract(&mut state, 1, code);
+        let vm = ContractVm::<Bls12_381>::new(VmConfig::default());
+
+        let mut overlay = StateOverlay::new(&state);
+        let outcome = vm.call(&mut overlay, &context(contract_id), 1_000).unwrap();
+
+        assert!(!outcome.success);
+        assert_eq!(outcome.computation_used, 1_000);
+    }
+
+    #[test]
+    fn test_cross_contract_call() {
+        // Callee returns calldata word 0 + 1
+        let mut callee = Vec::new();
+        push(&mut callee, 0);
+        callee.push(CALLDATALOAD);
+        push(&mut callee, 1);
+        callee.extend_from_slice(&[ADD, RETURN]);
+
+        // Caller calls callee with argument 41 and returns the result
+        let mut caller = Vec::new();
+        push(&mut caller, 41);
+        push(&mut caller, 1); // argc
+        push(&mut caller, 0); // value
+        caller.extend_from_slice(&[ADDR, 1, 2, CALL, POP, RETURN]);
+
+        let mut state = State::<Bls12_381>::new();
+        setup_contract(&mut state, 2, callee);
+        let caller_id = setup_contract(&mut state, 1, caller);
+        let vm = ContractVm::<Bls12_381>::new(VmConfig::default());
+
+        let mut overlay = StateOverlay::new(&state);
+        let outcome = vm.call(&mut overlay, &context(caller_id), 10_000).unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.return_value, Some(Fr::from(42u64)));
+    }
+
+    #[test]
+    fn test_transfer_rejects_oversized_amounts() {
+        for amount in [u64::MAX, 1 << 63] {
+            // Transfer `amount` to account 2
+            let mut code = Vec::new();
+            push(&mut code, amount);
+            code.extend_from_slice(&[ADDR, 1, 2, TRANSFER, STOP]);
+
+            let mut state = State::<Bls12_381>::new();
+            let contract_id = setup_contract(&mut state, 1, code);
+            let mut contract = state.get_account(&contract_id).unwrap();
+            contract.balance = 10;
+            state.set_account(contract);
+            let mut receiver = Account::new(
+                AccountId(vec![2]),
+                Bls12_381::G1Projective::prime_subgroup_generator(),
+            );
+            receiver.balance = 100;
+            state.set_account(receiver);
+            let vm = ContractVm::<Bls12_381>::new(VmConfig::default());
+
+            let mut overlay = StateOverlay::new(&state);
+            let outcome = vm.call(&mut overlay, &context(contract_id.clone()), 10_000).unwrap();
+
+            assert!(!outcome.success);
+            assert_eq!(overlay.get_account(&contract_id).unwrap().balance, 10);
+            assert_eq!(overlay.get_account(&AccountId(vec![2])).unwrap().balance, 100);
+        }
+    }
+
+    #[test]
+    fn test_validate_code() {
+        assert!(ContractVm::<Bls12_381>::validate_code(&[PUSH, 0, 0]).is_err());
+        assert!(ContractVm::<Bls12_381>::validate_code(&[0xff]).is_err());
+        assert!(ContractVm::<Bls12_381>::validate_code(&[ADDR, 1, 9, STOP]).is_ok());
+    }
+}
@@ -44,8 +44,8 @@ func TestTransactionHandling(t *testing.T) {
 	// Test transaction
 	tx := &types.Transaction{
 		Hash:  [32]byte{1},
-		From:  [20]byte{1},
-		To:    [20]byte{2},
+		From:  []byte{1},
+		To:    []byte{2},
 		Value: 1000,
 		Nonce: 0,
 	}
@@ -181,9 +181,9 @@ func TestBatchValidation(t *testing.T) {
 
 	// Create transactions with dependencies
 	txs := []*types.Transaction{
-		{Hash: [32]byte{1}, From: [20]byte{1}, Nonce: 0},
-		{Hash: [32]byte{2}, From: [20]byte{1}, Nonce: 1},
-		{Hash: [32]byte{3}, From: [20]byte{1}, Nonce: 2},
+		{Hash: [32]byte{1}, From: []byte{1}, Nonce: 0},
+		{Hash: [32]byte{2}, From: []byte{1}, Nonce: 1},
+		{Hash: [32]byte{3}, From: []byte{1}, Nonce: 2},
 	}
 
 	// Process transactions in reverse order
@@ -212,7 +212,7 @@ func (p *TransactionPool) Stop() {
 
 func (p *TransactionPool) updateIndexes(tx *types.Transaction, hash string) {
 	// Update nonce index
-	from := string(tx.From[:])
+	from := string(tx.From)
 	if txs, exists := p.byNonce[from]; exists {
 		p.byNonce[from] = append(txs, hash)
 		// Sort by nonce
@@ -235,7 +235,7 @@ func (p *TransactionPool) updateIndexes(tx *types.Transaction, hash string) {
 
 func (p *TransactionPool) removeFromIndexes(tx *types.Transaction, hash string) {
 	// Remove from nonce index
-	from := string(tx.From[:])
+	from := string(tx.From)
 	if txs, exists := p.byNonce[from]; exists {
 		for i, h := range txs {
 			if h == hash {
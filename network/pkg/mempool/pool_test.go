@@ -16,12 +16,14 @@ func createTestTransaction(nonce uint64) *types.Transaction {
 	tx := &types.Transaction{
 		Nonce: nonce,
 		Value: 1000,
+		From:  make([]byte, 20),
+		To:    make([]byte, 20),
 	}
 
 	// Generate random hash
 	rand.Read(tx.Hash[:])
-	rand.Read(tx.From[:])
-	rand.Read(tx.To[:])
+	rand.Read(tx.From)
+	rand.Read(tx.To)
 
 	// Add mock computation proof
 	tx.ComputationProof = []byte{1, 2, 3, 4}
@@ -196,7 +198,7 @@ func TestGetPendingTransactions(t *testing.T) {
 
 func TestTransactionsByAddress(t *testing.T) {
 	pool, _ := NewTransactionPool(DefaultPoolConfig())
-	addr := [20]byte{1, 2, 3}
+	addr := []byte{1, 2, 3}
 
 	// Add transactions for specific address
 	txCount := 5
@@ -207,7 +209,7 @@ func TestTransactionsByAddress(t *testing.T) {
 	}
 
 	// Get transactions by address
-	txs := pool.GetTransactionsByAddress(string(addr[:]))
+	txs := pool.GetTransactionsByAddress(string(addr))
 	assert.Equal(t, txCount, len(txs))
 
 	// Verify nonce sequence
@@ -78,7 +78,7 @@ func (v *TransactionValidator) validateBasics(tx *types.Transaction) error {
 	}
 
 	// Check addresses
-	if tx.From == [20]byte{} {
+	if len(tx.From) == 0 {
 		return errors.New("missing sender address")
 	}
 
@@ -93,7 +93,7 @@ func (v *TransactionValidator) validateBasics(tx *types.Transaction) error {
 // Nonce validation
 func (v *TransactionValidator) validateNonce(tx *types.Transaction) error {
 	v.nonceMgr.mu.RLock()
-	currentNonce, exists := v.nonceMgr.nonces[string(tx.From[:])]
+	currentNonce, exists := v.nonceMgr.nonces[string(tx.From)]
 	v.nonceMgr.mu.RUnlock()
 
 	if !exists {
@@ -0,0 +1,295 @@
+package types
+
+import (
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"math"
+	"math/big"
+)
+
+// TxWireVersion is the version of the canonical transaction encoding
+const TxWireVersion = 1
+
+// SignatureSize is the size of an encoded signature: a compressed
+// BLS12-381 G1 point followed by a little-endian scalar
+const SignatureSize = 48 + 32
+
+// TxType represents the transaction type
+type TxType uint8
+
+const (
+	TxTypeTransfer TxType = iota
+	TxTypeDeploy
+	TxTypeCall
+	TxTypeCreateAccount
+	TxTypeUpdateAccount
+)
+
+var (
+	ErrTxTruncated    = errors.New("transaction is truncated")
+	ErrTxTrailing     = errors.New("trailing bytes after transaction")
+	ErrTxNonCanonical = errors.New("non-canonical transaction encoding")
+)
+
+// BLS12-381 scalar field modulus
+var scalarModulus, _ = new(big.Int).SetString(
+	"73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001", 16)
+
+// EncodeForSigning returns the canonical encoding without the signature
+// and computation proof. It matches the core Transaction::encode_for_signing.
+func (tx *Transaction) EncodeForSigning() ([]byte, error) {
+	if tx.Type > TxTypeUpdateAccount {
+		return nil, fmt.Errorf("unknown transaction type %d", tx.Type)
+	}
+	if len(tx.From) == 0 {
+		return nil, errors.New("sender is empty")
+	}
+	if tx.To != nil && len(tx.To) == 0 {
+		return nil, errors.New("receiver is empty")
+	}
+
+	b := []byte{TxWireVersion}
+	b = binary.BigEndian.AppendUint64(b, tx.ChainID)
+	b = append(b, byte(tx.Type))
+	b = binary.BigEndian.AppendUint64(b, tx.Nonce)
+
+	var err error
+	if b, err = appendBytes(b, tx.From); err != nil {
+		return nil, err
+	}
+	if tx.To != nil {
+		b = append(b, 1)
+		if b, err = appendBytes(b, tx.To); err != nil {
+			return nil, err
+		}
+	} else {
+		b = append(b, 0)
+	}
+
+	b = binary.BigEndian.AppendUint64(b, tx.Value)
+	b = binary.BigEndian.AppendUint64(b, tx.GasPrice)
+	b = binary.BigEndian.AppendUint64(b, tx.GasLimit)
+	return appendBytes(b, tx.Data)
+}
+
+// Encode returns the canonical wire encoding of the transaction
+func (tx *Transaction) Encode() ([]byte, error) {
+	b, err := tx.EncodeForSigning()
+	if err != nil {
+		return nil, err
+	}
+
+	switch {
+	case tx.Signature != nil && len(tx.Cosignatures) > 0:
+		return nil, errors.New("transaction has both a signature and cosignatures")
+	case tx.Signature != nil:
+		if err := checkSignature(tx.Signature); err != nil {
+			return nil, err
+		}
+		b = append(b, 1)
+		b = append(b, tx.Signature...)
+	case len(tx.Cosignatures) > 0:
+		b = append(b, 2)
+		b = binary.BigEndian.AppendUint32(b, uint32(len(tx.Cosignatures)))
+		for i, c := range tx.Cosignatures {
+			if i > 0 && tx.Cosignatures[i-1].Signer >= c.Signer {
+				return nil, ErrTxNonCanonical
+			}
+			if err := checkSignature(c.Signature); err != nil {
+				return nil, err
+			}
+			b = binary.BigEndian.AppendUint32(b, c.Signer)
+			b = append(b, c.Signature...)
+		}
+	default:
+		b = append(b, 0)
+	}
+
+	if tx.ComputationProof != nil {
+		b = append(b, 1)
+		return appendBytes(b, tx.ComputationProof)
+	}
+	return append(b, 0), nil
+}
+
+// Size returns the size of the wire encoding, or math.MaxInt64 if the
+// transaction cannot be encoded
+func (tx *Transaction) Size() int64 {
+	b, err := tx.Encode()
+	if err != nil {
+		return math.MaxInt64
+	}
+	return int64(len(b))
+}
+
+// DecodeTransaction decodes a transaction from its canonical wire encoding.
+// Trailing bytes and non-canonical encodings are rejected.
+func DecodeTransaction(data []byte) (*Transaction, error) {
+	r := &reader{buf: data}
+	tx := &Transaction{}
+
+	if version := r.u8(); r.err == nil && version != TxWireVersion {
+		return nil, fmt.Errorf("unsupported transaction version %d", version)
+	}
+	tx.ChainID = r.u64()
+	tx.Type = TxType(r.u8())
+	if r.err == nil && tx.Type > TxTypeUpdateAccount {
+		return nil, fmt.Errorf("unknown transaction type %d", tx.Type)
+	}
+	tx.Nonce = r.u64()
+
+	tx.From = r.bytes()
+	if r.err == nil && len(tx.From) == 0 {
+		return nil, errors.New("sender is empty")
+	}
+	if r.flag() {
+		tx.To = r.bytes()
+		if r.err == nil && len(tx.To) == 0 {
+			return nil, errors.New("receiver is empty")
+		}
+	}
+
+	tx.Value = r.u64()
+	tx.GasPrice = r.u64()
+	tx.GasLimit = r.u64()
+	tx.Data = r.bytes()
+
+	switch r.u8() {
+	case 0:
+	case 1:
+		tx.Signature = r.signature()
+	case 2:
+		count := r.u32()
+		if r.err == nil && count == 0 {
+			return nil, ErrTxNonCanonical
+		}
+		for i := uint32(0); i < count && r.err == nil; i++ {
+			c := Cosignature{Signer: r.u32(), Signature: r.signature()}
+			if n := len(tx.Cosignatures); n > 0 && tx.Cosignatures[n-1].Signer >= c.Signer {
+				return nil, ErrTxNonCanonical
+			}
+			tx.Cosignatures = append(tx.Cosignatures, c)
+		}
+	default:
+		if r.err == nil {
+			return nil, ErrTxNonCanonical
+		}
+	}
+	if r.flag() {
+		tx.ComputationProof = r.bytes()
+	}
+
+	if r.err != nil {
+		return nil, r.err
+	}
+	if len(r.buf) != 0 {
+		return nil, ErrTxTrailing
+	}
+	return tx, nil
+}
+
+// checkSignature rejects signatures that are not compressed points with
+// a reduced scalar. Curve membership is checked by VerifySignature.
+func checkSignature(sig []byte) error {
+	if len(sig) != SignatureSize {
+		return fmt.Errorf("signature must be %d bytes", SignatureSize)
+	}
+	if sig[0]&0x80 == 0 {
+		return ErrTxNonCanonical
+	}
+
+	// Scalar is little-endian
+	s := make([]byte, 32)
+	for i, v := range sig[48:] {
+		s[31-i] = v
+	}
+	if new(big.Int).SetBytes(s).Cmp(scalarModulus) >= 0 {
+		return ErrTxNonCanonical
+	}
+	return nil
+}
+
+func appendBytes(b, v []byte) ([]byte, error) {
+	if uint64(len(v)) > math.MaxUint32 {
+		return nil, errors.New("field too long")
+	}
+	b = binary.BigEndian.AppendUint32(b, uint32(len(v)))
+	return append(b, v...), nil
+}
+
+// reader consumes a wire encoding, recording the first error
+type reader struct {
+	buf []byte
+	err error
+}
+
+func (r *reader) take(n int) []byte {
+	if r.err != nil {
+		return nil
+	}
+	if len(r.buf) < n {
+		r.err = ErrTxTruncated
+		return nil
+	}
+	v := r.buf[:n]
+	r.buf = r.buf[n:]
+	return v
+}
+
+func (r *reader) u8() byte {
+	if v := r.take(1); v != nil {
+		return v[0]
+	}
+	return 0
+}
+
+func (r *reader) u32() uint32 {
+	if v := r.take(4); v != nil {
+		return binary.BigEndian.Uint32(v)
+	}
+	return 0
+}
+
+func (r *reader) signature() []byte {
+	v := r.take(SignatureSize)
+	if v == nil {
+		return nil
+	}
+	if err := checkSignature(v); err != nil {
+		r.err = err
+		return nil
+	}
+	return append([]byte{}, v...)
+}
+
+func (r *reader) u64() uint64 {
+	if v := r.take(8); v != nil {
+		return binary.BigEndian.Uint64(v)
+	}
+	return 0
+}
+
+func (r *reader) flag() bool {
+	switch f := r.u8(); {
+	case r.err != nil:
+		return false
+	case f > 1:
+		r.err = ErrTxNonCanonical
+		return false
+	default:
+		return f == 1
+	}
+}
+
+func (r *reader) bytes() []byte {
+	n := r.u32()
+	if r.err != nil {
+		return nil
+	}
+	if uint64(n) > uint64(len(r.buf)) {
+		r.err = ErrTxTruncated
+		return nil
+	}
+	return append([]byte{}, r.take(int(n))...)
+}
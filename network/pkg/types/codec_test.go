@@ -0,0 +1,104 @@
+package types
+
+import (
+	"encoding/hex"
+	"encoding/json"
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+// Vectors shared with the core
+const wireVectorsPath = "../../../tools/tests/vectors/transaction.json"
+
+type wireVectors struct {
+	Valid []struct {
+		Name             string  `json:"name"`
+		ChainID          uint64  `json:"chain_id"`
+		Type             uint8   `json:"type"`
+		Nonce            uint64  `json:"nonce"`
+		From             string  `json:"from"`
+		To               *string `json:"to"`
+		Value            uint64  `json:"value"`
+		GasPrice         uint64  `json:"gas_price"`
+		GasLimit         uint64  `json:"gas_limit"`
+		Data             string  `json:"data"`
+		Signature        *string `json:"signature"`
+		Cosignatures     []struct {
+			Signer    uint32 `json:"signer"`
+			Signature string `json:"signature"`
+		} `json:"cosignatures"`
+		ComputationProof *string `json:"computation_proof"`
+		Encoded          string  `json:"encoded"`
+	} `json:"valid"`
+	Invalid []struct {
+		Name    string `json:"name"`
+		Encoded string `json:"encoded"`
+	} `json:"invalid"`
+}
+
+func mustHex(t *testing.T, s *string) []byte {
+	if s == nil {
+		return nil
+	}
+	b, err := hex.DecodeString(*s)
+	require.NoError(t, err)
+	return b
+}
+
+func TestTransactionWireVectors(t *testing.T) {
+	raw, err := os.ReadFile(wireVectorsPath)
+	require.NoError(t, err)
+
+	var vectors wireVectors
+	require.NoError(t, json.Unmarshal(raw, &vectors))
+
+	for _, v := range vectors.Valid {
+		t.Run(v.Name, func(t *testing.T) {
+			expected := &Transaction{
+				ChainID:          v.ChainID,
+				Type:             TxType(v.Type),
+				From:             mustHex(t, &v.From),
+				To:               mustHex(t, v.To),
+				Value:            v.Value,
+				Nonce:            v.Nonce,
+				GasPrice:         v.GasPrice,
+				GasLimit:         v.GasLimit,
+				Data:             mustHex(t, &v.Data),
+				Signature:        mustHex(t, v.Signature),
+				ComputationProof: mustHex(t, v.ComputationProof),
+			}
+			for _, c := range v.Cosignatures {
+				expected.Cosignatures = append(expected.Cosignatures, Cosignature{
+					Signer:    c.Signer,
+					Signature: mustHex(t, &c.Signature),
+				})
+			}
+			encoded := mustHex(t, &v.Encoded)
+
+			got, err := expected.Encode()
+			require.NoError(t, err)
+			assert.Equal(t, encoded, got)
+
+			decoded, err := DecodeTransaction(encoded)
+			require.NoError(t, err)
+			assert.Equal(t, expected, decoded)
+			assert.Equal(t, int64(len(encoded)), decoded.Size())
+		})
+	}
+
+	for _, v := range vectors.Invalid {
+		t.Run(v.Name, func(t *testing.T) {
+			_, err := DecodeTransaction(mustHex(t, &v.Encoded))
+			assert.Error(t, err)
+		})
+	}
+}
+
+func TestTransactionEncodeRejectsEmptyReceiver(t *testing.T) {
+	tx := &Transaction{From: []byte{1}, To: []byte{}}
+	_, err := tx.Encode()
+	assert.Error(t, err)
+}
@@ -0,0 +1,163 @@
+package types
+
+import (
+	"crypto/sha256"
+	"errors"
+	"math/big"
+
+	"github.com/ethereum/go-ethereum/crypto/bls12381"
+)
+
+// PublicKeySize is the size of a compressed BLS12-381 G1 public key
+const PublicKeySize = 48
+
+// signatureChallengeTag matches the core DomainTag::SIGNATURE_CHALLENGE
+const signatureChallengeTag = "APORIA-ZERO-V01-SIGNATURE-CHALLENGE"
+
+// ErrInvalidPoint is returned for encodings that are not a compressed
+// point of the prime order subgroup
+var ErrInvalidPoint = errors.New("invalid curve point")
+
+// BLS12-381 base field modulus
+var baseModulus, _ = new(big.Int).SetString(
+	"1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab", 16)
+
+// VerifySignature checks a signature made by the core SignatureScheme: a
+// Schnorr signature (R, s) is valid if s·G = R + e·P, where
+// e = H(R || P || message) over compressed points. Non-canonical keys and
+// signatures are rejected with an error, as the core rejects them.
+func VerifySignature(publicKey, message, signature []byte) (bool, error) {
+	if err := checkSignature(signature); err != nil {
+		return false, err
+	}
+
+	g := bls12381.NewG1()
+	key, err := decompressG1(g, publicKey)
+	if err != nil {
+		return false, err
+	}
+	r, err := decompressG1(g, signature[:48])
+	if err != nil {
+		return false, err
+	}
+	if g.IsZero(r) {
+		return false, ErrInvalidPoint
+	}
+
+	// Any signature satisfies the equation under the identity key
+	if g.IsZero(key) {
+		return false, nil
+	}
+
+	transcript := make([]byte, 0, 2*PublicKeySize+len(message))
+	transcript = append(transcript, signature[:48]...)
+	transcript = append(transcript, publicKey...)
+	transcript = append(transcript, message...)
+	e := hashToScalar(transcript, signatureChallengeTag)
+
+	// Scalar is little-endian
+	sb := make([]byte, 32)
+	for i, v := range signature[48:] {
+		sb[31-i] = v
+	}
+	s := new(big.Int).SetBytes(sb)
+
+	left := g.MulScalar(g.New(), g.One(), s)
+	right := g.MulScalar(g.New(), key, e)
+	g.Add(right, right, r)
+	return g.Equal(left, right), nil
+}
+
+// decompressG1 decodes a compressed G1 point, requiring the canonical
+// encoding and membership of the prime order subgroup
+func decompressG1(g *bls12381.G1, in []byte) (*bls12381.PointG1, error) {
+	if len(in) != PublicKeySize || in[0]&0x80 == 0 {
+		return nil, ErrInvalidPoint
+	}
+
+	// Identity has no other bits set
+	if in[0]&0x40 != 0 {
+		if in[0] != 0xc0 {
+			return nil, ErrInvalidPoint
+		}
+		for _, v := range in[1:] {
+			if v != 0 {
+				return nil, ErrInvalidPoint
+			}
+		}
+		return g.Zero(), nil
+	}
+
+	largest := in[0]&0x20 != 0
+	xb := append([]byte{in[0] & 0x1f}, in[1:]...)
+	x := new(big.Int).SetBytes(xb)
+	if x.Cmp(baseModulus) >= 0 {
+		return nil, ErrInvalidPoint
+	}
+
+	// y^2 = x^3 + 4, and p = 3 mod 4 so a square root is y2^((p+1)/4)
+	y2 := new(big.Int).Exp(x, big.NewInt(3), baseModulus)
+	y2.Add(y2, big.NewInt(4)).Mod(y2, baseModulus)
+	exp := new(big.Int).Add(baseModulus, big.NewInt(1))
+	exp.Rsh(exp, 2)
+	y := new(big.Int).Exp(y2, exp, baseModulus)
+	if new(big.Int).Exp(y, big.NewInt(2), baseModulus).Cmp(y2) != 0 {
+		return nil, ErrInvalidPoint
+	}
+
+	half := new(big.Int).Rsh(baseModulus, 1)
+	if (y.Cmp(half) > 0) != largest {
+		y.Sub(baseModulus, y)
+	}
+
+	uncompressed := make([]byte, 2*PublicKeySize)
+	x.FillBytes(uncompressed[:PublicKeySize])
+	y.FillBytes(uncompressed[PublicKeySize:])
+	p, err := g.FromBytes(uncompressed)
+	if err != nil || !g.InCorrectSubgroup(p) {
+		return nil, ErrInvalidPoint
+	}
+	return p, nil
+}
+
+// hashToScalar matches the core hash_to_scalar: RFC 9380 hash_to_field
+// with expand_message_xmd over SHA-256, reducing 48 bytes modulo the
+// scalar field order
+func hashToScalar(msg []byte, tag string) *big.Int {
+	e := new(big.Int).SetBytes(expandMessageXMD(msg, tag, 48))
+	return e.Mod(e, scalarModulus)
+}
+
+// expandMessageXMD implements RFC 9380 expand_message_xmd with SHA-256
+// for outputs of at most 255 blocks and tags of at most 255 bytes
+func expandMessageXMD(msg []byte, tag string, length int) []byte {
+	tagPrime := append([]byte(tag), byte(len(tag)))
+
+	h := sha256.New()
+	h.Write(make([]byte, sha256.BlockSize))
+	h.Write(msg)
+	h.Write([]byte{byte(length >> 8), byte(length), 0})
+	h.Write(tagPrime)
+	b0 := h.Sum(nil)
+
+	h.Reset()
+	h.Write(b0)
+	h.Write([]byte{1})
+	h.Write(tagPrime)
+	bi := h.Sum(nil)
+
+	out := append([]byte{}, bi...)
+	for i := 2; len(out) < length; i++ {
+		mixed := make([]byte, sha256.Size)
+		for j := range mixed {
+			mixed[j] = b0[j] ^ bi[j]
+		}
+		h.Reset()
+		h.Write(mixed)
+		h.Write([]byte{byte(i)})
+		h.Write(tagPrime)
+		bi = h.Sum(nil)
+		out = append(out, bi...)
+	}
+	return out[:length]
+}
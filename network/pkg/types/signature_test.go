@@ -0,0 +1,55 @@
+package types
+
+import (
+	"encoding/hex"
+	"encoding/json"
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+// Vectors shared with the core
+const signatureVectorsPath = "../../../tools/tests/vectors/signature.json"
+
+type signatureVector struct {
+	Name      string `json:"name"`
+	PublicKey string `json:"public_key"`
+	Message   string `json:"message"`
+	Signature string `json:"signature"`
+}
+
+func TestSignatureVectors(t *testing.T) {
+	raw, err := os.ReadFile(signatureVectorsPath)
+	require.NoError(t, err)
+
+	var vectors struct {
+		Valid   []signatureVector `json:"valid"`
+		Invalid []signatureVector `json:"invalid"`
+	}
+	require.NoError(t, json.Unmarshal(raw, &vectors))
+	require.NotEmpty(t, vectors.Valid)
+
+	for _, v := range vectors.Valid {
+		t.Run(v.Name, func(t *testing.T) {
+			ok, err := VerifySignature(mustHex(t, &v.PublicKey), mustHex(t, &v.Message), mustHex(t, &v.Signature))
+			require.NoError(t, err)
+			assert.True(t, ok)
+		})
+	}
+
+	for _, v := range vectors.Invalid {
+		t.Run(v.Name, func(t *testing.T) {
+			ok, _ := VerifySignature(mustHex(t, &v.PublicKey), mustHex(t, &v.Message), mustHex(t, &v.Signature))
+			assert.False(t, ok)
+		})
+	}
+}
+
+func TestExpandMessageXMD(t *testing.T) {
+	// RFC 9380, appendix K.1
+	const tag = "QUUX-V01-CS02-with-expander-SHA256-128"
+	expected := "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+	assert.Equal(t, expected, hex.EncodeToString(expandMessageXMD([]byte("abc"), tag, 0x20)))
+}
@@ -40,15 +40,30 @@ type Peer struct {
 	Capabilities []string
 }
 
+// Cosignature is a signature by one signer of a weighted account policy
+type Cosignature struct {
+	Signer    uint32
+	Signature []byte
+}
+
 // Transaction represents a network transaction
+//
+// Accounts are variable-length IDs matching the core AccountId. A nil To
+// means no receiver, a nil Signature or ComputationProof means absent.
+// Weighted policy accounts sign with Cosignatures instead of Signature.
 type Transaction struct {
 	Hash             [32]byte
-	From             [20]byte
-	To               [20]byte
+	ChainID          uint64
+	Type             TxType
+	From             []byte
+	To               []byte
 	Value            uint64
 	Nonce            uint64
+	GasPrice         uint64
+	GasLimit         uint64
 	Data             []byte
 	Signature        []byte
+	Cosignatures     []Cosignature
 	ComputationProof []byte
 }
 
@@ -138,8 +153,8 @@ func (tx *Transaction) Verify() bool {
 func (tx *Transaction) String() string {
 	return fmt.Sprintf("TX(hash=%s, from=%s, to=%s, value=%d)",
 		hex.EncodeToString(tx.Hash[:]),
-		hex.EncodeToString(tx.From[:]),
-		hex.EncodeToString(tx.To[:]),
+		hex.EncodeToString(tx.From),
+		hex.EncodeToString(tx.To),
 		tx.Value,
 	)
 }